@@ -1,5 +1,6 @@
-use ethers::types::H160;
+use ethers::{types::H160, utils::hex};
 use heliosphere::core::Address;
+use serde::{Deserialize, Deserializer};
 
 use hyperlane_core::H256;
 
@@ -8,12 +9,48 @@ use crate::HyperlaneTronError;
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct TronAddress(Address);
 
+impl TronAddress {
+    /// Parses an address in any of the formats returned by nodes: base58 (`T...`),
+    /// hex with the `41` prefix, or the right-aligned 20 bytes of EVM encodings as hex,
+    /// each of the hex ones with or without `0x`.
+    pub(crate) fn parse_any(s: &str) -> Result<Self, HyperlaneTronError> {
+        let s = s.trim();
+        let unprefixed = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+
+        let bytes = match hex::decode(unprefixed) {
+            Ok(bytes) if bytes.len() == 20 => [&[0x41], bytes.as_slice()].concat(),
+            Ok(bytes) => bytes,
+            Err(_) => s.parse::<Address>()?.as_bytes().to_vec(),
+        };
+        let bytes =
+            <[u8; 21]>::try_from(bytes).map_err(|_| heliosphere_core::Error::InvalidAddress)?;
+
+        Ok(TronAddress(Address::new(bytes)?))
+    }
+}
+
+impl<'de> Deserialize<'de> for TronAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        TronAddress::parse_any(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<H160> for TronAddress {
     fn from(value: H160) -> Self {
         TronAddress(Address::from(value))
     }
 }
 
+impl From<Address> for TronAddress {
+    fn from(value: Address) -> Self {
+        TronAddress(value)
+    }
+}
+
 impl From<TronAddress> for H160 {
     fn from(value: TronAddress) -> Self {
         H160::from(value.0)
@@ -34,8 +71,9 @@ impl TryFrom<H256> for TronAddress {
 
 impl From<TronAddress> for H256 {
     fn from(value: TronAddress) -> Self {
+        // Skip the 0x41 prefix of Tron addresses
         let mut bytes = [0u8; 32];
-        bytes[12..].copy_from_slice(value.0.as_bytes());
+        bytes[12..].copy_from_slice(&value.0.as_bytes()[1..]);
 
         H256::from(bytes)
     }
@@ -46,3 +84,53 @@ impl AsRef<Address> for TronAddress {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_h256_round_trip() {
+        let h256 = H256::from_low_u64_be(0xdeadbeef);
+
+        let address = TronAddress::try_from(h256).unwrap();
+
+        assert_eq!(address.as_ref().as_bytes()[0], 0x41);
+        assert_eq!(H256::from(address), h256);
+        assert_eq!(H160::from(address), H160::from_low_u64_be(0xdeadbeef));
+    }
+
+    #[test]
+    fn test_parse_any() {
+        let expected = H256::from(H160::from_slice(
+            &hex::decode("a614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap(),
+        ));
+
+        for s in [
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t",
+            "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            "0x41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            "0xa614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            "a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            " TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t\n",
+        ] {
+            let address = TronAddress::parse_any(s).unwrap();
+            assert_eq!(H256::from(address), expected, "{s}");
+        }
+
+        let address: TronAddress =
+            serde_json::from_str("\"41a614f803b6fd780986a42c78ec9c7f77e6ded13c\"").unwrap();
+        assert_eq!(H256::from(address), expected);
+
+        for s in [
+            // Not a Tron prefix
+            "42a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            // Bad checksum
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u",
+            "0xa614f8",
+            "",
+        ] {
+            assert!(TronAddress::parse_any(s).is_err(), "{s}");
+        }
+    }
+}
@@ -1,8 +1,875 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 use url::Url;
 
-/// Tron connection configuration
-#[derive(Debug, Clone)]
+use hyperlane_core::utils::hex_or_base58_to_h256;
+use hyperlane_core::{HyperlaneDomain, HyperlaneDomainType, ReorgPeriod, H256};
+
+use crate::{CircuitBreakerConf, HyperlaneTronError, RetryPolicy, MAX_TX_EXPIRATION};
+
+/// Number of blocks after which a Tron block is solidified (confirmed by 2/3 of the 27 SRs)
+pub const TRON_SOLIDIFICATION_BLOCKS: u32 = 19;
+
+/// Number of log queries of a backfill run concurrently if not configured
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: u32 = 4;
+
+/// Header under which TronGrid expects the API key
+pub const TRON_API_KEY_HEADER: &str = "TRON-PRO-API-KEY";
+
+/// Tron connection configuration, deserializable from the agent config JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ConnectionConf {
     /// Fully qualified string to connect to
     pub url: Url,
+    /// Other nodes of the chain, only queried by [`crate::TronProvider::detect_fork`]
+    /// to check that the node at `url` agrees with them. All other calls go to `url`.
+    #[serde(default)]
+    pub reference_urls: Vec<Url>,
+    /// API key of each reference node, keyed by its URL. Reference nodes without
+    /// an entry are sent `api_key`, so a private node and TronGrid can be mixed.
+    #[serde(default, skip_serializing)]
+    pub reference_api_keys: HashMap<Url, ApiKey>,
+    /// Maximum block range of a single log query. If unset, the range limit is
+    /// discovered from the node's errors.
+    #[serde(default)]
+    pub max_log_range: Option<u32>,
+    /// Maximum number of the log queries a block range is split into that run
+    /// concurrently. Lower it if backfills overwhelm the node.
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: u32,
+    /// Timeout of a single HTTP request to the node, none if unset
+    #[serde(default, rename = "timeoutSecs", with = "serde_duration::opt_secs")]
+    pub timeout: Option<Duration>,
+    /// API key sent with every request, required by TronGrid past its free quota
+    #[serde(default, skip_serializing)]
+    pub api_key: Option<ApiKey>,
+    /// HTTP connection pool settings shared by the RPC clients
+    #[serde(default)]
+    pub connection_pool: ConnectionPoolConf,
+    /// Whether to log the path, request body and response body of the calls to the
+    /// node's HTTP API at trace level, for debugging. Headers, which hold the API
+    /// key, aren't logged.
+    #[serde(default)]
+    pub trace_rpc: bool,
+    /// How failed calls to the node are retried
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// When calls to a failing node are short-circuited
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConf,
+    /// Whether to wait for sent transactions to be confirmed. If disabled, transactions
+    /// are reported as not executed right after being broadcast, and their outcome
+    /// has to be tracked separately, e.g. by polling `delivered` on the mailbox.
+    #[serde(default = "default_wait_for_confirmation")]
+    pub wait_for_confirmation: bool,
+    /// Whether to check that the metadata of a message passes the verification of
+    /// the recipient ISM before processing it. Costs an extra call, but avoids
+    /// spending energy on transactions that would revert.
+    #[serde(default)]
+    pub preflight_verify: bool,
+    /// Whether to check that the recipient of a message is a contract before looking
+    /// up its ISM or processing the message. Costs an extra call, but reports messages
+    /// sent to an account without code clearly rather than as a revert.
+    #[serde(default)]
+    pub check_recipient_is_contract: bool,
+    /// Whether to check that the mailbox isn't paused before processing a message,
+    /// which would revert. Mailboxes without a `paused()` view are never paused.
+    #[serde(default)]
+    pub check_paused: bool,
+    /// Whether to read contract state at the latest finalized block rather than at
+    /// the tip, so as not to act on state that may be reorged away
+    #[serde(default)]
+    pub prefer_finalized_reads: bool,
+    /// Address of a Multicall3 contract, used to batch view calls. Views are read
+    /// one by one if unset.
+    #[serde(default, deserialize_with = "deserialize_opt_address")]
+    pub multicall_address: Option<H256>,
+    /// Account paying for the energy of sent transactions by delegating its staked
+    /// energy to the signer. Tron has no fee delegation: the signer still signs and
+    /// owns transactions, and burns its own TRX for energy beyond the delegated one.
+    /// If set, transactions are only sent while the fee payer delegates energy to the
+    /// signer, so that a lapsed delegation doesn't silently drain the signer.
+    #[serde(default, deserialize_with = "deserialize_opt_address")]
+    pub fee_payer: Option<H256>,
+    /// Whether the signer may burn its TRX for the energy of a transaction that the
+    /// energy delegated by the fee payer doesn't cover, or for bandwidth it lacks. If
+    /// disabled, such transactions aren't sent.
+    #[serde(default = "default_allow_energy_burn")]
+    pub allow_energy_burn: bool,
+    /// Energy price in sun used when the node doesn't report `getEnergyFee` among its
+    /// chain parameters, as on some custom networks. Sending transactions fails
+    /// without one in that case.
+    #[serde(default)]
+    pub fallback_energy_fee: Option<u64>,
+    /// Energy price in sun used in all estimates and fee limits instead of the
+    /// node's `getEnergyFee`, e.g. on test networks or to ride out a fee spike
+    #[serde(default)]
+    pub energy_price_override: Option<u64>,
+    /// How long sent transactions stay valid after being built, up to 24 hours.
+    /// The node's default, usually 60s, applies if unset.
+    #[serde(
+        default,
+        rename = "txExpirationSecs",
+        with = "serde_duration::opt_secs"
+    )]
+    pub tx_expiration: Option<Duration>,
+    /// Energy limit below which sent transactions are never capped, for calls doing
+    /// more work on chain than their estimation captures, e.g. storage-heavy handles
+    #[serde(default)]
+    pub min_energy_limit: Option<u64>,
+    /// Permission of the signer's account that sent transactions are signed under,
+    /// the owner permission if unset. For a signer holding a key of a multisig
+    /// account, the id of a permission the key can sign alone, see
+    /// [`crate::TronProvider::get_account_permissions`].
+    #[serde(default)]
+    pub permission_id: Option<u32>,
+    /// Largest fraction of the signer's TRX balance, in (0, 1], that the fee limit of
+    /// a sent transaction may reach, so that a single transaction can't drain most of
+    /// the account. Fee limits are capped to it on top of the maximum of the chain.
+    #[serde(default)]
+    pub max_fee_fraction_of_balance: Option<f64>,
+    /// Reissuing of sent transactions with a higher fee limit when they run out of
+    /// energy or expire, disabled if unset. Only applies when awaiting confirmations.
+    #[serde(default)]
+    pub fee_limit_bump: Option<FeeLimitBumpConf>,
+    /// Whether messages to the same destination processed concurrently are broadcast
+    /// in nonce order, for recipients expecting them in order. A message waits for the
+    /// ones with lower nonces being processed to be accepted by the node, but not for
+    /// their confirmation.
+    #[serde(default)]
+    pub ordered_submission: bool,
+}
+
+/// API key of a node, left out of `Debug` output so that it doesn't end up in logs
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct ApiKey(String);
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl Deref for ApiKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(<redacted>)")
+    }
+}
+
+/// Addresses as hex or base58, like in the rest of the agent config
+fn deserialize_opt_address<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<H256>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|address| hex_or_base58_to_h256(&address).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn default_max_concurrent_requests() -> u32 {
+    DEFAULT_MAX_CONCURRENT_REQUESTS
+}
+
+fn default_wait_for_confirmation() -> bool {
+    true
+}
+
+fn default_allow_energy_burn() -> bool {
+    true
+}
+
+impl ConnectionConf {
+    /// Configuration of a connection to the node at `url` with the default settings,
+    /// the same as when deserialized without any other option
+    pub fn new(url: Url) -> Self {
+        ConnectionConf {
+            url,
+            reference_urls: vec![],
+            reference_api_keys: HashMap::new(),
+            max_log_range: None,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            timeout: None,
+            api_key: None,
+            connection_pool: Default::default(),
+            trace_rpc: false,
+            retry_policy: Default::default(),
+            circuit_breaker: Default::default(),
+            wait_for_confirmation: default_wait_for_confirmation(),
+            preflight_verify: false,
+            check_recipient_is_contract: false,
+            check_paused: false,
+            prefer_finalized_reads: false,
+            multicall_address: None,
+            fee_payer: None,
+            allow_energy_burn: default_allow_energy_burn(),
+            fallback_energy_fee: None,
+            energy_price_override: None,
+            tx_expiration: None,
+            min_energy_limit: None,
+            permission_id: None,
+            max_fee_fraction_of_balance: None,
+            fee_limit_bump: None,
+            ordered_submission: false,
+        }
+    }
+
+    /// Checks that the url can point at a Tron full node, which serves its HTTP API
+    /// (and the JSON-RPC one) over http or https
+    pub fn validate(&self) -> Result<(), HyperlaneTronError> {
+        for url in std::iter::once(&self.url).chain(&self.reference_urls) {
+            validate_url(url)?;
+        }
+        if self.max_concurrent_requests == 0 {
+            return Err(HyperlaneTronError::InvalidConnectionConf(
+                "max concurrent requests must be at least 1".to_owned(),
+            ));
+        }
+        if let Some(tx_expiration) = self.tx_expiration {
+            if tx_expiration.is_zero() || tx_expiration > MAX_TX_EXPIRATION {
+                return Err(HyperlaneTronError::InvalidConnectionConf(format!(
+                    "transaction expiration of {tx_expiration:?} out of (0, {MAX_TX_EXPIRATION:?}]"
+                )));
+            }
+        }
+        if let Some(fraction) = self.max_fee_fraction_of_balance {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(HyperlaneTronError::InvalidConnectionConf(format!(
+                    "max fee fraction of balance {fraction} out of (0, 1]"
+                )));
+            }
+        }
+        if let Some(bump) = &self.fee_limit_bump {
+            if !bump.multiplier.is_finite() || bump.multiplier <= 1.0 {
+                return Err(HyperlaneTronError::InvalidConnectionConf(format!(
+                    "fee limit bump multiplier {} must be above 1",
+                    bump.multiplier
+                )));
+            }
+        }
+        if let Some(api_key) = &self.api_key {
+            if reqwest::header::HeaderValue::from_str(api_key).is_err() {
+                return Err(HyperlaneTronError::InvalidConnectionConf(
+                    "api key contains characters not allowed in an HTTP header".to_owned(),
+                ));
+            }
+        }
+        for (url, api_key) in &self.reference_api_keys {
+            if reqwest::header::HeaderValue::from_str(api_key).is_err() {
+                return Err(HyperlaneTronError::InvalidConnectionConf(format!(
+                    "api key of {url} contains characters not allowed in an HTTP header"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_url(url: &Url) -> Result<(), HyperlaneTronError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(HyperlaneTronError::InvalidConnectionConf(format!(
+            "unsupported scheme `{}` in url {}, expected http or https",
+            url.scheme(),
+            url
+        )));
+    }
+    if url.host_str().unwrap_or_default().is_empty() {
+        return Err(HyperlaneTronError::InvalidConnectionConf(format!(
+            "missing host in url {}",
+            url
+        )));
+    }
+    Ok(())
+}
+
+/// ConnectionConf builder, filling unset options with their defaults
+#[derive(Debug, Clone)]
+pub struct ConnectionConfBuilder {
+    conf: ConnectionConf,
+}
+
+impl ConnectionConfBuilder {
+    /// Instantiate builder
+    pub fn new(url: Url) -> Self {
+        Self {
+            conf: ConnectionConf::new(url),
+        }
+    }
+
+    /// Set the other nodes the node is checked against for forks
+    pub fn with_reference_urls(mut self, reference_urls: Vec<Url>) -> Self {
+        self.conf.reference_urls = reference_urls;
+        self
+    }
+
+    /// Set the API key sent to the reference node at `url` instead of the global one
+    pub fn with_reference_api_key(mut self, url: Url, api_key: impl Into<String>) -> Self {
+        self.conf
+            .reference_api_keys
+            .insert(url, ApiKey::from(api_key.into()));
+        self
+    }
+
+    /// Set the maximum block range of a single log query
+    pub fn with_max_log_range(mut self, max_log_range: u32) -> Self {
+        self.conf.max_log_range = Some(max_log_range);
+        self
+    }
+
+    /// Set the maximum number of log queries run concurrently
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: u32) -> Self {
+        self.conf.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    /// Set the timeout of a single HTTP request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.conf.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the API key sent with every request
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.conf.api_key = Some(ApiKey::from(api_key.into()));
+        self
+    }
+
+    /// Set the HTTP connection pool settings
+    pub fn with_connection_pool(mut self, connection_pool: ConnectionPoolConf) -> Self {
+        self.conf.connection_pool = connection_pool;
+        self
+    }
+
+    /// Set whether calls to the node's HTTP API are logged with their bodies
+    pub fn with_trace_rpc(mut self, trace_rpc: bool) -> Self {
+        self.conf.trace_rpc = trace_rpc;
+        self
+    }
+
+    /// Set how failed calls are retried
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.conf.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set when calls to a failing node are short-circuited
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConf) -> Self {
+        self.conf.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Set whether sent transactions are awaited until confirmed
+    pub fn with_wait_for_confirmation(mut self, wait_for_confirmation: bool) -> Self {
+        self.conf.wait_for_confirmation = wait_for_confirmation;
+        self
+    }
+
+    /// Set whether message metadata is verified against the recipient ISM before processing
+    pub fn with_preflight_verify(mut self, preflight_verify: bool) -> Self {
+        self.conf.preflight_verify = preflight_verify;
+        self
+    }
+
+    /// Set whether message recipients are checked to be contracts before delivery
+    pub fn with_check_recipient_is_contract(mut self, check_recipient_is_contract: bool) -> Self {
+        self.conf.check_recipient_is_contract = check_recipient_is_contract;
+        self
+    }
+
+    /// Set whether the mailbox is checked not to be paused before processing
+    pub fn with_check_paused(mut self, check_paused: bool) -> Self {
+        self.conf.check_paused = check_paused;
+        self
+    }
+
+    /// Set whether contract state is read at the latest finalized block
+    pub fn with_prefer_finalized_reads(mut self, prefer_finalized_reads: bool) -> Self {
+        self.conf.prefer_finalized_reads = prefer_finalized_reads;
+        self
+    }
+
+    /// Set the address of the Multicall3 contract batching view calls
+    pub fn with_multicall_address(mut self, multicall_address: H256) -> Self {
+        self.conf.multicall_address = Some(multicall_address);
+        self
+    }
+
+    /// Set the account delegating the energy of sent transactions to the signer
+    pub fn with_fee_payer(mut self, fee_payer: H256) -> Self {
+        self.conf.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Set whether the signer may burn TRX for energy not covered by the fee payer, or
+    /// for bandwidth it lacks
+    pub fn with_allow_energy_burn(mut self, allow_energy_burn: bool) -> Self {
+        self.conf.allow_energy_burn = allow_energy_burn;
+        self
+    }
+
+    /// Set the energy price used when the node doesn't report the energy fee
+    pub fn with_fallback_energy_fee(mut self, fallback_energy_fee: u64) -> Self {
+        self.conf.fallback_energy_fee = Some(fallback_energy_fee);
+        self
+    }
+
+    /// Set the energy price used instead of the node's energy fee
+    pub fn with_energy_price_override(mut self, energy_price_override: u64) -> Self {
+        self.conf.energy_price_override = Some(energy_price_override);
+        self
+    }
+
+    /// Set how long sent transactions stay valid after being built
+    pub fn with_tx_expiration(mut self, tx_expiration: Duration) -> Self {
+        self.conf.tx_expiration = Some(tx_expiration);
+        self
+    }
+
+    /// Set the energy limit below which sent transactions are never capped
+    pub fn with_min_energy_limit(mut self, min_energy_limit: u64) -> Self {
+        self.conf.min_energy_limit = Some(min_energy_limit);
+        self
+    }
+
+    /// Set the permission sent transactions are signed under
+    pub fn with_permission_id(mut self, permission_id: u32) -> Self {
+        self.conf.permission_id = Some(permission_id);
+        self
+    }
+
+    /// Set the largest fraction of the signer's balance a fee limit may reach
+    pub fn with_max_fee_fraction_of_balance(mut self, max_fee_fraction_of_balance: f64) -> Self {
+        self.conf.max_fee_fraction_of_balance = Some(max_fee_fraction_of_balance);
+        self
+    }
+
+    /// Set how unconfirmed transactions are reissued with a higher fee limit
+    pub fn with_fee_limit_bump(mut self, fee_limit_bump: FeeLimitBumpConf) -> Self {
+        self.conf.fee_limit_bump = Some(fee_limit_bump);
+        self
+    }
+
+    /// Set whether concurrently processed messages are broadcast in nonce order
+    pub fn with_ordered_submission(mut self, ordered_submission: bool) -> Self {
+        self.conf.ordered_submission = ordered_submission;
+        self
+    }
+
+    /// Validate and build the ConnectionConf
+    pub fn build(self) -> Result<ConnectionConf, HyperlaneTronError> {
+        self.conf.validate()?;
+        Ok(self.conf)
+    }
+}
+
+/// Pooling of the TCP connections to the Tron node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ConnectionPoolConf {
+    /// Maximum number of idle connections kept open per host
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept before being closed
+    #[serde(rename = "idleTimeoutSecs", with = "serde_duration::secs")]
+    pub idle_timeout: Duration,
+    /// TCP keep-alive interval, disabled if unset
+    #[serde(rename = "tcpKeepaliveSecs", with = "serde_duration::opt_secs")]
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for ConnectionPoolConf {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 32,
+            // Below the usual 60s idle timeout of load balancers in front of nodes,
+            // so that we never reuse a connection the server already closed
+            idle_timeout: Duration::from_secs(15),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+/// Reissuing of a sent transaction with a higher fee limit once it can't be included
+/// anymore: when it ran out of energy, e.g. because its fee limit is too low for the
+/// energy price after a governance change, or when it expired. Tron transactions can't
+/// be replaced, so a transaction still pending is never reissued.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FeeLimitBumpConf {
+    /// Factor applied to the fee limit on each reissue, above 1. Fee limits stay
+    /// capped to the maximum of the chain and to the fraction of the balance, if set.
+    pub multiplier: f64,
+    /// Number of reissues of a transaction, after which its last result is reported
+    pub max_bumps: u32,
+}
+
+impl Default for FeeLimitBumpConf {
+    fn default() -> Self {
+        Self {
+            multiplier: 1.5,
+            max_bumps: 3,
+        }
+    }
+}
+
+/// Durations as whole seconds or milliseconds, like in the rest of the agent config
+pub(crate) mod serde_duration {
+    pub mod secs {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Duration,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u64(value.as_secs())
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Duration, D::Error> {
+            u64::deserialize(deserializer).map(Duration::from_secs)
+        }
+    }
+
+    pub mod opt_secs {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => serializer.serialize_some(&value.as_secs()),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            Option::<u64>::deserialize(deserializer).map(|secs| secs.map(Duration::from_secs))
+        }
+    }
+
+    pub mod millis {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Duration,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_u64(value.as_millis() as u64)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Duration, D::Error> {
+            u64::deserialize(deserializer).map(Duration::from_millis)
+        }
+    }
+}
+
+/// Checks that the reorg period used by an indexer is sane for the domain.
+///
+/// Recommended values per Tron network:
+/// - mainnet: the `finalized` tag, which reads solidified blocks, or at least
+///   [`TRON_SOLIDIFICATION_BLOCKS`] blocks
+/// - nile / shasta testnets: `1` block is usually enough, `finalized` if in doubt
+/// - local test chains: any value, including none
+///
+/// Returns `false` (and logs a warning) if the reorg period is unsafe for the domain.
+pub(crate) fn validate_reorg_period(domain: &HyperlaneDomain, reorg_period: &ReorgPeriod) -> bool {
+    let needs_finality = matches!(domain.domain_type(), HyperlaneDomainType::Mainnet);
+
+    if needs_finality && reorg_period.is_none() {
+        warn!(
+            domain = domain.name(),
+            ?reorg_period,
+            recommended_blocks = TRON_SOLIDIFICATION_BLOCKS,
+            "No reorg period configured on a Tron mainnet domain, indexed events may be reorged out. \
+             Use the `finalized` tag or a block count of at least the solidification depth"
+        );
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use hyperlane_core::{HyperlaneDomainProtocol, HyperlaneDomainTechnicalStack, H160};
+    use tracing_test::traced_test;
+
+    use crate::test_utils;
+
+    use super::*;
+
+    fn tron_domain(domain_type: HyperlaneDomainType) -> HyperlaneDomain {
+        HyperlaneDomain::Unknown {
+            domain_id: 728126428,
+            domain_name: "tron".to_owned(),
+            domain_type,
+            domain_protocol: HyperlaneDomainProtocol::Tron,
+            domain_technical_stack: HyperlaneDomainTechnicalStack::Other,
+        }
+    }
+
+    fn conf(url: &str) -> ConnectionConf {
+        test_utils::conf(Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(conf("http://localhost:8090").validate().is_ok());
+        assert!(conf("https://api.trongrid.io/jsonrpc").validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_invalid_url() {
+        assert!(matches!(
+            conf("ws://localhost:8090").validate(),
+            Err(HyperlaneTronError::InvalidConnectionConf(_))
+        ));
+        assert!(matches!(
+            conf("unix:/var/run/tron.sock").validate(),
+            Err(HyperlaneTronError::InvalidConnectionConf(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_invalid_api_key() {
+        let mut conf = conf("https://api.trongrid.io");
+        conf.api_key = Some("key\nwith newline".to_owned().into());
+
+        assert!(matches!(
+            conf.validate(),
+            Err(HyperlaneTronError::InvalidConnectionConf(_))
+        ));
+
+        conf.api_key = None;
+        conf.reference_api_keys.insert(
+            Url::parse("https://api.trongrid.io").unwrap(),
+            "key\nwith newline".to_owned().into(),
+        );
+        assert!(matches!(
+            conf.validate(),
+            Err(HyperlaneTronError::InvalidConnectionConf(_))
+        ));
+    }
+
+    #[test]
+    fn test_api_keys_redacted() {
+        let conf = ConnectionConfBuilder::new(Url::parse("https://api.trongrid.io").unwrap())
+            .with_api_key("main-key")
+            .with_reference_api_key(Url::parse("https://nile.trongrid.io").unwrap(), "nile-key")
+            .build()
+            .unwrap();
+
+        let debug = format!("{conf:?}");
+        let json = serde_json::to_string(&conf).unwrap();
+        for key in ["main-key", "nile-key"] {
+            assert!(!debug.contains(key), "{key} in {debug}");
+            assert!(!json.contains(key), "{key} in {json}");
+        }
+    }
+
+    #[test]
+    fn test_validate_tx_expiration() {
+        let mut conf = conf("https://api.trongrid.io");
+        conf.tx_expiration = Some(Duration::from_secs(3600));
+        assert!(conf.validate().is_ok());
+
+        conf.tx_expiration = Some(MAX_TX_EXPIRATION + Duration::from_secs(1));
+        assert!(matches!(
+            conf.validate(),
+            Err(HyperlaneTronError::InvalidConnectionConf(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_max_fee_fraction_of_balance() {
+        let mut conf = conf("https://api.trongrid.io");
+        conf.max_fee_fraction_of_balance = Some(0.25);
+        assert!(conf.validate().is_ok());
+
+        for fraction in [0.0, 1.5, f64::NAN] {
+            conf.max_fee_fraction_of_balance = Some(fraction);
+            assert!(matches!(
+                conf.validate(),
+                Err(HyperlaneTronError::InvalidConnectionConf(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_fee_limit_bump() {
+        let mut conf = conf("https://api.trongrid.io");
+        conf.fee_limit_bump = Some(FeeLimitBumpConf::default());
+        assert!(conf.validate().is_ok());
+
+        for multiplier in [1.0, 0.5, f64::INFINITY, f64::NAN] {
+            conf.fee_limit_bump = Some(FeeLimitBumpConf {
+                multiplier,
+                max_bumps: 3,
+            });
+            assert!(matches!(
+                conf.validate(),
+                Err(HyperlaneTronError::InvalidConnectionConf(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_conf() {
+        let json = serde_json::json!({
+            "url": "https://api.trongrid.io",
+            "maxLogRange": 5000,
+            "timeoutSecs": 30,
+            "apiKey": "0d2a3c5e-0000-4000-8000-000000000000",
+            "feePayer": "0x928c9af0651632157ef27a2cf17ca72c575a4d21",
+            "connectionPool": {"maxIdlePerHost": 8, "tcpKeepaliveSecs": null},
+            "retryPolicy": {"maxAttempts": 6, "baseDelayMs": 250},
+            "waitForConfirmation": false,
+        });
+
+        let conf: ConnectionConf = serde_json::from_value(json).unwrap();
+
+        assert_eq!(conf.url.as_str(), "https://api.trongrid.io/");
+        assert_eq!(conf.max_log_range, Some(5000));
+        assert_eq!(conf.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(
+            conf.api_key.as_deref(),
+            Some("0d2a3c5e-0000-4000-8000-000000000000")
+        );
+        assert_eq!(
+            conf.fee_payer,
+            Some(
+                H160::from_str("0x928c9af0651632157ef27a2cf17ca72c575a4d21")
+                    .unwrap()
+                    .into()
+            )
+        );
+        assert_eq!(
+            conf.connection_pool,
+            ConnectionPoolConf {
+                max_idle_per_host: 8,
+                tcp_keepalive: None,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            conf.retry_policy,
+            RetryPolicy {
+                max_attempts: 6,
+                base_delay: Duration::from_millis(250),
+                ..Default::default()
+            }
+        );
+        assert!(!conf.wait_for_confirmation);
+        assert!(!conf.preflight_verify);
+
+        let round_tripped: ConnectionConf =
+            serde_json::from_value(serde_json::to_value(&conf).unwrap()).unwrap();
+        assert_eq!(round_tripped.url, conf.url);
+        assert_eq!(round_tripped.timeout, conf.timeout);
+        // Keys aren't serialized
+        assert_eq!(round_tripped.api_key, None);
+        assert_eq!(round_tripped.connection_pool, conf.connection_pool);
+        assert_eq!(round_tripped.retry_policy, conf.retry_policy);
+    }
+
+    #[test]
+    fn test_deserialize_conf_defaults() {
+        let conf: ConnectionConf =
+            serde_json::from_value(serde_json::json!({"url": "http://localhost:8090"})).unwrap();
+
+        assert_eq!(conf.max_log_range, None);
+        assert_eq!(conf.timeout, None);
+        assert_eq!(conf.api_key, None);
+        assert_eq!(conf.connection_pool, ConnectionPoolConf::default());
+        assert_eq!(conf.retry_policy, RetryPolicy::default());
+        assert!(conf.wait_for_confirmation);
+        assert!(!conf.preflight_verify);
+        assert!(!conf.check_recipient_is_contract);
+        assert!(!conf.prefer_finalized_reads);
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let conf = ConnectionConfBuilder::new(Url::parse("https://api.trongrid.io").unwrap())
+            .with_api_key("0d2a3c5e-0000-4000-8000-000000000000")
+            .with_timeout(Duration::from_secs(30))
+            .with_preflight_verify(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            conf.api_key.as_deref(),
+            Some("0d2a3c5e-0000-4000-8000-000000000000")
+        );
+        assert_eq!(conf.timeout, Some(Duration::from_secs(30)));
+        assert!(conf.preflight_verify);
+        assert_eq!(conf.max_log_range, None);
+        assert_eq!(conf.connection_pool, ConnectionPoolConf::default());
+        assert_eq!(conf.retry_policy, RetryPolicy::default());
+        assert!(conf.wait_for_confirmation);
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let res = ConnectionConfBuilder::new(Url::parse("ws://localhost:8090").unwrap()).build();
+
+        assert!(matches!(
+            res,
+            Err(HyperlaneTronError::InvalidConnectionConf(_))
+        ));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_no_reorg_period_on_mainnet_warns() {
+        let domain = tron_domain(HyperlaneDomainType::Mainnet);
+
+        assert!(!validate_reorg_period(&domain, &ReorgPeriod::None));
+        assert!(logs_contain("No reorg period configured"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_reorg_period_accepted() {
+        let mainnet = tron_domain(HyperlaneDomainType::Mainnet);
+        let testnet = tron_domain(HyperlaneDomainType::Testnet);
+
+        assert!(validate_reorg_period(
+            &mainnet,
+            &ReorgPeriod::from_blocks(TRON_SOLIDIFICATION_BLOCKS)
+        ));
+        assert!(validate_reorg_period(
+            &mainnet,
+            &ReorgPeriod::Tag("finalized".to_owned())
+        ));
+        assert!(validate_reorg_period(&testnet, &ReorgPeriod::None));
+        assert!(!logs_contain("No reorg period configured"));
+    }
 }
@@ -1,30 +1,88 @@
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use async_trait::async_trait;
-use tracing::instrument;
+use ethers::abi::{self, AbiDecode, AbiEncode, ParamType, Token};
+use ethers::contract::{builders::ContractCall, Event};
+use ethers::providers::{HttpClientError, Middleware, ProviderError};
+use ethers::types::{
+    transaction::eip2718::TypedTransaction, Bytes, TransactionRequest, ValueOrArray,
+    H160 as EthersH160, H256 as EthersH256,
+};
+use ethers::utils::id;
+use ethers_contract::{Multicall, MulticallVersion};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use tracing::{debug, instrument, warn};
 
 use hyperlane_core::{
-    rpc_clients::call_and_retry_indefinitely, utils::bytes_to_hex, ChainCommunicationError,
+    rpc_clients::RPC_RETRY_SLEEP_DURATION, utils::bytes_to_hex, ChainCommunicationError,
     ChainResult, ContractLocator, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
-    HyperlaneMessage, HyperlaneProvider, Indexed, Indexer, LogMeta, Mailbox, RawHyperlaneMessage,
-    ReorgPeriod, SequenceAwareIndexer, TxCostEstimate, TxOutcome, H256, H512, U256,
+    HyperlaneMessage, HyperlaneProvider, HyperlaneProviderError, Indexed, Indexer, LogMeta,
+    Mailbox, RawHyperlaneMessage, ReorgPeriod, SequenceAwareIndexer, TxCostEstimate, TxOutcome,
+    H256, H512, U256,
 };
 
 use crate::interfaces::i_mailbox::{DispatchFilter, IMailbox as MailboxContract, ProcessCall};
-use crate::{ConnectionConf, Signer, TronAddress, TronEthClient, TronProvider};
+use crate::{
+    energy_to_gas, gas_to_energy, http_client_error, sun_to_fixed_point, validate_reorg_period,
+    BroadcastListener, ConnectionConf, CursorSnapshot, HyperlaneTronError, Signer, TronAddress,
+    TronCostEstimate, TronEthClient, TronProvider, TronTxOutcome,
+};
 
+use super::submission_queue::SubmissionQueue;
 use super::utils::{
-    call_with_reorg_period, fetch_raw_logs_and_meta, get_finalized_block_number, send_transaction,
+    call_with_abi_context, call_with_reorg_period, estimate_energy, fetch_logs_in_chunks,
+    fetch_tx_logs_with_retries, get_finalized_block_number, pin_to_finalized,
+    send_signed_transaction, verify_metadata, verify_return_shape, SentTransaction,
+    TRANSACTION_ENVELOPE_SIZE,
 };
 
+/// Largest transaction java-tron accepts, in bytes (`TRANSACTION_MAX_BYTE_SIZE`).
+/// Nodes don't expose it, so the protocol constant is used.
+const MAX_TRANSACTION_SIZE: usize = 500 * 1024;
+
+/// Room left for metadata in a delivery, enough for a multisig ISM with 20 validators
+const METADATA_ALLOWANCE: usize = 68 + 20 * 65;
+
+/// Size of the header of a message, before its body
+const MESSAGE_HEADER_SIZE: usize = 77;
+
+/// Most `delivered` reads batched in a single multicall, keeping it well within the
+/// energy the node allows a constant call
+const DELIVERED_BATCH_SIZE: usize = 200;
+
+/// A dispatched message along with the indexed fields of its `Dispatch` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchedMessage {
+    /// Sender of the message on the origin chain
+    pub sender: H256,
+    /// Domain the message is sent to
+    pub destination: u32,
+    /// Recipient of the message on the destination chain
+    pub recipient: H256,
+    pub message: HyperlaneMessage,
+}
+
+impl From<DispatchFilter> for DispatchedMessage {
+    fn from(event: DispatchFilter) -> Self {
+        Self {
+            sender: H256::from(event.sender),
+            destination: event.destination,
+            recipient: H256::from(event.recipient),
+            message: HyperlaneMessage::from(event.message.to_vec()),
+        }
+    }
+}
+
 /// Struct that retrieves event data for a Tron mailbox
 #[derive(Debug, Clone)]
 pub struct TronMailboxIndexer {
     contract: Arc<MailboxContract<TronEthClient>>,
     provider: TronProvider,
     reorg_period: ReorgPeriod,
+    destination_filter: Option<HashSet<u32>>,
 }
 
 impl TronMailboxIndexer {
@@ -33,6 +91,8 @@ impl TronMailboxIndexer {
         locator: ContractLocator,
         reorg_period: ReorgPeriod,
     ) -> ChainResult<Self> {
+        validate_reorg_period(locator.domain, &reorg_period);
+
         let address = TronAddress::try_from(locator.address)?;
         let provider = TronProvider::new(locator.domain.clone(), conf)?;
         let contract = Arc::new(MailboxContract::new(address, provider.eth_client.clone()));
@@ -41,8 +101,118 @@ impl TronMailboxIndexer {
             contract,
             provider,
             reorg_period,
+            destination_filter: None,
         })
     }
+
+    /// Only index messages sent to the given destination domains.
+    ///
+    /// Filtered out messages leave gaps in the indexed nonces, so this is only meant
+    /// for indexing that doesn't rely on the message sequence.
+    pub fn with_destination_filter(mut self, destinations: HashSet<u32>) -> Self {
+        self.destination_filter = Some(destinations);
+        self
+    }
+
+    fn dispatch_filter(
+        &self,
+        range: RangeInclusive<u32>,
+    ) -> Event<Arc<TronEthClient>, TronEthClient, DispatchFilter> {
+        let filter = self
+            .contract
+            .dispatch_filter()
+            .from_block(*range.start())
+            .to_block(*range.end());
+
+        match &self.destination_filter {
+            // The destination is the second indexed field of the event
+            Some(destinations) => filter.topic2(ValueOrArray::Array(
+                destinations
+                    .iter()
+                    .map(|destination| Some(EthersH256::from_low_u64_be(*destination as u64)))
+                    .collect(),
+            )),
+            None => filter,
+        }
+    }
+
+    /// Whether a message is sent to one of the indexed destinations
+    fn is_indexed_destination(&self, dispatch: &DispatchedMessage) -> bool {
+        match &self.destination_filter {
+            Some(destinations) => destinations.contains(&dispatch.destination),
+            None => true,
+        }
+    }
+}
+
+impl TronMailboxIndexer {
+    /// Dispatched messages in the block range along with the indexed fields of
+    /// their events, which allows filtering them without parsing the messages.
+    /// Messages excluded by the destination filter are skipped.
+    #[instrument(err, skip(self))]
+    pub async fn fetch_dispatches_in_range(
+        &self,
+        range: RangeInclusive<u32>,
+    ) -> ChainResult<Vec<(DispatchedMessage, LogMeta)>> {
+        Ok(fetch_logs_in_chunks(&self.provider, range, |range| {
+            let filter = self.dispatch_filter(range);
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?
+        .into_iter()
+        .map(|(event, meta)| (DispatchedMessage::from(event), meta.into()))
+        // Nodes may ignore the topic filter
+        .filter(|(dispatch, _)| self.is_indexed_destination(dispatch))
+        .collect())
+    }
+
+    /// Messages dispatched in the blocks past the finalized one, up to the tip of the
+    /// chain, for relayers to fetch their metadata ahead of finality.
+    ///
+    /// These messages are unconfirmed: the blocks they were dispatched in may still be
+    /// reorged out, in which case the messages may never be dispatched, or be
+    /// dispatched with another nonce. They must not be relayed before they are
+    /// fetched again by [`Indexer::fetch_logs_in_range`].
+    #[instrument(err, skip(self))]
+    pub async fn fetch_pending_logs(
+        &self,
+    ) -> ChainResult<Vec<(Indexed<HyperlaneMessage>, LogMeta)>> {
+        let finalized = Indexer::<HyperlaneMessage>::get_finalized_block_number(self).await?;
+        let tip = get_finalized_block_number(&self.provider, &ReorgPeriod::None).await?;
+        if tip <= finalized {
+            return Ok(vec![]);
+        }
+
+        let logs =
+            Indexer::<HyperlaneMessage>::fetch_logs_in_range(self, finalized + 1..=tip).await?;
+        debug!(
+            count = logs.len(),
+            from = finalized + 1,
+            to = tip,
+            "Fetched unconfirmed dispatches"
+        );
+        Ok(logs)
+    }
+
+    /// Checks that the node can resolve the reorg period of the indexer, to fail at
+    /// startup rather than on every query if it can't
+    pub async fn ensure_reorg_period_supported(&self) -> ChainResult<()> {
+        self.provider
+            .ensure_reorg_period_supported(&self.reorg_period)
+            .await
+    }
+
+    /// Snapshot of the position of the indexer, to persist and resume from with
+    /// [`Self::resume_block`] after a restart
+    pub async fn cursor_snapshot(&self, last_indexed_block: u32) -> ChainResult<CursorSnapshot> {
+        self.provider.cursor_snapshot(last_indexed_block).await
+    }
+
+    /// Block from which to resume indexing from a snapshot, further back than the
+    /// snapshot if its last indexed block was reorged out since
+    pub async fn resume_block(&self, snapshot: &CursorSnapshot) -> ChainResult<u32> {
+        self.provider.resume_block(snapshot).await
+    }
 }
 
 #[async_trait]
@@ -59,19 +229,10 @@ impl Indexer<HyperlaneMessage> for TronMailboxIndexer {
         range: RangeInclusive<u32>,
     ) -> ChainResult<Vec<(Indexed<HyperlaneMessage>, LogMeta)>> {
         let mut events: Vec<(Indexed<HyperlaneMessage>, LogMeta)> = self
-            .contract
-            .dispatch_filter()
-            .from_block(*range.start())
-            .to_block(*range.end())
-            .query_with_meta()
+            .fetch_dispatches_in_range(range)
             .await?
             .into_iter()
-            .map(|(event, meta)| {
-                (
-                    HyperlaneMessage::from(event.message.to_vec()).into(),
-                    meta.into(),
-                )
-            })
+            .map(|(dispatch, meta)| (dispatch.message.into(), meta))
             .collect();
 
         events.sort_by(|a, b| a.0.inner().nonce.cmp(&b.0.inner().nonce));
@@ -82,14 +243,13 @@ impl Indexer<HyperlaneMessage> for TronMailboxIndexer {
         &self,
         tx_hash: H512,
     ) -> ChainResult<Vec<(Indexed<HyperlaneMessage>, LogMeta)>> {
-        let raw_logs_and_meta = call_and_retry_indefinitely(|| {
-            let provider = self.provider.clone();
-            let contract = self.contract.address();
-            Box::pin(async move {
-                fetch_raw_logs_and_meta::<DispatchFilter>(&provider, contract, tx_hash).await
-            })
-        })
-        .await;
+        let raw_logs_and_meta = fetch_tx_logs_with_retries::<DispatchFilter>(
+            &self.provider,
+            self.contract.address(),
+            tx_hash,
+            RPC_RETRY_SLEEP_DURATION,
+        )
+        .await?;
         let logs = raw_logs_and_meta
             .into_iter()
             .map(|(log, log_meta)| {
@@ -109,7 +269,7 @@ impl SequenceAwareIndexer<HyperlaneMessage> for TronMailboxIndexer {
     #[allow(clippy::blocks_in_conditions)] // TODO: `rustc` 1.80.1 clippy issue
     async fn latest_sequence_count_and_tip(&self) -> ChainResult<(Option<u32>, u32)> {
         let tip = Indexer::<HyperlaneMessage>::get_finalized_block_number(self).await?;
-        let sequence = self.contract.nonce().block(u64::from(tip)).call().await?;
+        let sequence = call_with_abi_context(self.contract.nonce().block(u64::from(tip))).await?;
         Ok((Some(sequence), tip))
     }
 }
@@ -120,31 +280,37 @@ impl Indexer<H256> for TronMailboxIndexer {
         get_finalized_block_number(&self.provider, &self.reorg_period).await
     }
 
-    /// Note: This call may return duplicates depending on the provider used
+    /// Note: This call may return duplicates depending on the provider used.
+    /// Ids are indexed without a sequence: `ProcessId` events only carry the message
+    /// id, and deliveries aren't sequenced, see [`SequenceAwareIndexer<H256>`].
     #[instrument(err, skip(self))]
     #[allow(clippy::blocks_in_conditions)] // TODO: `rustc` 1.80.1 clippy issue
     async fn fetch_logs_in_range(
         &self,
         range: RangeInclusive<u32>,
     ) -> ChainResult<Vec<(Indexed<H256>, LogMeta)>> {
-        Ok(self
-            .contract
-            .process_id_filter()
-            .from_block(*range.start())
-            .to_block(*range.end())
-            .query_with_meta()
-            .await?
-            .into_iter()
-            .map(|(event, meta)| (Indexed::new(H256::from(event.message_id)), meta.into()))
-            .collect())
+        Ok(fetch_logs_in_chunks(&self.provider, range, |range| {
+            let filter = self
+                .contract
+                .process_id_filter()
+                .from_block(*range.start())
+                .to_block(*range.end());
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?
+        .into_iter()
+        .map(|(event, meta)| (Indexed::new(H256::from(event.message_id)), meta.into()))
+        .collect())
     }
 }
 
 #[async_trait]
 impl SequenceAwareIndexer<H256> for TronMailboxIndexer {
+    /// Deliveries have no sequence: the mailbox has no view counting them, and
+    /// messages are delivered out of order and from many origins, with `ProcessId`
+    /// events carrying only the message id. Delivery events are indexed by block
+    /// range alone.
     async fn latest_sequence_count_and_tip(&self) -> ChainResult<(Option<u32>, u32)> {
-        // A blanket implementation for this trait is fine for the EVM.
-        // TODO: Consider removing `Indexer` as a supertrait of `SequenceAwareIndexer`
         let tip = Indexer::<H256>::get_finalized_block_number(self).await?;
         Ok((None, tip))
     }
@@ -156,6 +322,10 @@ pub struct TronMailbox {
     contract: Arc<MailboxContract<TronEthClient>>,
     provider: TronProvider,
     signer: Option<Signer>,
+    /// Immutable domain of the mailbox, read on first access
+    local_domain: OnceLock<u32>,
+    /// Orders the broadcast of processed messages, if configured
+    submission_queue: Option<Arc<SubmissionQueue>>,
 }
 
 impl TronMailbox {
@@ -167,13 +337,539 @@ impl TronMailbox {
         let address = TronAddress::try_from(locator.address)?;
         let provider = TronProvider::new(locator.domain.clone(), conf)?;
         let contract = Arc::new(MailboxContract::new(address, provider.eth_client.clone()));
+        let submission_queue = provider
+            .ordered_submission()
+            .then(|| Arc::new(SubmissionQueue::default()));
 
         Ok(TronMailbox {
             contract,
             provider,
             signer,
+            local_domain: OnceLock::new(),
+            submission_queue,
+        })
+    }
+
+    /// Domain the mailbox was deployed with. It is immutable, so it is only read
+    /// from the chain on first access.
+    #[instrument(err, skip(self))]
+    pub async fn local_domain(&self) -> ChainResult<u32> {
+        if let Some(local_domain) = self.local_domain.get() {
+            return Ok(*local_domain);
+        }
+
+        let local_domain = call_with_abi_context(self.contract.local_domain()).await?;
+        Ok(*self.local_domain.get_or_init(|| local_domain))
+    }
+
+    /// Notifies `listener` of the transactions accepted by the node, so that the
+    /// outcome of a delivery interrupted while awaiting its confirmation can be
+    /// recovered with [`TronProvider::await_outcome`]
+    pub fn with_broadcast_listener(mut self, listener: BroadcastListener) -> Self {
+        self.provider = self.provider.with_broadcast_listener(listener);
+        self
+    }
+
+    /// Whether `message` is destined to the domain of this mailbox, guarding against
+    /// processing messages on the wrong chain in multi-chain deployments
+    pub fn is_for_this_domain(&self, message: &HyperlaneMessage) -> bool {
+        message.destination == self.domain().id()
+    }
+
+    /// ISM the relayer should use for messages to `recipient`: the one returned by
+    /// `recipientIsm`, falling back to the default ISM if that one is unset (zero)
+    #[instrument(err, skip(self))]
+    pub async fn effective_ism(&self, recipient: H256) -> ChainResult<H256> {
+        let ism = self.recipient_ism(recipient).await?;
+        if !ism.is_zero() {
+            return Ok(ism);
+        }
+        self.default_ism().await
+    }
+
+    /// Replays the verification of `message` with `metadata` by the ISM of its
+    /// recipient as of `block`, or the latest state if unset, to explain post mortem
+    /// why a delivery succeeded or reverted. ISMs and their validator sets change over
+    /// time, so pass the block before the delivery to see what the delivery saw.
+    #[instrument(err, skip(self, message, metadata), fields(msg=%message))]
+    pub async fn replay_verify(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+        block: Option<u64>,
+    ) -> ChainResult<bool> {
+        let at_block = |call: ContractCall<_, EthersH160>| match block {
+            Some(block) => call.block(block),
+            None => call,
+        };
+        let recipient: TronAddress = message.recipient.try_into()?;
+        let mut ism =
+            call_with_abi_context(at_block(self.contract.recipient_ism(recipient.into()))).await?;
+        if ism.is_zero() {
+            ism = call_with_abi_context(at_block(self.contract.default_ism())).await?;
+        }
+
+        verify_metadata(&self.provider, ism, message, metadata, block).await
+    }
+
+    /// Reads the configuration of the mailbox, in a single call if a multicall
+    /// contract is configured
+    #[instrument(err, skip(self))]
+    pub async fn read_config(&self) -> ChainResult<MailboxConfig> {
+        match self.provider.multicall_address() {
+            Some(multicall_address) => self.read_config_batched(multicall_address).await,
+            None => self.read_config_sequentially().await,
+        }
+    }
+
+    /// Multicall through the given contract, pinned to the latest finalized block if
+    /// the provider prefers finalized reads
+    async fn multicall(&self, multicall_address: H256) -> ChainResult<Multicall<TronEthClient>> {
+        let multicall_address = TronAddress::try_from(multicall_address)?;
+        let mut multicall = Multicall::new(
+            self.provider.eth_client.clone(),
+            Some(multicall_address.into()),
+        )
+        .await
+        .map_err(ChainCommunicationError::from_other)?
+        .version(MulticallVersion::Multicall3);
+        if self.provider.prefer_finalized_reads() {
+            let rpc_client = &self.provider.rpc_client;
+            let block = self
+                .provider
+                .retry_policy()
+                .retry(|| rpc_client.call(|client| client.get_finalized_block_number()))
+                .await?;
+            multicall = multicall.block(block);
+        }
+        Ok(multicall)
+    }
+
+    async fn read_config_batched(&self, multicall_address: H256) -> ChainResult<MailboxConfig> {
+        let mut multicall = self.multicall(multicall_address).await?;
+        multicall
+            .add_call(self.contract.local_domain(), false)
+            .add_call(self.contract.nonce(), false)
+            .add_call(self.contract.default_ism(), false)
+            .add_call(self.contract.default_hook(), false);
+
+        let (local_domain, nonce, default_ism, default_hook): (u32, u32, EthersH160, EthersH160) =
+            multicall
+                .call()
+                .await
+                .map_err(ChainCommunicationError::from_other)?;
+
+        Ok(MailboxConfig {
+            local_domain,
+            nonce,
+            default_ism: TronAddress::from(default_ism).into(),
+            default_hook: TronAddress::from(default_hook).into(),
+        })
+    }
+
+    /// Whether each of the messages with the given ids was delivered, in batched
+    /// calls if a multicall contract is configured, else in concurrent calls to
+    /// `delivered`. Speeds up reconciling a backlog.
+    #[instrument(err, skip(self, ids), fields(ids = ids.len()))]
+    pub async fn delivered_batch(&self, ids: &[H256]) -> ChainResult<Vec<bool>> {
+        let Some(multicall_address) = self.provider.multicall_address() else {
+            return stream::iter(ids)
+                .map(|id| self.delivered(*id))
+                .buffered(self.provider.max_concurrent_requests() as usize)
+                .try_collect()
+                .await;
+        };
+
+        let mut multicall = self.multicall(multicall_address).await?;
+        let mut delivered = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(DELIVERED_BATCH_SIZE) {
+            multicall.clear_calls();
+            multicall.add_calls(
+                false,
+                chunk.iter().map(|id| self.contract.delivered((*id).into())),
+            );
+            delivered.extend(
+                multicall
+                    .call_array::<bool>()
+                    .await
+                    .map_err(ChainCommunicationError::from_other)?,
+            );
+        }
+        Ok(delivered)
+    }
+
+    async fn read_config_sequentially(&self) -> ChainResult<MailboxConfig> {
+        let local_domain = self.local_domain().await?;
+        let nonce = call_with_abi_context(self.contract.nonce()).await?;
+        let default_ism: TronAddress = call_with_abi_context(self.contract.default_ism())
+            .await?
+            .into();
+        let default_hook: TronAddress = call_with_abi_context(self.contract.default_hook())
+            .await?
+            .into();
+
+        Ok(MailboxConfig {
+            local_domain,
+            nonce,
+            default_ism: default_ism.into(),
+            default_hook: default_hook.into(),
         })
     }
+
+    /// Checks that the deployed mailbox answers its views with the shapes the
+    /// generated bindings expect, to catch an ABI drift at startup rather than
+    /// reading garbage later on
+    #[instrument(err, skip(self))]
+    pub async fn verify_abi_compatibility(&self) -> ChainResult<()> {
+        verify_return_shape(&self.contract.local_domain()).await?;
+        verify_return_shape(&self.contract.nonce()).await?;
+        verify_return_shape(&self.contract.delivered([0; 32])).await?;
+        verify_return_shape(&self.contract.default_ism()).await?;
+        verify_return_shape(&self.contract.default_hook()).await?;
+        verify_return_shape(&self.contract.required_hook()).await?;
+
+        Ok(())
+    }
+
+    /// Finds the transaction that delivered the message with the given id, scanning
+    /// the `ProcessId` events of the blocks in `search_range`
+    #[instrument(err, skip(self))]
+    pub async fn delivery_tx(
+        &self,
+        id: H256,
+        search_range: RangeInclusive<u32>,
+    ) -> ChainResult<Option<H512>> {
+        let logs = fetch_logs_in_chunks(&self.provider, search_range, |range| {
+            let filter = self
+                .contract
+                .process_id_filter()
+                .topic1(EthersH256::from(id.0))
+                .from_block(*range.start())
+                .to_block(*range.end());
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?;
+
+        Ok(logs
+            .into_iter()
+            .find(|(event, _)| H256::from(event.message_id) == id)
+            .map(|(_, meta)| LogMeta::from(meta).transaction_id))
+    }
+
+    /// Whether the dispatch of `message` by this mailbox was emitted in the blocks in
+    /// `search_range`, looking up the `DispatchId` event of its id. Allows checking
+    /// end to end that a dispatched message was indexed as expected.
+    #[instrument(err, skip(self, message), fields(msg=%message))]
+    pub async fn verify_dispatch(
+        &self,
+        message: &HyperlaneMessage,
+        search_range: RangeInclusive<u32>,
+    ) -> ChainResult<bool> {
+        let id = message.id();
+        let logs = fetch_logs_in_chunks(&self.provider, search_range, |range| {
+            let filter = self
+                .contract
+                .dispatch_id_filter()
+                .topic1(EthersH256::from(id.0))
+                .from_block(*range.start())
+                .to_block(*range.end());
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?;
+
+        Ok(logs
+            .iter()
+            .any(|(event, _)| H256::from(event.message_id) == id))
+    }
+
+    /// Whether the message with the given nonce from `origin` was delivered in the
+    /// blocks in `search_range`.
+    ///
+    /// The mailbox tracks deliveries by message id only, and nonces are only unique
+    /// per origin, so this can't be read on chain. Instead, the messages delivered
+    /// by the transactions of the `Process` events from `origin` in the range are
+    /// decoded from their calldata. Deliveries through another contract calling the
+    /// mailbox can't be decoded, and are skipped with a warning.
+    #[instrument(err, skip(self))]
+    pub async fn delivered_nonce(
+        &self,
+        origin: u32,
+        nonce: u32,
+        search_range: RangeInclusive<u32>,
+    ) -> ChainResult<bool> {
+        let logs = fetch_logs_in_chunks(&self.provider, search_range, |range| {
+            let filter = self
+                .contract
+                .process_filter()
+                .topic1(EthersH256::from_low_u64_be(origin as u64))
+                .from_block(*range.start())
+                .to_block(*range.end());
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?;
+
+        let mut tx_hashes = logs
+            .into_iter()
+            .map(|(_, meta)| meta.transaction_hash)
+            .collect::<Vec<_>>();
+        tx_hashes.dedup();
+
+        for tx_hash in tx_hashes {
+            let tx = self
+                .provider
+                .eth_client
+                .get_transaction(tx_hash)
+                .await
+                .map_err(HyperlaneTronError::from)?
+                .ok_or_else(|| {
+                    HyperlaneProviderError::CouldNotFindTransactionByHash(H256(tx_hash.0).into())
+                })?;
+
+            let Ok(call) = ProcessCall::decode(&tx.input) else {
+                warn!(
+                    ?tx_hash,
+                    "Can't decode delivered message from calldata not calling process"
+                );
+                continue;
+            };
+            let message = HyperlaneMessage::from(call.message.to_vec());
+            if message.origin == origin && message.nonce == nonce {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Largest message body that can be delivered to this mailbox, for senders to
+    /// avoid dispatching undeliverable messages. Derived from the maximum size of
+    /// transactions, minus the transaction envelope, the encoding of the `process`
+    /// call and room for the metadata of multisig ISMs with up to 20 validators.
+    ///
+    /// No chain parameter reports the maximum size of transactions, so java-tron's
+    /// protocol constant, [`MAX_TRANSACTION_SIZE`], is the source of truth, and
+    /// nothing is read from the node.
+    pub fn max_deliverable_message_size(&self) -> usize {
+        max_message_body_size(MAX_TRANSACTION_SIZE, METADATA_ALLOWANCE)
+    }
+
+    /// Whether the mailbox is paused, read from its `paused()` view. Mailboxes that
+    /// aren't pausable, i.e. whose call reverts or returns nothing, are not paused.
+    #[instrument(err, skip(self))]
+    pub async fn is_paused(&self) -> ChainResult<bool> {
+        match self.call_optional_view("paused()").await? {
+            Some(data) if data.len() == 32 => Ok(data[31] != 0),
+            _ => Ok(false),
+        }
+    }
+
+    /// Version of the deployed contracts package, read from the `PACKAGE_VERSION()`
+    /// view, e.g. to warn about mailboxes newer or older than what this crate
+    /// supports. `None` for mailboxes deployed before the view was introduced.
+    #[instrument(err, ret, skip(self))]
+    pub async fn deployed_version(&self) -> ChainResult<Option<String>> {
+        let Some(data) = self.call_optional_view("PACKAGE_VERSION()").await? else {
+            return Ok(None);
+        };
+
+        match abi::decode(&[ParamType::String], &data).map(|mut tokens| tokens.pop()) {
+            Ok(Some(Token::String(version))) => Ok(Some(version)),
+            _ => {
+                debug!(data = %data, "Mailbox has no PACKAGE_VERSION view");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Calls a view taking no arguments that older mailboxes may not have, returning
+    /// `None` if the call reverts. Other errors, e.g. of the node, are returned.
+    async fn call_optional_view(&self, signature: &str) -> ChainResult<Option<Bytes>> {
+        let call: TypedTransaction = TransactionRequest::new()
+            .to(self.contract.address())
+            .data(id(signature).to_vec())
+            .into();
+
+        match self.provider.eth_client.call(&call, None).await {
+            Ok(data) if !data.is_empty() => Ok(Some(data)),
+            Ok(_) => {
+                debug!(signature, "Mailbox has no such view");
+                Ok(None)
+            }
+            Err(err) if is_revert(&err) => {
+                debug!(signature, %err, "Mailbox has no such view");
+                Ok(None)
+            }
+            Err(err) => Err(HyperlaneTronError::from(err).into()),
+        }
+    }
+
+    /// Fails with a clear error if configured to check the mailbox isn't paused and it
+    /// is, rather than letting processing revert
+    async fn ensure_not_paused(&self) -> ChainResult<()> {
+        if self.provider.check_paused() && self.is_paused().await? {
+            return Err(HyperlaneTronError::MailboxPaused(self.address()).into());
+        }
+        Ok(())
+    }
+
+    /// Fails with a clear error if configured to check recipients and `recipient`
+    /// has no code, rather than letting calls to it revert
+    async fn ensure_recipient_is_contract(&self, recipient: H256) -> ChainResult<()> {
+        if self.provider.check_recipient_is_contract()
+            && !self.provider.is_contract(&recipient).await?
+        {
+            return Err(HyperlaneTronError::RecipientNotAContract(recipient).into());
+        }
+        Ok(())
+    }
+
+    /// Processes a message like [`Mailbox::process`], also returning why the
+    /// transaction failed if it did
+    pub async fn process_with_result(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+        tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TronTxOutcome> {
+        let sent = self
+            .send_process(message, metadata, tx_gas_limit, None)
+            .await?;
+        Ok(TronTxOutcome {
+            outcome: sent.outcome,
+            result: sent.result,
+        })
+    }
+
+    /// Processes a message like [`Mailbox::process`], also returning the signed
+    /// transaction as broadcast, serialized as JSON, for audit logs
+    pub async fn process_with_signed_tx(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+        tx_gas_limit: Option<U256>,
+    ) -> ChainResult<(TxOutcome, Vec<u8>)> {
+        let sent = self
+            .send_process(message, metadata, tx_gas_limit, None)
+            .await?;
+        Ok((sent.outcome, sent.signed_tx))
+    }
+
+    /// Estimates the cost of processing a message in Tron resources, which
+    /// [`Mailbox::process_estimate_costs`] maps to gas. The TRX fee assumes that both
+    /// energy and bandwidth are paid by burning TRX.
+    #[instrument(err, ret, skip(self), fields(msg=%message, metadata=%bytes_to_hex(metadata)))]
+    pub async fn process_estimate_costs_tron(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+    ) -> ChainResult<TronCostEstimate> {
+        let call = process_calldata(message, metadata);
+        let bandwidth = (TRANSACTION_ENVELOPE_SIZE + AbiEncode::encode(call.clone()).len()) as u64;
+        let energy = estimate_energy(&self.provider, &self.contract.address().into(), call).await?;
+
+        let rpc_client = &self.provider.rpc_client;
+        let energy_price = self.provider.energy_price().await?;
+        let bandwidth_price = self
+            .provider
+            .retry_policy()
+            .retry(|| rpc_client.call(|client| client.get_transaction_fee_cached()))
+            .await?;
+
+        Ok(TronCostEstimate {
+            energy,
+            bandwidth,
+            energy_price_sun: energy_price,
+            trx_fee_sun: energy
+                .saturating_mul(energy_price)
+                .saturating_add(bandwidth.saturating_mul(bandwidth_price)),
+        })
+    }
+
+    /// Processes a message like [`Mailbox::process`], with a fee limit in sun instead
+    /// of the cost of the energy limit at the current energy price, e.g. to allow
+    /// more for high-value messages
+    pub async fn process_with_fee_limit(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+        tx_gas_limit: Option<U256>,
+        fee_limit_sun: u64,
+    ) -> ChainResult<TxOutcome> {
+        let sent = self
+            .send_process(message, metadata, tx_gas_limit, Some(fee_limit_sun))
+            .await?;
+        Ok(sent.outcome)
+    }
+
+    async fn send_process(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+        tx_gas_limit: Option<U256>,
+        fee_limit_sun: Option<u64>,
+    ) -> ChainResult<SentTransaction> {
+        if !self.is_for_this_domain(message) {
+            return Err(HyperlaneTronError::WrongDestination {
+                message_id: message.id(),
+                destination: message.destination,
+                domain: self.domain().id(),
+            }
+            .into());
+        }
+
+        // Queued before anything is awaited, so that concurrent calls are ordered
+        let ticket = self
+            .submission_queue
+            .as_ref()
+            .map(|queue| queue.enqueue(message.destination, message.nonce));
+
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or(ChainCommunicationError::SignerUnavailable)?;
+
+        self.ensure_not_paused().await?;
+        self.ensure_recipient_is_contract(message.recipient).await?;
+
+        if self.provider.preflight_verify() {
+            let recipient: TronAddress = message.recipient.try_into()?;
+            let ism = call_with_abi_context(self.contract.recipient_ism(recipient.into())).await?;
+            if !verify_metadata(&self.provider, ism, message, metadata, None).await? {
+                return Err(HyperlaneTronError::MetadataVerificationFailed {
+                    message_id: message.id(),
+                    ism: TronAddress::from(ism).into(),
+                }
+                .into());
+            }
+        }
+
+        if let Some(ticket) = &ticket {
+            ticket.wait_turn().await;
+        }
+        Ok(send_signed_transaction(
+            &self.provider,
+            &self.contract.address().into(),
+            process_calldata(message, metadata),
+            signer,
+            tx_gas_limit.map(gas_to_energy),
+            fee_limit_sun,
+            ticket,
+        )
+        .await?)
+    }
+}
+
+/// Configuration of a mailbox, as read from the contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailboxConfig {
+    /// Domain of the chain the mailbox is deployed on
+    pub local_domain: u32,
+    /// Number of dispatched messages
+    pub nonce: u32,
+    /// Default ISM
+    pub default_ism: H256,
+    /// Default post dispatch hook
+    pub default_hook: H256,
 }
 
 impl HyperlaneChain for TronMailbox {
@@ -198,33 +894,36 @@ impl Mailbox for TronMailbox {
     async fn count(&self, reorg_period: &ReorgPeriod) -> ChainResult<u32> {
         let call =
             call_with_reorg_period(&self.provider, reorg_period, self.contract.nonce()).await?;
-        let nonce = call.call().await?;
+        let nonce = call_with_abi_context(call).await?;
 
         Ok(nonce)
     }
 
     #[instrument(skip(self))]
     async fn delivered(&self, id: H256) -> ChainResult<bool> {
-        Ok(self.contract.delivered(id.into()).call().await?)
+        let call = pin_to_finalized(&self.provider, self.contract.delivered(id.into())).await?;
+        call_with_abi_context(call).await
     }
 
     #[instrument(skip(self))]
     async fn default_ism(&self) -> ChainResult<H256> {
-        let ism: TronAddress = self.contract.default_ism().call().await?.into();
+        let call = pin_to_finalized(&self.provider, self.contract.default_ism()).await?;
+        let ism: TronAddress = call_with_abi_context(call).await?.into();
 
         Ok(ism.into())
     }
 
     #[instrument(skip(self))]
     async fn recipient_ism(&self, recipient: H256) -> ChainResult<H256> {
+        self.ensure_recipient_is_contract(recipient).await?;
         let recipient: TronAddress = recipient.try_into()?;
 
-        let ism: TronAddress = self
-            .contract
-            .recipient_ism(recipient.into())
-            .call()
-            .await?
-            .into();
+        let call = pin_to_finalized(
+            &self.provider,
+            self.contract.recipient_ism(recipient.into()),
+        )
+        .await?;
+        let ism: TronAddress = call_with_abi_context(call).await?.into();
 
         Ok(ism.into())
     }
@@ -236,20 +935,9 @@ impl Mailbox for TronMailbox {
         metadata: &[u8],
         tx_gas_limit: Option<U256>,
     ) -> ChainResult<TxOutcome> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(ChainCommunicationError::SignerUnavailable)?;
-
-        send_transaction(
-            &self.provider,
-            &self.contract.address().into(),
-            process_calldata(message, metadata),
-            signer,
-            tx_gas_limit.map(|v| v.as_u64()),
-        )
-        .await
-        .map_err(Into::into)
+        self.process_with_signed_tx(message, metadata, tx_gas_limit)
+            .await
+            .map(|(outcome, _)| outcome)
     }
 
     #[instrument(skip(self), fields(msg=%message, metadata=%bytes_to_hex(metadata)))]
@@ -258,10 +946,13 @@ impl Mailbox for TronMailbox {
         message: &HyperlaneMessage,
         metadata: &[u8],
     ) -> ChainResult<TxCostEstimate> {
-        // TODO use correct data upon integrating IGP support
+        let estimate = self.process_estimate_costs_tron(message, metadata).await?;
+
+        // Bandwidth has no gas equivalent, and is mostly covered by the daily free
+        // allowance
         Ok(TxCostEstimate {
-            gas_limit: U256::zero(),
-            gas_price: hyperlane_core::FixedPointNumber::zero(),
+            gas_limit: energy_to_gas(estimate.energy),
+            gas_price: sun_to_fixed_point(estimate.energy_price_sun)?,
             l2_gas_limit: None,
         })
     }
@@ -271,9 +962,1277 @@ impl Mailbox for TronMailbox {
     }
 }
 
+/// Largest message body whose delivery with `metadata_size` bytes of metadata fits
+/// in a transaction of `max_tx_size` bytes
+fn max_message_body_size(max_tx_size: usize, metadata_size: usize) -> usize {
+    let padded = |size: usize| size.div_ceil(32) * 32;
+    // Selector, then offset and length of both arguments
+    let call_overhead = 4 + 4 * 32;
+
+    let max_padded_message = max_tx_size
+        .saturating_sub(TRANSACTION_ENVELOPE_SIZE + call_overhead + padded(metadata_size));
+    (max_padded_message / 32 * 32).saturating_sub(MESSAGE_HEADER_SIZE)
+}
+
+/// Whether a call failed because it reverted or hit an invalid opcode, e.g. calling
+/// a function the contract doesn't have, rather than because of the node
+fn is_revert(err: &ProviderError) -> bool {
+    match http_client_error(err) {
+        Some(HttpClientError::JsonRpcError(err)) => {
+            let message = err.message.to_ascii_lowercase();
+            message.contains("revert") || message.contains("invalid opcode")
+        }
+        _ => false,
+    }
+}
+
 fn process_calldata(message: &HyperlaneMessage, metadata: &[u8]) -> ProcessCall {
     ProcessCall {
         message: RawHyperlaneMessage::from(message).to_vec().into(),
         metadata: metadata.to_vec().into(),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use ethers::abi::{self, AbiEncode, RawLog, Token};
+    use ethers::contract::{EthCall, EthEvent};
+    use ethers::utils::hex;
+    use hyperlane_core::{FixedPointNumber, KnownHyperlaneDomain};
+    use url::Url;
+
+    use crate::interfaces::i_interchain_security_module::VerifyCall;
+    use crate::interfaces::i_mailbox::{
+        DefaultIsmCall, DeliveredCall, DispatchIdFilter, LocalDomainCall, ProcessFilter,
+        ProcessIdFilter, RecipientIsmCall,
+    };
+    use crate::test_utils::{self, MockTronNode};
+    use crate::ConnectionConf;
+
+    use super::*;
+
+    #[test]
+    fn test_dispatched_message_from_event() {
+        let message = HyperlaneMessage {
+            version: 3,
+            nonce: 7,
+            origin: 728126428,
+            sender: H256::from_low_u64_be(0xaa),
+            destination: 1,
+            recipient: H256::from_low_u64_be(0xbb),
+            body: vec![1, 2, 3],
+        };
+        let raw_log = RawLog {
+            topics: vec![
+                DispatchFilter::signature(),
+                EthersH256::from_low_u64_be(0xaa),
+                EthersH256::from_low_u64_be(1),
+                EthersH256::from_low_u64_be(0xbb),
+            ],
+            data: abi::encode(&[Token::Bytes(RawHyperlaneMessage::from(&message))]),
+        };
+
+        let event = DispatchFilter::decode_log(&raw_log).unwrap();
+
+        assert_eq!(
+            DispatchedMessage::from(event),
+            DispatchedMessage {
+                sender: H256::from_low_u64_be(0xaa),
+                destination: 1,
+                recipient: H256::from_low_u64_be(0xbb),
+                message,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pending_logs() {
+        let message = HyperlaneMessage {
+            nonce: 7,
+            destination: 1,
+            body: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let ranges = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let ranges = ranges.clone();
+            let data = abi::encode(&[Token::Bytes(RawHyperlaneMessage::from(&message))]);
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getnowblock" => test_utils::block(105),
+                "/" => {
+                    let filter = &body["params"][0];
+                    ranges
+                        .lock()
+                        .unwrap()
+                        .push((filter["fromBlock"].clone(), filter["toBlock"].clone()));
+                    let mut log = test_utils::json_rpc_log(
+                        &[
+                            format!("{:#x}", DispatchFilter::signature()),
+                            format!("{:#x}", EthersH256::zero()),
+                            format!("{:#x}", EthersH256::from_low_u64_be(1)),
+                            format!("{:#x}", EthersH256::zero()),
+                        ],
+                        &format!("0x{}", hex::encode(&data)),
+                    );
+                    log["blockNumber"] = "0x69".into();
+                    test_utils::json_rpc_result(&body, vec![log])
+                }
+                _ => panic!("unexpected request to {path}"),
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let indexer = TronMailboxIndexer::new(
+            node.conf(),
+            ContractLocator::new(&domain, H256::zero()),
+            ReorgPeriod::from_blocks(5),
+        )
+        .unwrap();
+
+        let logs = indexer.fetch_pending_logs().await.unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(*logs[0].0.inner(), message);
+        assert_eq!(logs[0].1.block_number, 105);
+        // Only the blocks past the finalized one are queried
+        assert_eq!(
+            *ranges.lock().unwrap(),
+            vec![(serde_json::json!("0x65"), serde_json::json!("0x69"))]
+        );
+    }
+
+    fn indexer() -> TronMailboxIndexer {
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let locator = ContractLocator::new(&domain, H256::zero());
+        let conf = test_utils::conf(Url::parse("http://localhost:8090").unwrap());
+
+        TronMailboxIndexer::new(conf, locator, ReorgPeriod::None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delivery_sequence_count_and_tip() {
+        let node = MockTronNode::start(|path, _| {
+            // The tip only, with no view call for a sequence
+            assert_eq!(path, "/wallet/getnowblock");
+            test_utils::block(42)
+        })
+        .await;
+        let locator = ContractLocator::new(
+            &HyperlaneDomain::Known(KnownHyperlaneDomain::Test1),
+            H256::zero(),
+        );
+        let indexer = TronMailboxIndexer::new(node.conf(), locator, ReorgPeriod::None).unwrap();
+
+        let sequence_and_tip =
+            SequenceAwareIndexer::<H256>::latest_sequence_count_and_tip(&indexer)
+                .await
+                .unwrap();
+
+        assert_eq!(sequence_and_tip, (None, 42));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_events_carry_no_sequence() {
+        let message_id = H256::repeat_byte(0x11);
+        let node = MockTronNode::start(move |_, body| {
+            assert_eq!(body["method"], "eth_getLogs");
+            let log = test_utils::json_rpc_log(
+                &[
+                    format!("{:#x}", ProcessIdFilter::signature()),
+                    format!("{message_id:#x}"),
+                ],
+                "0x",
+            );
+            test_utils::json_rpc_result(&body, vec![log])
+        })
+        .await;
+        let locator = ContractLocator::new(
+            &HyperlaneDomain::Known(KnownHyperlaneDomain::Test1),
+            H256::zero(),
+        );
+        let indexer = TronMailboxIndexer::new(node.conf(), locator, ReorgPeriod::None).unwrap();
+
+        let logs = Indexer::<H256>::fetch_logs_in_range(&indexer, 0..=100)
+            .await
+            .unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(*logs[0].0.inner(), message_id);
+        assert_eq!(logs[0].0.sequence, None);
+    }
+
+    #[tokio::test]
+    async fn test_verify_dispatch() {
+        let message = HyperlaneMessage {
+            version: 3,
+            nonce: 7,
+            origin: 1000,
+            sender: H256::from_low_u64_be(0xaa),
+            destination: 2000,
+            recipient: H256::from_low_u64_be(0xbb),
+            body: b"hello".to_vec(),
+        };
+        let id = message.id();
+
+        let node = MockTronNode::start(move |_, body| {
+            assert_eq!(body["method"], "eth_getLogs");
+            let filter = &body["params"][0];
+            assert_eq!(
+                filter["topics"][0],
+                format!("{:#x}", DispatchIdFilter::signature())
+            );
+            let logs = if filter["topics"][1] == format!("{id:#x}") {
+                vec![test_utils::json_rpc_log(
+                    &[
+                        format!("{:#x}", DispatchIdFilter::signature()),
+                        format!("{id:#x}"),
+                    ],
+                    "0x",
+                )]
+            } else {
+                vec![]
+            };
+            test_utils::json_rpc_result(&body, logs)
+        })
+        .await;
+        let mailbox = test_utils::mailbox(node.conf(), None);
+
+        assert!(mailbox.verify_dispatch(&message, 0..=100).await.unwrap());
+        let other = HyperlaneMessage {
+            nonce: 8,
+            ..message
+        };
+        assert!(!mailbox.verify_dispatch(&other, 0..=100).await.unwrap());
+    }
+
+    fn dispatch(destination: u32) -> DispatchedMessage {
+        DispatchedMessage {
+            sender: H256::zero(),
+            destination,
+            recipient: H256::zero(),
+            message: HyperlaneMessage {
+                destination,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_destination_filter() {
+        let indexer = indexer();
+        assert!(indexer.dispatch_filter(0..=10).filter.topics[2].is_none());
+        assert!(indexer.is_indexed_destination(&dispatch(1)));
+
+        let indexer = indexer.with_destination_filter(HashSet::from([1, 2]));
+
+        let Some(ValueOrArray::Array(topics)) = &indexer.dispatch_filter(0..=10).filter.topics[2]
+        else {
+            panic!("destination topic not set");
+        };
+        let mut topics = topics.clone();
+        topics.sort();
+        assert_eq!(
+            topics,
+            vec![
+                Some(EthersH256::from_low_u64_be(1)),
+                Some(EthersH256::from_low_u64_be(2))
+            ]
+        );
+
+        let dispatches: Vec<_> = [1, 2, 3, 1]
+            .into_iter()
+            .map(dispatch)
+            .filter(|dispatch| indexer.is_indexed_destination(dispatch))
+            .map(|dispatch| dispatch.destination)
+            .collect();
+        assert_eq!(dispatches, vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn test_max_message_body_size() {
+        // 500 KiB, less 512 bytes of envelope, 132 of call encoding and 1376 of metadata
+        assert_eq!(max_message_body_size(500 * 1024, 1368), 509_875);
+        // The encoded delivery of the largest body fits
+        let message = HyperlaneMessage {
+            body: vec![0; 509_875],
+            ..Default::default()
+        };
+        let calldata = process_calldata(&message, &[0; 1368]).encode();
+        assert!(calldata.len() + TRANSACTION_ENVELOPE_SIZE <= 500 * 1024);
+        // Nothing fits in a transaction too small for the overhead
+        assert_eq!(max_message_body_size(1000, 1368), 0);
+    }
+
+    #[test]
+    fn test_max_deliverable_message_size() {
+        let mailbox = test_utils::mailbox(
+            test_utils::conf(Url::parse("http://127.0.0.1:1").unwrap()),
+            None,
+        );
+
+        // See `test_max_message_body_size`
+        assert_eq!(mailbox.max_deliverable_message_size(), 509_875);
+    }
+
+    /// Input of `process(bytes,bytes)` delivering [`reference_message`] with metadata
+    /// `0xaabbcc`, laid out as `Message.formatMessage` packs it: version (1 byte),
+    /// nonce (4), origin (4), sender (32), destination (4), recipient (32), body
+    const PROCESS_INPUT_FIXTURE: &str = concat!(
+        "7c39d130",
+        // Offsets of metadata and message
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        // Metadata
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "aabbcc0000000000000000000000000000000000000000000000000000000000",
+        // Message of 82 bytes
+        "0000000000000000000000000000000000000000000000000000000000000052",
+        "03",
+        "01020304",
+        "05060708",
+        "1111111111111111111111111111111111111111111111111111111111111111",
+        "2b6653dc",
+        "0000000000000000000000002222222222222222222222222222222222222222",
+        "68656c6c6f",
+        // Padding to a whole word
+        "0000000000000000000000000000",
+    );
+
+    fn reference_message() -> HyperlaneMessage {
+        HyperlaneMessage {
+            version: 3,
+            nonce: 0x01020304,
+            origin: 0x05060708,
+            sender: H256::repeat_byte(0x11),
+            destination: 728126428,
+            recipient: H256::from(EthersH160::repeat_byte(0x22)),
+            body: b"hello".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_process_calldata_matches_mailbox_layout() {
+        let calldata = process_calldata(&reference_message(), &[0xaa, 0xbb, 0xcc]).encode();
+
+        assert_eq!(hex::encode(calldata), PROCESS_INPUT_FIXTURE);
+    }
+
+    #[tokio::test]
+    async fn test_process_preflight_verify_prevents_broadcast() {
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let requests = requests.clone();
+            MockTronNode::start(move |path, body| {
+                requests.lock().unwrap().push(path.to_owned());
+                if path != "/" {
+                    return serde_json::json!({});
+                }
+                let data = body["params"][0]["data"].as_str().unwrap();
+                let selector = |selector: [u8; 4]| format!("0x{}", hex::encode(selector));
+                let mut resp = if data.starts_with(&selector(RecipientIsmCall::selector())) {
+                    serde_json::json!({"result": format!("{:#x}", H256::from_low_u64_be(0xcc))})
+                } else if data.starts_with(&selector(VerifyCall::selector())) {
+                    serde_json::json!({"error": {
+                        "code": 3,
+                        "message": "execution reverted: !threshold",
+                        "data": "0x08c379a0"
+                    }})
+                } else {
+                    panic!("unexpected call {data}");
+                };
+                resp["jsonrpc"] = "2.0".into();
+                resp["id"] = body["id"].clone();
+                resp
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let conf = ConnectionConf {
+            preflight_verify: true,
+            ..node.conf()
+        };
+        let mailbox = test_utils::mailbox(conf, Some(Signer::from_bytes(&[1; 32]).unwrap()));
+        let message = HyperlaneMessage {
+            destination: domain.id(),
+            recipient: H256::from_low_u64_be(0xbb),
+            ..Default::default()
+        };
+
+        let err = mailbox
+            .process(&message, &[1, 2, 3], None)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("fails verification by ISM"));
+        assert_eq!(*requests.lock().unwrap(), vec!["/", "/"]);
+    }
+
+    #[tokio::test]
+    async fn test_is_for_this_domain() {
+        let node = MockTronNode::start(|path, _| panic!("unexpected request to {path}")).await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let mailbox = test_utils::mailbox(node.conf(), Some(Signer::from_bytes(&[1; 32]).unwrap()));
+        let message = |destination| HyperlaneMessage {
+            destination,
+            ..Default::default()
+        };
+
+        assert!(mailbox.is_for_this_domain(&message(domain.id())));
+        assert!(!mailbox.is_for_this_domain(&message(domain.id() + 1)));
+
+        // Rejected before anything is sent to the node
+        let err = mailbox
+            .process(&message(domain.id() + 1), &[], None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not to domain"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_verify() {
+        let valid_metadata = [0xaa; 4];
+        let blocks = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let blocks = blocks.clone();
+            MockTronNode::start(move |_, body| {
+                blocks.lock().unwrap().push(body["params"][1].clone());
+                let data = body["params"][0]["data"].as_str().unwrap();
+                let selector = |selector: [u8; 4]| format!("0x{}", hex::encode(selector));
+                let mut resp = if data.starts_with(&selector(RecipientIsmCall::selector())) {
+                    // The recipient relies on the default ISM
+                    serde_json::json!({"result": format!("{:#x}", H256::zero())})
+                } else if data.starts_with(&selector(DefaultIsmCall::selector())) {
+                    serde_json::json!({"result": format!("{:#x}", H256::from_low_u64_be(0xcc))})
+                } else if data.starts_with(&selector(VerifyCall::selector())) {
+                    assert_eq!(
+                        body["params"][0]["to"],
+                        format!("{:#x}", EthersH160::from_low_u64_be(0xcc))
+                    );
+                    if data.contains(&hex::encode(valid_metadata)) {
+                        serde_json::json!({"result": format!("{:#x}", H256::from_low_u64_be(1))})
+                    } else {
+                        serde_json::json!({"error": {
+                            "code": 3,
+                            "message": "execution reverted: !threshold",
+                            "data": "0x08c379a0"
+                        }})
+                    }
+                } else {
+                    panic!("unexpected call {data}");
+                };
+                resp["jsonrpc"] = "2.0".into();
+                resp["id"] = body["id"].clone();
+                resp
+            })
+            .await
+        };
+        let mailbox = test_utils::mailbox(node.conf(), None);
+        let message = HyperlaneMessage {
+            recipient: H256::from_low_u64_be(0xbb),
+            ..Default::default()
+        };
+
+        assert!(mailbox
+            .replay_verify(&message, &valid_metadata, Some(100))
+            .await
+            .unwrap());
+        // Every call reads the state at the given block
+        assert!(blocks.lock().unwrap().iter().all(|block| block == "0x64"));
+
+        assert!(!mailbox
+            .replay_verify(&message, &[0xbb; 4], Some(100))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_process_estimate_costs_tron() {
+        let node = MockTronNode::start(|path, _| match path {
+            "/wallet/triggerconstantcontract" => test_utils::constant_call(50_000),
+            "/wallet/getchainparameters" => serde_json::json!({
+                "chainParameter": [
+                    {"key": "getEnergyFee", "value": 420},
+                    {"key": "getTransactionFee", "value": 1000}
+                ]
+            }),
+            _ => panic!("unexpected request to {path}"),
+        })
+        .await;
+        let mailbox = test_utils::mailbox(node.conf(), None);
+        let message = HyperlaneMessage {
+            body: vec![1; 100],
+            ..Default::default()
+        };
+        let metadata = [2; 100];
+
+        let native = mailbox
+            .process_estimate_costs_tron(&message, &metadata)
+            .await
+            .unwrap();
+        let estimate = mailbox
+            .process_estimate_costs(&message, &metadata)
+            .await
+            .unwrap();
+
+        // Selector, offsets and lengths, then the padded message and metadata
+        let calldata_size = 4 + 4 * 32 + 192 + 128;
+        assert_eq!(
+            native,
+            TronCostEstimate {
+                energy: 50_000,
+                bandwidth: (TRANSACTION_ENVELOPE_SIZE + calldata_size) as u64,
+                energy_price_sun: 420,
+                trx_fee_sun: 50_000 * 420
+                    + (TRANSACTION_ENVELOPE_SIZE + calldata_size) as u64 * 1000,
+            }
+        );
+        assert_eq!(estimate.gas_limit, energy_to_gas(native.energy));
+        assert_eq!(
+            estimate.gas_price.clone() * estimate.gas_limit.as_u64(),
+            FixedPointNumber::from(native.energy * 420)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_estimate_costs_tron_saturates() {
+        let node = MockTronNode::start(|path, _| match path {
+            "/wallet/triggerconstantcontract" => test_utils::constant_call(50_000),
+            "/wallet/getchainparameters" => serde_json::json!({
+                "chainParameter": [
+                    {"key": "getEnergyFee", "value": i64::MAX},
+                    {"key": "getTransactionFee", "value": 1000}
+                ]
+            }),
+            _ => panic!("unexpected request to {path}"),
+        })
+        .await;
+        let mailbox = test_utils::mailbox(node.conf(), None);
+
+        let estimate = mailbox
+            .process_estimate_costs_tron(&HyperlaneMessage::default(), &[])
+            .await
+            .unwrap();
+
+        assert_eq!(estimate.trx_fee_sun, u64::MAX);
+    }
+
+    /// Fee limit of the transaction sent to process a message
+    async fn process_fee_limit(
+        tx_gas_limit: Option<U256>,
+        fee_limit_sun: Option<u64>,
+    ) -> serde_json::Value {
+        let fee_limits = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let fee_limits = fee_limits.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+                "/wallet/triggersmartcontract" => {
+                    fee_limits.lock().unwrap().push(body["fee_limit"].clone());
+                    let txid = format!("{:#x}", H256::from_low_u64_be(1));
+                    test_utils::triggered_transaction(&txid)
+                }
+                "/wallet/broadcasttransaction" => {
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                _ => panic!("unexpected request to {path}"),
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let conf = ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        };
+        let mailbox = test_utils::mailbox(conf, Some(Signer::from_bytes(&[1; 32]).unwrap()));
+        let message = HyperlaneMessage {
+            destination: domain.id(),
+            recipient: H256::from_low_u64_be(0xbb),
+            ..Default::default()
+        };
+
+        match fee_limit_sun {
+            Some(fee_limit_sun) => mailbox
+                .process_with_fee_limit(&message, &[], tx_gas_limit, fee_limit_sun)
+                .await
+                .unwrap(),
+            None => mailbox.process(&message, &[], tx_gas_limit).await.unwrap(),
+        };
+
+        let fee_limits = fee_limits.lock().unwrap();
+        assert_eq!(fee_limits.len(), 1);
+        fee_limits[0].clone()
+    }
+
+    #[tokio::test]
+    async fn test_process_converts_gas_limit_to_energy() {
+        let gas_limit = U256::from(150_000);
+
+        assert_eq!(
+            process_fee_limit(Some(gas_limit), None).await,
+            serde_json::json!(gas_to_energy(gas_limit) * 210)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_fee_limit_saturates_for_huge_gas_limit() {
+        assert_eq!(
+            process_fee_limit(Some(U256::MAX), None).await,
+            serde_json::json!(u64::MAX)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_fee_limit_override() {
+        let gas_limit = U256::from(150_000);
+
+        assert_eq!(
+            process_fee_limit(Some(gas_limit), Some(100_000_000)).await,
+            serde_json::json!(100_000_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ordered_submission_broadcasts_in_nonce_order() {
+        let broadcast_nonces = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let broadcast_nonces = broadcast_nonces.clone();
+            let nonces_by_txid = Mutex::new(HashMap::new());
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+                "/wallet/triggerconstantcontract" => test_utils::constant_call(1000),
+                "/wallet/triggersmartcontract" => {
+                    let parameter = hex::decode(body["parameter"].as_str().unwrap()).unwrap();
+                    let call = ProcessCall::decode(parameter).unwrap();
+                    let message = HyperlaneMessage::from(call.message.to_vec());
+                    let txid = format!("{:#x}", H256::from_low_u64_be(message.nonce as u64));
+                    nonces_by_txid
+                        .lock()
+                        .unwrap()
+                        .insert(txid.clone(), message.nonce);
+                    test_utils::triggered_transaction(&txid)
+                }
+                "/wallet/broadcasttransaction" => {
+                    let txid = body["txID"].as_str().unwrap();
+                    let nonce = nonces_by_txid.lock().unwrap()[txid];
+                    broadcast_nonces.lock().unwrap().push(nonce);
+                    test_utils::broadcast_result(txid)
+                }
+                _ => serde_json::json!({}),
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let conf = ConnectionConf {
+            wait_for_confirmation: false,
+            ordered_submission: true,
+            ..node.conf()
+        };
+        let mailbox = test_utils::mailbox(conf, Some(Signer::from_bytes(&[1; 32]).unwrap()));
+        let message = |nonce| HyperlaneMessage {
+            destination: domain.id(),
+            nonce,
+            recipient: H256::from_low_u64_be(0xbb),
+            ..Default::default()
+        };
+        let (first, third, second) = (message(5), message(7), message(6));
+
+        // Nonce 5 is queued alone so is processed right away, and nonce 7 is queued
+        // before nonce 6
+        let results = futures::join!(
+            mailbox.process(&first, &[], None),
+            mailbox.process(&third, &[], None),
+            mailbox.process(&second, &[], None),
+        );
+        results.0.unwrap();
+        results.1.unwrap();
+        results.2.unwrap();
+
+        assert_eq!(*broadcast_nonces.lock().unwrap(), vec![5, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_ordered_submission_releases_turn_once_broadcast() {
+        let broadcast_nonces = Arc::new(Mutex::new(vec![]));
+
+        // Transactions stay pending until both messages are broadcast, so the second
+        // message can't wait for the confirmation of the first
+        let node = {
+            let broadcast_nonces = broadcast_nonces.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+                "/wallet/triggerconstantcontract" => test_utils::constant_call(1000),
+                "/wallet/triggersmartcontract" => {
+                    let parameter = hex::decode(body["parameter"].as_str().unwrap()).unwrap();
+                    let call = ProcessCall::decode(parameter).unwrap();
+                    let message = HyperlaneMessage::from(call.message.to_vec());
+                    let txid = format!("{:#x}", H256::from_low_u64_be(message.nonce as u64));
+                    test_utils::triggered_transaction(&txid)
+                }
+                "/wallet/broadcasttransaction" => {
+                    let txid = body["txID"].as_str().unwrap();
+                    let nonce = u32::from_str_radix(&txid[txid.len() - 8..], 16).unwrap();
+                    broadcast_nonces.lock().unwrap().push(nonce);
+                    test_utils::broadcast_result(txid)
+                }
+                "/walletsolidity/gettransactionbyid"
+                    if broadcast_nonces.lock().unwrap().len() == 2 =>
+                {
+                    let mut tx = test_utils::transaction(body["value"].as_str().unwrap());
+                    tx["ret"] = serde_json::json!([{"contractRet": "SUCCESS"}]);
+                    tx
+                }
+                "/wallet/gettransactionfrompending" => serde_json::json!({"txID": body["value"]}),
+                _ => serde_json::json!({}),
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let conf = ConnectionConf {
+            ordered_submission: true,
+            ..node.conf()
+        };
+        let mailbox = test_utils::mailbox(conf, Some(Signer::from_bytes(&[1; 32]).unwrap()));
+        let message = |nonce| HyperlaneMessage {
+            destination: domain.id(),
+            nonce,
+            recipient: H256::from_low_u64_be(0xbb),
+            ..Default::default()
+        };
+        let (first, second) = (message(5), message(6));
+
+        let results = tokio::time::timeout(Duration::from_secs(30), async {
+            futures::join!(
+                mailbox.process(&first, &[], None),
+                mailbox.process(&second, &[], None),
+            )
+        })
+        .await
+        .expect("messages waited for each other");
+        assert!(results.0.unwrap().executed);
+        assert!(results.1.unwrap().executed);
+
+        assert_eq!(*broadcast_nonces.lock().unwrap(), vec![5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_prefer_finalized_reads() {
+        let blocks = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let blocks = blocks.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/walletsolidity/getblock" => test_utils::block(63_999_995),
+                "/" => {
+                    blocks.lock().unwrap().push(body["params"][1].clone());
+                    test_utils::json_rpc_result(&body, format!("{:#x}", H256::from_low_u64_be(1)))
+                }
+                _ => panic!("unexpected request to {path}"),
+            })
+            .await
+        };
+        let mailbox = |prefer_finalized_reads| {
+            let conf = ConnectionConf {
+                prefer_finalized_reads,
+                ..node.conf()
+            };
+            test_utils::mailbox(conf, None)
+        };
+
+        let mailbox_at_tip = mailbox(false);
+        mailbox_at_tip.delivered(H256::zero()).await.unwrap();
+        mailbox_at_tip.default_ism().await.unwrap();
+
+        let finalized_mailbox = mailbox(true);
+        finalized_mailbox.delivered(H256::zero()).await.unwrap();
+        finalized_mailbox.default_ism().await.unwrap();
+        finalized_mailbox
+            .recipient_ism(H256::from_low_u64_be(0xbb))
+            .await
+            .unwrap();
+        finalized_mailbox.count(&ReorgPeriod::None).await.unwrap();
+
+        let finalized = serde_json::json!(format!("{:#x}", 63_999_995));
+        assert_eq!(
+            *blocks.lock().unwrap(),
+            vec![
+                serde_json::json!("latest"),
+                serde_json::json!("latest"),
+                finalized.clone(),
+                finalized.clone(),
+                finalized.clone(),
+                finalized,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_effective_ism_of_recipient_with_ism() {
+        let (mailbox, _node) =
+            test_utils::mailbox_with_recipient_ism(H256::from_low_u64_be(0xcc)).await;
+
+        let ism = mailbox
+            .effective_ism(H256::from_low_u64_be(0xbb))
+            .await
+            .unwrap();
+
+        assert_eq!(ism, H256::from_low_u64_be(0xcc));
+    }
+
+    #[tokio::test]
+    async fn test_effective_ism_falls_back_to_default() {
+        let (mailbox, _node) = test_utils::mailbox_with_recipient_ism(H256::zero()).await;
+
+        let ism = mailbox
+            .effective_ism(H256::from_low_u64_be(0xbb))
+            .await
+            .unwrap();
+
+        assert_eq!(ism, H256::from_low_u64_be(0xdd));
+    }
+
+    #[tokio::test]
+    async fn test_eoa_recipient_is_rejected() {
+        let methods = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let methods = methods.clone();
+            MockTronNode::start(move |path, body| {
+                assert_eq!(path, "/");
+                methods.lock().unwrap().push(body["method"].clone());
+                assert_eq!(body["method"], "eth_getCode");
+                test_utils::json_rpc_result(&body, "0x")
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let conf = ConnectionConf {
+            check_recipient_is_contract: true,
+            ..node.conf()
+        };
+        let mailbox = test_utils::mailbox(conf, Some(Signer::from_bytes(&[1; 32]).unwrap()));
+        let eoa = H256::from_low_u64_be(0xbb);
+        let message = HyperlaneMessage {
+            destination: domain.id(),
+            recipient: eoa,
+            ..Default::default()
+        };
+
+        let err = mailbox.recipient_ism(eoa).await.unwrap_err();
+        assert!(err.to_string().contains("is not a contract"), "{err}");
+        let err = mailbox
+            .process(&message, &[1, 2, 3], None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is not a contract"), "{err}");
+
+        // Neither the ISM lookup nor the delivery were attempted
+        assert_eq!(methods.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_is_paused() {
+        let paused = |response: serde_json::Value| async move {
+            let calls = Arc::new(Mutex::new(vec![]));
+            let node = {
+                let calls = calls.clone();
+                MockTronNode::start(move |path, body| {
+                    assert_eq!(path, "/");
+                    assert_eq!(body["method"], "eth_call");
+                    calls
+                        .lock()
+                        .unwrap()
+                        .push(body["params"][0]["data"].clone());
+                    let mut response = response.clone();
+                    response["jsonrpc"] = "2.0".into();
+                    response["id"] = body["id"].clone();
+                    response
+                })
+                .await
+            };
+            let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+            let conf = ConnectionConf {
+                check_paused: true,
+                ..node.conf()
+            };
+            let mailbox = test_utils::mailbox(conf, Some(Signer::from_bytes(&[1; 32]).unwrap()));
+
+            let is_paused = mailbox.is_paused().await.unwrap();
+            if is_paused {
+                let err = mailbox
+                    .process(
+                        &HyperlaneMessage {
+                            destination: domain.id(),
+                            ..Default::default()
+                        },
+                        &[1, 2, 3],
+                        None,
+                    )
+                    .await
+                    .unwrap_err();
+                assert!(err.to_string().contains("is paused"), "{err}");
+                // Nothing was sent after the pause checks
+                assert_eq!(calls.lock().unwrap().len(), 2);
+            }
+            assert_eq!(
+                calls.lock().unwrap()[0],
+                format!("0x{}", hex::encode(id("paused()")))
+            );
+            is_paused
+        };
+
+        let word = |value: u64| format!("{:#x}", H256::from_low_u64_be(value));
+        assert!(paused(serde_json::json!({"result": word(1)})).await);
+        assert!(!paused(serde_json::json!({"result": word(0)})).await);
+        // Mailboxes without the view
+        assert!(!paused(serde_json::json!({"result": "0x"})).await);
+        assert!(
+            !paused(serde_json::json!({
+                "error": {"code": -32000, "message": "REVERT opcode executed"}
+            }))
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_is_paused_fails_on_node_errors() {
+        let node = MockTronNode::start(|_, body| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": body["id"],
+                "error": {"code": -32603, "message": "internal error"}
+            })
+        })
+        .await;
+        let mailbox = test_utils::mailbox(node.conf(), None);
+
+        let err = mailbox.is_paused().await.unwrap_err();
+        assert!(err.to_string().contains("internal error"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_deployed_version() {
+        let version = |result: serde_json::Value| async move {
+            let node = MockTronNode::start(move |_, body| {
+                assert_eq!(
+                    body["params"][0]["data"],
+                    format!("0x{}", hex::encode(id("PACKAGE_VERSION()")))
+                );
+                let mut result = result.clone();
+                result["jsonrpc"] = "2.0".into();
+                result["id"] = body["id"].clone();
+                result
+            })
+            .await;
+            let mailbox = test_utils::mailbox(node.conf(), None);
+
+            mailbox.deployed_version().await.unwrap()
+        };
+
+        let encoded = abi::encode(&[Token::String("5.11.4".to_owned())]);
+        assert_eq!(
+            version(serde_json::json!({"result": format!("0x{}", hex::encode(encoded))})).await,
+            Some("5.11.4".to_owned())
+        );
+        // Mailboxes without the view
+        assert_eq!(version(serde_json::json!({"result": "0x"})).await, None);
+        assert_eq!(
+            version(serde_json::json!({
+                "error": {"code": -32000, "message": "REVERT opcode executed"}
+            }))
+            .await,
+            None
+        );
+        // A fallback function answering with a word
+        let word = format!("{:#x}", H256::from_low_u64_be(1));
+        assert_eq!(version(serde_json::json!({"result": word})).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_tx() {
+        let delivered = H256::repeat_byte(0x11);
+        let delivery_tx = EthersH256::repeat_byte(0xdd);
+        let queried_topics = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let queried_topics = queried_topics.clone();
+            MockTronNode::start(move |_, body| {
+                assert_eq!(body["method"], "eth_getLogs");
+                let filter = &body["params"][0];
+                queried_topics
+                    .lock()
+                    .unwrap()
+                    .push(filter["topics"].clone());
+
+                let message_id = filter["topics"][1].as_str().unwrap();
+                let logs = if message_id == format!("{delivered:#x}") {
+                    let mut log = test_utils::json_rpc_log(
+                        &[
+                            format!("{:#x}", ProcessIdFilter::signature()),
+                            message_id.to_owned(),
+                        ],
+                        "0x",
+                    );
+                    log["transactionHash"] = format!("{delivery_tx:#x}").into();
+                    log["logIndex"] = "0x2".into();
+                    vec![log]
+                } else {
+                    vec![]
+                };
+                test_utils::json_rpc_result(&body, logs)
+            })
+            .await
+        };
+        let mailbox = test_utils::mailbox(node.conf(), None);
+
+        assert_eq!(
+            mailbox.delivery_tx(delivered, 90..=110).await.unwrap(),
+            Some(H256::from(delivery_tx.0).into())
+        );
+        assert_eq!(
+            mailbox
+                .delivery_tx(H256::repeat_byte(0x22), 90..=110)
+                .await
+                .unwrap(),
+            None
+        );
+        // Filtered by message id on the node
+        assert_eq!(
+            queried_topics.lock().unwrap()[0][1],
+            format!("{delivered:#x}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivered_nonce() {
+        let origin = 1000u32;
+        let message = |nonce| HyperlaneMessage {
+            nonce,
+            origin,
+            ..Default::default()
+        };
+        // The first delivery calls the mailbox directly, the second through another contract
+        let inputs = [
+            ProcessCall {
+                metadata: vec![].into(),
+                message: RawHyperlaneMessage::from(&message(5)).into(),
+            }
+            .encode(),
+            vec![0x12, 0x34, 0x56, 0x78],
+        ];
+
+        let node = MockTronNode::start(move |_, body| {
+            let result = match body["method"].as_str().unwrap() {
+                "eth_getLogs" => {
+                    let origin_topic = format!("{:#x}", H256::from_low_u64_be(origin as u64));
+                    assert_eq!(body["params"][0]["topics"][1], origin_topic);
+                    serde_json::json!([1u64, 2]
+                        .into_iter()
+                        .map(|tx| {
+                            let mut log = test_utils::json_rpc_log(
+                                &[
+                                    format!("{:#x}", ProcessFilter::signature()),
+                                    origin_topic.clone(),
+                                    format!("{:#x}", H256::zero()),
+                                    format!("{:#x}", H256::zero()),
+                                ],
+                                "0x",
+                            );
+                            log["transactionHash"] =
+                                format!("{:#x}", EthersH256::from_low_u64_be(tx)).into();
+                            log
+                        })
+                        .collect::<Vec<_>>())
+                }
+                "eth_getTransactionByHash" => {
+                    let hash: EthersH256 = body["params"][0].as_str().unwrap().parse().unwrap();
+                    let input = &inputs[hash.to_low_u64_be() as usize - 1];
+                    test_utils::json_rpc_transaction(
+                        &format!("{hash:#x}"),
+                        &format!("0x{}", hex::encode(input)),
+                    )
+                }
+                method => panic!("unexpected call to {method}"),
+            };
+            test_utils::json_rpc_result(&body, result)
+        })
+        .await;
+        let mailbox = test_utils::mailbox(node.conf(), None);
+
+        assert!(mailbox.delivered_nonce(origin, 5, 90..=110).await.unwrap());
+        assert!(!mailbox.delivered_nonce(origin, 6, 90..=110).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_local_domain_cached() {
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let calls = calls.clone();
+            MockTronNode::start(move |_, body| {
+                assert_eq!(body["method"], "eth_call");
+                calls
+                    .lock()
+                    .unwrap()
+                    .push(body["params"][0]["data"].clone());
+                let result = format!("{:#x}", H256::from_low_u64_be(728126428));
+                test_utils::json_rpc_result(&body, result)
+            })
+            .await
+        };
+        let mailbox = test_utils::mailbox(node.conf(), None);
+
+        assert_eq!(mailbox.local_domain().await.unwrap(), 728126428);
+        assert_eq!(mailbox.local_domain().await.unwrap(), 728126428);
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![format!("0x{}", hex::encode(LocalDomainCall::selector()))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_abi_compatibility() {
+        let (mailbox, _node) =
+            test_utils::mailbox_with_nonce(format!("{:#x}", H256::from_low_u64_be(7))).await;
+
+        mailbox.verify_abi_compatibility().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_abi_compatibility_detects_wrong_nonce_type() {
+        // A `uint256` nonce doesn't fit the `uint32` of the bindings
+        let (mailbox, _node) =
+            test_utils::mailbox_with_nonce(format!("{:#x}", H256::repeat_byte(0xff))).await;
+
+        let err = mailbox.verify_abi_compatibility().await.unwrap_err();
+
+        assert!(
+            err.to_string().contains("call to nonce():(uint32)"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_config_batched() {
+        let multicall_address = H256::from_low_u64_be(0xca11);
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let calls = calls.clone();
+            MockTronNode::start(move |path, body| {
+                if path == "/walletsolidity/getblock" {
+                    return test_utils::block(63_999_995);
+                }
+                calls
+                    .lock()
+                    .unwrap()
+                    .push((body["params"][0]["to"].clone(), body["params"][1].clone()));
+                let word = |value: u64| EthersH256::from_low_u64_be(value).as_bytes().to_vec();
+                let results = [word(728126428), word(42), word(0xaa), word(0xbb)]
+                    .into_iter()
+                    .map(|data| Token::Tuple(vec![Token::Bool(true), Token::Bytes(data)]))
+                    .collect();
+                let result = abi::encode(&[Token::Array(results)]);
+                test_utils::json_rpc_result(&body, format!("0x{}", hex::encode(result)))
+            })
+            .await
+        };
+        let conf = ConnectionConf {
+            multicall_address: Some(multicall_address),
+            ..node.conf()
+        };
+        let mailbox = test_utils::mailbox(conf.clone(), None);
+        let finalized_mailbox = test_utils::mailbox(
+            ConnectionConf {
+                prefer_finalized_reads: true,
+                ..conf
+            },
+            None,
+        );
+
+        let config = mailbox.read_config().await.unwrap();
+        let finalized_config = finalized_mailbox.read_config().await.unwrap();
+
+        let expected = MailboxConfig {
+            local_domain: 728126428,
+            nonce: 42,
+            default_ism: H256::from_low_u64_be(0xaa),
+            default_hook: H256::from_low_u64_be(0xbb),
+        };
+        assert_eq!(config, expected);
+        assert_eq!(finalized_config, expected);
+        let to = serde_json::json!(format!("{:#x}", EthersH160::from_low_u64_be(0xca11)));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                (to.clone(), serde_json::json!("latest")),
+                (to, serde_json::json!(format!("{:#x}", 63_999_995))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivered_batch() {
+        // Messages with an odd id are delivered
+        let delivered = |call: &[u8]| {
+            let call = DeliveredCall::decode(call).unwrap();
+            call.message_id[31] % 2 == 1
+        };
+        let multicalls = Arc::new(Mutex::new(0));
+        let node = {
+            let multicalls = multicalls.clone();
+            MockTronNode::start(move |_, body| {
+                let data = body["params"][0]["data"].as_str().unwrap();
+                let data = hex::decode(data.trim_start_matches("0x")).unwrap();
+                let result = if data.starts_with(&DeliveredCall::selector()) {
+                    abi::encode(&[Token::Bool(delivered(&data))])
+                } else {
+                    *multicalls.lock().unwrap() += 1;
+                    let kind = ParamType::Array(Box::new(ParamType::Tuple(vec![
+                        ParamType::Address,
+                        ParamType::Bool,
+                        ParamType::Bytes,
+                    ])));
+                    let calls = abi::decode(&[kind], &data[4..]).unwrap();
+                    let results = calls[0]
+                        .clone()
+                        .into_array()
+                        .unwrap()
+                        .into_iter()
+                        .map(|call| {
+                            let call = call.into_tuple().unwrap()[2].clone().into_bytes().unwrap();
+                            let status = abi::encode(&[Token::Bool(delivered(&call))]);
+                            Token::Tuple(vec![Token::Bool(true), Token::Bytes(status)])
+                        })
+                        .collect();
+                    abi::encode(&[Token::Array(results)])
+                };
+                test_utils::json_rpc_result(&body, format!("0x{}", hex::encode(result)))
+            })
+            .await
+        };
+        let mailbox = |conf| test_utils::mailbox(conf, None);
+        let ids: Vec<_> = (1..=5).map(H256::from_low_u64_be).collect();
+        let expected = vec![true, false, true, false, true];
+
+        let unbatched = mailbox(node.conf());
+        assert_eq!(unbatched.delivered_batch(&ids).await.unwrap(), expected);
+        assert_eq!(*multicalls.lock().unwrap(), 0);
+
+        let batched = mailbox(ConnectionConf {
+            multicall_address: Some(H256::from_low_u64_be(0xca11)),
+            ..node.conf()
+        });
+        assert_eq!(batched.delivered_batch(&ids).await.unwrap(), expected);
+        assert_eq!(*multicalls.lock().unwrap(), 1);
+    }
+}
@@ -1,23 +1,30 @@
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use tracing::instrument;
+use tracing::{instrument, warn};
 
 use hyperlane_core::{
-    accumulator::incremental::IncrementalMerkle, rpc_clients::call_and_retry_indefinitely,
-    ChainResult, Checkpoint, ContractLocator, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
-    HyperlaneProvider, Indexed, Indexer, LogMeta, MerkleTreeHook, MerkleTreeInsertion, ReorgPeriod,
-    SequenceAwareIndexer, H256, H512,
+    accumulator::incremental::IncrementalMerkle, rpc_clients::RPC_RETRY_SLEEP_DURATION,
+    ChainCommunicationError, ChainResult, Checkpoint, ContractLocator, HyperlaneChain,
+    HyperlaneContract, HyperlaneDomain, HyperlaneProvider, Indexed, Indexer, LogMeta,
+    MerkleTreeHook, MerkleTreeInsertion, ReorgPeriod, SequenceAwareIndexer, H256, H512,
 };
 
 use crate::interfaces::merkle_tree_hook::{
     InsertedIntoTreeFilter, MerkleTreeHook as MerkleTreeHookContract, Tree,
 };
-use crate::{ConnectionConf, TronAddress, TronEthClient, TronProvider};
+use crate::{
+    validate_reorg_period, ConnectionConf, CursorSnapshot, HyperlaneTronError, TronAddress,
+    TronEthClient, TronProvider,
+};
 
-use super::utils::{call_with_reorg_period, fetch_raw_logs_and_meta, get_finalized_block_number};
+use super::utils::{
+    call_with_abi_context, call_with_reorg_period, fetch_logs_in_chunks,
+    fetch_tx_logs_with_retries, get_finalized_block_number,
+};
 
 /// Struct that retrieves event data for an Tron MerkleTreeHook
 #[derive(Debug)]
@@ -33,6 +40,8 @@ impl TronMerkleTreeHookIndexer {
         locator: ContractLocator,
         reorg_period: ReorgPeriod,
     ) -> ChainResult<Self> {
+        validate_reorg_period(locator.domain, &reorg_period);
+
         let address = TronAddress::try_from(locator.address)?;
         let provider = TronProvider::new(locator.domain.clone(), conf)?;
         let contract = Arc::new(MerkleTreeHookContract::new(
@@ -46,6 +55,46 @@ impl TronMerkleTreeHookIndexer {
             reorg_period,
         })
     }
+
+    /// Checks that the node can resolve the reorg period of the indexer, to fail at
+    /// startup rather than on every query if it can't
+    pub async fn ensure_reorg_period_supported(&self) -> ChainResult<()> {
+        self.provider
+            .ensure_reorg_period_supported(&self.reorg_period)
+            .await
+    }
+
+    /// Snapshot of the position of the indexer, to persist and resume from with
+    /// [`Self::resume_block`] after a restart
+    pub async fn cursor_snapshot(&self, last_indexed_block: u32) -> ChainResult<CursorSnapshot> {
+        self.provider.cursor_snapshot(last_indexed_block).await
+    }
+
+    /// Block from which to resume indexing from a snapshot, further back than the
+    /// snapshot if its last indexed block was reorged out since
+    pub async fn resume_block(&self, snapshot: &CursorSnapshot) -> ChainResult<u32> {
+        self.provider.resume_block(snapshot).await
+    }
+
+    /// Insertions of the messages with the given ids in the blocks in `range`, giving
+    /// their leaf indices. Message ids aren't indexed in `InsertedIntoTree` events, so
+    /// all insertions of the range are fetched and filtered here.
+    #[instrument(err, skip(self, ids))]
+    pub async fn insertions_for_ids(
+        &self,
+        ids: &[H256],
+        range: RangeInclusive<u32>,
+    ) -> ChainResult<Vec<MerkleTreeInsertion>> {
+        let ids: HashSet<_> = ids.iter().collect();
+
+        Ok(self
+            .fetch_logs_in_range(range)
+            .await?
+            .into_iter()
+            .map(|(insertion, _)| *insertion.inner())
+            .filter(|insertion| ids.contains(&insertion.message_id()))
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -55,13 +104,15 @@ impl Indexer<MerkleTreeInsertion> for TronMerkleTreeHookIndexer {
         &self,
         range: RangeInclusive<u32>,
     ) -> ChainResult<Vec<(Indexed<MerkleTreeInsertion>, LogMeta)>> {
-        let events = self
-            .contract
-            .inserted_into_tree_filter()
-            .from_block(*range.start())
-            .to_block(*range.end())
-            .query_with_meta()
-            .await?;
+        let events = fetch_logs_in_chunks(&self.provider, range, |range| {
+            let filter = self
+                .contract
+                .inserted_into_tree_filter()
+                .from_block(*range.start())
+                .to_block(*range.end());
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?;
 
         let logs = events
             .into_iter()
@@ -84,15 +135,13 @@ impl Indexer<MerkleTreeInsertion> for TronMerkleTreeHookIndexer {
         &self,
         tx_hash: H512,
     ) -> ChainResult<Vec<(Indexed<MerkleTreeInsertion>, LogMeta)>> {
-        let raw_logs_and_meta = call_and_retry_indefinitely(|| {
-            let provider = self.provider.clone();
-            let contract = self.contract.address();
-            Box::pin(async move {
-                fetch_raw_logs_and_meta::<InsertedIntoTreeFilter>(&provider, contract, tx_hash)
-                    .await
-            })
-        })
-        .await;
+        let raw_logs_and_meta = fetch_tx_logs_with_retries::<InsertedIntoTreeFilter>(
+            &self.provider,
+            self.contract.address(),
+            tx_hash,
+            RPC_RETRY_SLEEP_DURATION,
+        )
+        .await?;
 
         let logs = raw_logs_and_meta
             .into_iter()
@@ -112,7 +161,7 @@ impl Indexer<MerkleTreeInsertion> for TronMerkleTreeHookIndexer {
 impl SequenceAwareIndexer<MerkleTreeInsertion> for TronMerkleTreeHookIndexer {
     async fn latest_sequence_count_and_tip(&self) -> ChainResult<(Option<u32>, u32)> {
         let tip = self.get_finalized_block_number().await?;
-        let sequence = self.contract.count().block(u64::from(tip)).call().await?;
+        let sequence = call_with_abi_context(self.contract.count().block(u64::from(tip))).await?;
         Ok((Some(sequence), tip))
     }
 }
@@ -134,11 +183,159 @@ impl TronMerkleTreeHook {
 
         Ok(TronMerkleTreeHook { provider, contract })
     }
+
+    /// Tree of the hook as it was when it held `count` leaves, to reconstruct
+    /// historical roots.
+    ///
+    /// The first block at which the hook held `count` leaves is found by bisecting
+    /// its `count` since deployment, and the tree is read at that block. If more
+    /// leaves were inserted later in that block, the block's insertions are replayed
+    /// on the tree of the previous block instead.
+    #[instrument(err, skip(self))]
+    pub async fn tree_at_count(&self, count: u32) -> ChainResult<IncrementalMerkle> {
+        let Some(last_index) = count.checked_sub(1) else {
+            return Ok(IncrementalMerkle::default());
+        };
+
+        let deployed_block = call_with_abi_context(self.contract.deployed_block()).await?;
+        let deployed_block = u32::try_from(deployed_block).map_err(|_| {
+            HyperlaneTronError::BlockNumberOverflow(deployed_block.try_into().unwrap_or(u64::MAX))
+        })?;
+        let tip = get_finalized_block_number(&self.provider, &ReorgPeriod::None).await?;
+        if self.count_at_block(tip).await? < count {
+            return Err(ChainCommunicationError::from_other_str(&format!(
+                "No insertion of leaf {last_index} into merkle tree hook {:?}",
+                self.address()
+            )));
+        }
+
+        let (mut low, mut high) = (deployed_block, tip);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.count_at_block(middle).await? >= count {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        let block = low;
+
+        let tree = self.tree_at_block(block).await?;
+        if tree.count() == count as usize {
+            return Ok(tree);
+        }
+
+        // Leaves were inserted after the last one in the same block
+        let mut tree = match block.checked_sub(1) {
+            Some(previous) if previous >= deployed_block => self.tree_at_block(previous).await?,
+            _ => IncrementalMerkle::default(),
+        };
+        let insertions = fetch_logs_in_chunks(&self.provider, block..=block, |range| {
+            let filter = self
+                .contract
+                .inserted_into_tree_filter()
+                .from_block(*range.start())
+                .to_block(*range.end());
+            async move { Ok(filter.query_with_meta().await?) }
+        })
+        .await?;
+        let mut leaves = insertions
+            .into_iter()
+            .map(|(log, _)| log)
+            .filter(|log| log.index as usize >= tree.count() && log.index <= last_index)
+            .collect::<Vec<_>>();
+        leaves.sort_by_key(|log| log.index);
+        leaves
+            .into_iter()
+            .for_each(|log| tree.ingest(H256::from(log.message_id)));
+
+        if tree.count() != count as usize {
+            return Err(ChainCommunicationError::from_other_str(&format!(
+                "Reconstructed merkle tree has {} leaves, expected {count}",
+                tree.count()
+            )));
+        }
+        Ok(tree)
+    }
+
+    /// Whether the root of the latest checkpoint matches the root computed locally
+    /// from the tree, both read at the same block. A mismatch points at a corrupt
+    /// node or at bindings not matching the deployed contract.
+    #[instrument(err, skip(self))]
+    pub async fn verify_checkpoint(&self, reorg_period: &ReorgPeriod) -> ChainResult<bool> {
+        let block = get_finalized_block_number(&self.provider, reorg_period).await?;
+        let tree = self.tree_at_block(block).await?;
+        let (root, index) =
+            call_with_abi_context(self.contract.latest_checkpoint().block(u64::from(block)))
+                .await?;
+
+        let computed_root = tree.root();
+        let matches = computed_root == H256::from(root) && tree.count() == index as usize + 1;
+        if !matches {
+            warn!(
+                block,
+                ?computed_root,
+                count = tree.count(),
+                checkpoint_root = ?H256::from(root),
+                checkpoint_index = index,
+                "Checkpoint of merkle tree hook doesn't match its tree"
+            );
+        }
+        Ok(matches)
+    }
+
+    /// Owner of the hook, zero if it was deployed without being initialized
+    #[instrument(err, skip(self))]
+    pub async fn owner(&self) -> ChainResult<H256> {
+        let owner = call_with_abi_context(self.contract.owner()).await?;
+        Ok(TronAddress::from(owner).into())
+    }
+
+    /// Mailbox the hook was deployed for, immutable
+    #[instrument(err, skip(self))]
+    pub async fn mailbox(&self) -> ChainResult<H256> {
+        let mailbox = call_with_abi_context(self.contract.mailbox()).await?;
+        Ok(TronAddress::from(mailbox).into())
+    }
+
+    /// Domain of the mailbox the hook was deployed for, immutable
+    #[instrument(err, skip(self))]
+    pub async fn local_domain(&self) -> ChainResult<u32> {
+        call_with_abi_context(self.contract.local_domain()).await
+    }
+
+    /// Whether the hook was deployed for `mailbox`. A hook deployed for another
+    /// mailbox reverts the dispatches of this one, as they aren't the latest
+    /// dispatched by its own mailbox.
+    #[instrument(err, skip(self))]
+    pub async fn verify_mailbox(&self, mailbox: H256) -> ChainResult<bool> {
+        let hook_mailbox = self.mailbox().await?;
+        let matches = hook_mailbox == mailbox;
+        if !matches {
+            warn!(
+                ?mailbox,
+                ?hook_mailbox,
+                "Merkle tree hook was deployed for another mailbox"
+            );
+        }
+        Ok(matches)
+    }
+
+    async fn tree_at_block(&self, block: u32) -> ChainResult<IncrementalMerkle> {
+        let tree = call_with_abi_context(self.contract.tree().block(u64::from(block)))
+            .await?
+            .into();
+        Ok(tree)
+    }
+
+    async fn count_at_block(&self, block: u32) -> ChainResult<u32> {
+        call_with_abi_context(self.contract.count().block(u64::from(block))).await
+    }
 }
 
 impl HyperlaneContract for TronMerkleTreeHook {
     fn address(&self) -> H256 {
-        self.contract.address().into()
+        TronAddress::from(self.contract.address()).into()
     }
 }
 
@@ -163,7 +360,7 @@ impl MerkleTreeHook for TronMerkleTreeHook {
         )
         .await?;
 
-        let (root, index) = call.call().await?;
+        let (root, index) = call_with_abi_context(call).await?;
         Ok(Checkpoint {
             merkle_tree_hook_address: self.address(),
             mailbox_domain: self.domain().id(),
@@ -178,7 +375,7 @@ impl MerkleTreeHook for TronMerkleTreeHook {
         let call =
             call_with_reorg_period(&self.provider, reorg_period, self.contract.tree()).await?;
 
-        let tree = call.call().await?.into();
+        let tree = call_with_abi_context(call).await?.into();
         Ok(tree)
     }
 
@@ -187,7 +384,7 @@ impl MerkleTreeHook for TronMerkleTreeHook {
         let call =
             call_with_reorg_period(&self.provider, reorg_period, self.contract.count()).await?;
 
-        let count = call.call().await?;
+        let count = call_with_abi_context(call).await?;
         Ok(count)
     }
 }
@@ -208,3 +405,289 @@ impl Into<IncrementalMerkle> for Tree {
         IncrementalMerkle::new(branch, self.count.as_usize())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ethers::abi::AbiEncode;
+    use ethers::contract::{EthCall, EthEvent};
+    use ethers::types::{H160 as EthersH160, H256 as EthersH256, U256};
+    use ethers::utils::hex;
+    use hyperlane_core::{ContractLocator, HyperlaneDomain, KnownHyperlaneDomain, H256};
+
+    use crate::interfaces::merkle_tree_hook::{
+        CountCall, DeployedBlockCall, InsertedIntoTreeFilter, LatestCheckpointCall,
+        LatestCheckpointReturn, LocalDomainCall, MailboxCall, OwnerCall, Tree, TreeCall,
+        TreeReturn,
+    };
+    use crate::test_utils::{self, MockTronNode};
+
+    use super::{
+        IncrementalMerkle, MerkleTreeInsertion, ReorgPeriod, TronMerkleTreeHook,
+        TronMerkleTreeHookIndexer,
+    };
+
+    fn leaf(index: u32) -> H256 {
+        H256::from_low_u64_be(index as u64 + 1)
+    }
+
+    fn tree_with_leaves(count: u32) -> IncrementalMerkle {
+        let mut tree = IncrementalMerkle::default();
+        (0..count).for_each(|index| tree.ingest(leaf(index)));
+        tree
+    }
+
+    fn encode_tree(tree: IncrementalMerkle) -> String {
+        let tree = TreeReturn(Tree {
+            branch: tree.branch.map(|node| node.0),
+            count: U256::from(tree.count),
+        });
+        format!("0x{}", hex::encode(tree.encode()))
+    }
+
+    fn insertion_log(index: u32, block: u64) -> serde_json::Value {
+        let data = (EthersH256::from(leaf(index).0), index).encode();
+        let mut log = test_utils::json_rpc_log(
+            &[format!("{:#x}", InsertedIntoTreeFilter::signature())],
+            &format!("0x{}", hex::encode(data)),
+        );
+        log["blockNumber"] = format!("{block:#x}").into();
+        log["transactionHash"] = format!("{:#x}", EthersH256::from_low_u64_be(index as u64)).into();
+        log["transactionIndex"] = format!("{index:#x}").into();
+        log
+    }
+
+    /// Hook whose leaf 0 was inserted at block 100 and leaves 1 and 2 at block 110
+    async fn merkle_tree_hook() -> (TronMerkleTreeHook, MockTronNode) {
+        let node = MockTronNode::start(|path, body| {
+            if path == "/wallet/getnowblock" {
+                return test_utils::block(200);
+            }
+
+            let parse_block = |value: &serde_json::Value| {
+                u64::from_str_radix(value.as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+            };
+            let count_at = |block: u64| match block {
+                ..=99 => 0,
+                100..=109 => 1,
+                _ => 3,
+            };
+
+            let result = match body["method"].as_str().unwrap() {
+                "eth_getLogs" => {
+                    let filter = &body["params"][0];
+                    let range = parse_block(&filter["fromBlock"])..=parse_block(&filter["toBlock"]);
+                    let logs = [(0, 100), (1, 110), (2, 110)]
+                        .into_iter()
+                        .filter(|(_, block)| range.contains(block))
+                        .map(|(index, block)| insertion_log(index, block))
+                        .collect::<Vec<_>>();
+                    serde_json::json!(logs)
+                }
+                "eth_call" => {
+                    let data = body["params"][0]["data"].as_str().unwrap();
+                    if data == format!("0x{}", hex::encode(DeployedBlockCall::selector())) {
+                        serde_json::json!(format!("{:#x}", H256::from_low_u64_be(90)))
+                    } else if data == format!("0x{}", hex::encode(CountCall::selector())) {
+                        let count: u32 = count_at(parse_block(&body["params"][1]));
+                        serde_json::json!(format!("0x{}", hex::encode(count.encode())))
+                    } else {
+                        assert_eq!(data, format!("0x{}", hex::encode(TreeCall::selector())));
+                        let count = count_at(parse_block(&body["params"][1]));
+                        serde_json::json!(encode_tree(tree_with_leaves(count)))
+                    }
+                }
+                method => panic!("Unexpected method {method}"),
+            };
+            test_utils::json_rpc_result(&body, result)
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let hook =
+            TronMerkleTreeHook::new(node.conf(), ContractLocator::new(&domain, H256::zero()))
+                .unwrap();
+
+        (hook, node)
+    }
+
+    /// Hook with a tree of 3 leaves reporting the given checkpoint
+    async fn merkle_tree_hook_with_checkpoint(
+        root: H256,
+        index: u32,
+    ) -> (TronMerkleTreeHook, MockTronNode) {
+        let node = MockTronNode::start(move |path, body| {
+            if path == "/wallet/getnowblock" {
+                return test_utils::block(200);
+            }
+
+            assert_eq!(body["method"], "eth_call");
+            assert_eq!(body["params"][1], "0xc8");
+            let data = body["params"][0]["data"].as_str().unwrap();
+            let result = if data == format!("0x{}", hex::encode(LatestCheckpointCall::selector())) {
+                format!(
+                    "0x{}",
+                    hex::encode(LatestCheckpointReturn(root.0, index).encode())
+                )
+            } else {
+                encode_tree(tree_with_leaves(3))
+            };
+            test_utils::json_rpc_result(&body, result)
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let hook =
+            TronMerkleTreeHook::new(node.conf(), ContractLocator::new(&domain, H256::zero()))
+                .unwrap();
+
+        (hook, node)
+    }
+
+    /// Hook deployed for `mailbox` on domain 1000, owned by `owner`
+    async fn merkle_tree_hook_with_config(
+        owner: EthersH160,
+        mailbox: EthersH160,
+    ) -> (TronMerkleTreeHook, MockTronNode) {
+        let node = MockTronNode::start(move |_, body| {
+            assert_eq!(body["method"], "eth_call");
+            let data = body["params"][0]["data"].as_str().unwrap();
+            let selector = hex::decode(data.trim_start_matches("0x")).unwrap();
+            let result = if selector == OwnerCall::selector() {
+                owner.encode()
+            } else if selector == MailboxCall::selector() {
+                mailbox.encode()
+            } else {
+                assert_eq!(selector, LocalDomainCall::selector());
+                1000u32.encode()
+            };
+            test_utils::json_rpc_result(&body, format!("0x{}", hex::encode(result)))
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let hook =
+            TronMerkleTreeHook::new(node.conf(), ContractLocator::new(&domain, H256::zero()))
+                .unwrap();
+
+        (hook, node)
+    }
+
+    #[tokio::test]
+    async fn test_owner_and_mailbox() {
+        let (hook, _node) = merkle_tree_hook_with_config(
+            EthersH160::repeat_byte(0xaa),
+            EthersH160::repeat_byte(0xbb),
+        )
+        .await;
+
+        assert_eq!(
+            hook.owner().await.unwrap(),
+            H256::from(EthersH160::repeat_byte(0xaa))
+        );
+        assert_eq!(
+            hook.mailbox().await.unwrap(),
+            H256::from(EthersH160::repeat_byte(0xbb))
+        );
+        assert_eq!(hook.local_domain().await.unwrap(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_verify_mailbox() {
+        let (hook, _node) = merkle_tree_hook_with_config(
+            EthersH160::repeat_byte(0xaa),
+            EthersH160::repeat_byte(0xbb),
+        )
+        .await;
+
+        assert!(hook
+            .verify_mailbox(H256::from(EthersH160::repeat_byte(0xbb)))
+            .await
+            .unwrap());
+        assert!(!hook
+            .verify_mailbox(H256::from(EthersH160::repeat_byte(0xaa)))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_checkpoint() {
+        let tree = tree_with_leaves(3);
+        let (hook, _node) = merkle_tree_hook_with_checkpoint(tree.root(), 2).await;
+
+        assert!(hook.verify_checkpoint(&ReorgPeriod::None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_checkpoint_detects_mismatch() {
+        let tree = tree_with_leaves(3);
+
+        let (hook, _node) = merkle_tree_hook_with_checkpoint(H256::repeat_byte(0xee), 2).await;
+        assert!(!hook.verify_checkpoint(&ReorgPeriod::None).await.unwrap());
+
+        let (hook, _node) = merkle_tree_hook_with_checkpoint(tree.root(), 1).await;
+        assert!(!hook.verify_checkpoint(&ReorgPeriod::None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tree_at_count() {
+        let (hook, _node) = merkle_tree_hook().await;
+
+        for count in 0..=3 {
+            let tree = hook.tree_at_count(count).await.unwrap();
+            assert_eq!(tree.count(), count as usize);
+            assert_eq!(tree, tree_with_leaves(count));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insertions_for_ids() {
+        let (_, node) = merkle_tree_hook().await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let indexer = TronMerkleTreeHookIndexer::new(
+            node.conf(),
+            ContractLocator::new(&domain, H256::zero()),
+            ReorgPeriod::None,
+        )
+        .unwrap();
+
+        let insertions = indexer
+            .insertions_for_ids(&[leaf(2), leaf(0), H256::repeat_byte(0xee)], 90..=120)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            insertions,
+            vec![
+                MerkleTreeInsertion::new(0, leaf(0)),
+                MerkleTreeInsertion::new(2, leaf(2))
+            ]
+        );
+        assert!(indexer
+            .insertions_for_ids(&[], 90..=120)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tree_at_count_beyond_insertions() {
+        let (hook, _node) = merkle_tree_hook().await;
+
+        let err = hook.tree_at_count(4).await.unwrap_err();
+
+        assert!(err.to_string().contains("No insertion of leaf 3"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_tree_at_count_with_deployed_block_beyond_u32() {
+        let node = MockTronNode::start(|_, body| {
+            test_utils::json_rpc_result(&body, format!("{:#x}", H256::from_low_u64_be(1 << 40)))
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let hook =
+            TronMerkleTreeHook::new(node.conf(), ContractLocator::new(&domain, H256::zero()))
+                .unwrap();
+
+        let err = hook.tree_at_count(1).await.unwrap_err();
+
+        assert!(err.to_string().contains("doesn't fit in a u32"), "{err}");
+    }
+}
@@ -1,6 +1,11 @@
-pub use {mailbox::*, merkle_tree_hook::*, validator_announce::*};
+pub(crate) use revert_reason::decode_revert_reason;
+pub use utils::TX_LIFECYCLE_TARGET;
+pub use {mailbox::*, merkle_tree_hook::*, multisig_ism::*, validator_announce::*};
 
 mod mailbox;
 mod merkle_tree_hook;
+mod multisig_ism;
+mod revert_reason;
+mod submission_queue;
 mod utils;
 mod validator_announce;
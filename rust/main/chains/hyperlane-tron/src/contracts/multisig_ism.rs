@@ -0,0 +1,395 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::instrument;
+
+use hyperlane_core::{
+    utils::bytes_to_hex, ChainCommunicationError, ChainResult, ContractLocator, HyperlaneChain,
+    HyperlaneContract, HyperlaneDomain, HyperlaneMessage, HyperlaneProvider, MultisigIsm,
+    RawHyperlaneMessage, ValidatorAnnounce, H256,
+};
+
+use crate::interfaces::i_multisig_ism::IMultisigIsm as MultisigIsmContract;
+use crate::{ConnectionConf, TronAddress, TronEthClient, TronProvider};
+
+use super::utils::{call_with_abi_context, verify_metadata};
+
+/// A reference to a MultisigIsm contract on some Tron chain
+#[derive(Debug)]
+pub struct TronMultisigIsm {
+    contract: Arc<MultisigIsmContract<TronEthClient>>,
+    provider: TronProvider,
+}
+
+impl TronMultisigIsm {
+    pub fn new(conf: ConnectionConf, locator: ContractLocator) -> ChainResult<Self> {
+        let address = TronAddress::try_from(locator.address)?;
+        let provider = TronProvider::new(locator.domain.clone(), conf)?;
+        let contract = Arc::new(MultisigIsmContract::new(
+            address,
+            provider.eth_client.clone(),
+        ));
+
+        Ok(TronMultisigIsm { contract, provider })
+    }
+
+    /// Validators of the ISM for `message`, each paired with the storage locations
+    /// it announced on `validator_announce`, in the order of the ISM. Validators that
+    /// never announced are returned with no locations.
+    #[instrument(err, skip(self, validator_announce))]
+    pub async fn announced_validators(
+        &self,
+        message: &HyperlaneMessage,
+        validator_announce: &dyn ValidatorAnnounce,
+    ) -> ChainResult<Vec<(H256, Vec<String>)>> {
+        let (validators, _) = self.validators_and_threshold(message).await?;
+
+        with_announced_locations(validators, validator_announce).await
+    }
+
+    /// Number of distinct validators of the ISM for `message` that announced a storage
+    /// location on `validator_announce`, along with the signature threshold. Metadata for the
+    /// message can only be built, and the message delivered, if enough validators are
+    /// reachable to meet the threshold.
+    #[instrument(err, skip(self, validator_announce))]
+    pub async fn coverage(
+        &self,
+        message: &HyperlaneMessage,
+        validator_announce: &dyn ValidatorAnnounce,
+    ) -> ChainResult<(usize, u8)> {
+        let (validators, threshold) = self.validators_and_threshold(message).await?;
+        let announced = with_announced_locations(validators, validator_announce)
+            .await?
+            .into_iter()
+            .filter(|(_, locations)| !locations.is_empty())
+            .map(|(validator, _)| validator)
+            .collect::<HashSet<_>>()
+            .len();
+
+        Ok((announced, threshold))
+    }
+
+    /// Whether `metadata` passes the verification of `message` by the ISM. Checked
+    /// with a constant call, so that no TRX is spent on a `process` bound to fail.
+    #[instrument(err, skip(self), fields(msg=%message, metadata=%bytes_to_hex(metadata)))]
+    #[allow(clippy::blocks_in_conditions)] // TODO: `rustc` 1.80.1 clippy issue
+    pub async fn verify_metadata(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+    ) -> ChainResult<bool> {
+        verify_metadata(
+            &self.provider,
+            self.contract.address(),
+            message,
+            metadata,
+            None,
+        )
+        .await
+    }
+}
+
+async fn with_announced_locations(
+    validators: Vec<H256>,
+    validator_announce: &dyn ValidatorAnnounce,
+) -> ChainResult<Vec<(H256, Vec<String>)>> {
+    let locations = validator_announce
+        .get_announced_storage_locations(&validators)
+        .await?;
+
+    if locations.len() != validators.len() {
+        return Err(ChainCommunicationError::from_other_str(&format!(
+            "Expected storage locations of {} validators, got {}",
+            validators.len(),
+            locations.len()
+        )));
+    }
+
+    Ok(validators.into_iter().zip(locations).collect())
+}
+
+impl HyperlaneChain for TronMultisigIsm {
+    fn domain(&self) -> &HyperlaneDomain {
+        self.provider.domain()
+    }
+
+    fn provider(&self) -> Box<dyn HyperlaneProvider> {
+        self.provider.provider()
+    }
+}
+
+impl HyperlaneContract for TronMultisigIsm {
+    fn address(&self) -> H256 {
+        TronAddress::from(self.contract.address()).into()
+    }
+}
+
+#[async_trait]
+impl MultisigIsm for TronMultisigIsm {
+    /// Validators of the ISM for `message` in the order of `validatorsAndThreshold`,
+    /// along with the signature threshold. Multisig ISMs check signatures in validator
+    /// order, so the signatures in metadata must follow this order.
+    #[instrument(err, skip(self, message))]
+    #[allow(clippy::blocks_in_conditions)] // TODO: `rustc` 1.80.1 clippy issue
+    async fn validators_and_threshold(
+        &self,
+        message: &HyperlaneMessage,
+    ) -> ChainResult<(Vec<H256>, u8)> {
+        let (validator_addresses, threshold) = call_with_abi_context(
+            self.contract
+                .validators_and_threshold(RawHyperlaneMessage::from(message).to_vec().into()),
+        )
+        .await?;
+        let validators = validator_addresses.into_iter().map(H256::from).collect();
+
+        Ok((validators, threshold))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::abi::Token;
+    use ethers::utils::hex;
+    use hyperlane_core::{Announcement, KnownHyperlaneDomain, SignedType, TxOutcome, U256};
+
+    use url::Url;
+
+    use crate::test_utils::{self, MockTronNode};
+
+    use super::*;
+
+    /// Validator announce returning fixed storage locations
+    #[derive(Debug)]
+    struct StaticValidatorAnnounce {
+        provider: TronProvider,
+        locations: Vec<Vec<String>>,
+    }
+
+    impl StaticValidatorAnnounce {
+        fn new(locations: Vec<Vec<String>>) -> Self {
+            // Never queried
+            let url = Url::parse("http://127.0.0.1:1").unwrap();
+            Self {
+                provider: test_utils::provider(test_utils::conf(url)),
+                locations,
+            }
+        }
+    }
+
+    impl HyperlaneChain for StaticValidatorAnnounce {
+        fn domain(&self) -> &HyperlaneDomain {
+            self.provider.domain()
+        }
+
+        fn provider(&self) -> Box<dyn HyperlaneProvider> {
+            self.provider.provider()
+        }
+    }
+
+    impl HyperlaneContract for StaticValidatorAnnounce {
+        fn address(&self) -> H256 {
+            H256::zero()
+        }
+    }
+
+    #[async_trait]
+    impl ValidatorAnnounce for StaticValidatorAnnounce {
+        async fn get_announced_storage_locations(
+            &self,
+            _validators: &[H256],
+        ) -> ChainResult<Vec<Vec<String>>> {
+            Ok(self.locations.clone())
+        }
+
+        async fn announce(
+            &self,
+            _announcement: SignedType<Announcement>,
+        ) -> ChainResult<TxOutcome> {
+            Err(ChainCommunicationError::from_other_str(
+                "Static validator announce can't announce",
+            ))
+        }
+
+        async fn announce_tokens_needed(
+            &self,
+            _announcement: SignedType<Announcement>,
+        ) -> Option<U256> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_announced_locations() {
+        let validators = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let validator_announce =
+            StaticValidatorAnnounce::new(vec![vec!["s3://bucket/us-east-1".to_owned()], vec![]]);
+
+        let announced = with_announced_locations(validators.clone(), &validator_announce)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            announced,
+            vec![
+                (validators[0], vec!["s3://bucket/us-east-1".to_owned()]),
+                (validators[1], vec![]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_announced_locations_length_mismatch() {
+        let validator_announce = StaticValidatorAnnounce::new(vec![]);
+
+        let result =
+            with_announced_locations(vec![H256::from_low_u64_be(1)], &validator_announce).await;
+
+        assert!(result.is_err());
+    }
+
+    /// ISM behind a node answering every JSON-RPC call with `response`
+    async fn ism(response: serde_json::Value) -> (TronMultisigIsm, MockTronNode) {
+        let node = MockTronNode::start(move |_, body| {
+            assert_eq!(body["method"], "eth_call");
+            let mut response = response.clone();
+            response["jsonrpc"] = "2.0".into();
+            response["id"] = body["id"].clone();
+            response
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let ism =
+            TronMultisigIsm::new(node.conf(), ContractLocator::new(&domain, H256::zero())).unwrap();
+
+        (ism, node)
+    }
+
+    /// Response of `validatorsAndThreshold`
+    fn validators_and_threshold_response(validators: &[H256], threshold: u8) -> serde_json::Value {
+        let encoded = ethers::abi::encode(&[
+            Token::Array(
+                validators
+                    .iter()
+                    .map(|validator| {
+                        Token::Address(TronAddress::try_from(*validator).unwrap().into())
+                    })
+                    .collect(),
+            ),
+            Token::Uint(threshold.into()),
+        ]);
+        serde_json::json!({ "result": format!("0x{}", hex::encode(encoded)) })
+    }
+
+    #[tokio::test]
+    async fn test_validators_and_threshold_preserves_order() {
+        // Not sorted by address, as the ISM may store them
+        let validators = [3u64, 1, 2].map(H256::from_low_u64_be);
+        let (ism, _node) = ism(validators_and_threshold_response(&validators, 2)).await;
+
+        let (returned, threshold) = ism
+            .validators_and_threshold(&HyperlaneMessage::default())
+            .await
+            .unwrap();
+
+        assert_eq!(returned, validators);
+        assert_eq!(threshold, 2);
+    }
+
+    #[tokio::test]
+    async fn test_coverage() {
+        let validators = [1u64, 2, 3].map(H256::from_low_u64_be);
+        let (ism, _node) = ism(validators_and_threshold_response(&validators, 2)).await;
+        let message = HyperlaneMessage::default();
+
+        // The second validator never announced
+        let validator_announce = StaticValidatorAnnounce::new(vec![
+            vec!["s3://bucket/us-east-1".to_owned()],
+            vec![],
+            vec![
+                "s3://bucket/eu-west-1".to_owned(),
+                "file:///tmp/checkpoints".to_owned(),
+            ],
+        ]);
+        assert_eq!(
+            ism.coverage(&message, &validator_announce).await.unwrap(),
+            (2, 2)
+        );
+
+        let validator_announce = StaticValidatorAnnounce::new(vec![
+            vec![],
+            vec![],
+            vec!["s3://bucket/eu-west-1".to_owned()],
+        ]);
+        assert_eq!(
+            ism.coverage(&message, &validator_announce).await.unwrap(),
+            (1, 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coverage_counts_validators_once() {
+        let validators = [1u64, 1, 2].map(H256::from_low_u64_be);
+        let (ism, _node) = ism(validators_and_threshold_response(&validators, 2)).await;
+
+        let validator_announce = StaticValidatorAnnounce::new(vec![
+            vec!["s3://bucket/us-east-1".to_owned()],
+            vec!["s3://bucket/us-east-1".to_owned()],
+            vec![],
+        ]);
+        assert_eq!(
+            ism.coverage(&HyperlaneMessage::default(), &validator_announce)
+                .await
+                .unwrap(),
+            (1, 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_metadata() {
+        let (ism, _node) = ism(serde_json::json!({
+            "result": format!("{:#x}", H256::from_low_u64_be(1))
+        }))
+        .await;
+
+        let verified = ism
+            .verify_metadata(&HyperlaneMessage::default(), &[1, 2, 3])
+            .await
+            .unwrap();
+
+        assert!(verified);
+    }
+
+    #[tokio::test]
+    async fn test_verify_metadata_reverted() {
+        let (ism, _node) = ism(serde_json::json!({
+            "error": {
+                "code": 3,
+                "message": "execution reverted: !threshold",
+                "data": "0x08c379a0"
+            }
+        }))
+        .await;
+
+        let verified = ism
+            .verify_metadata(&HyperlaneMessage::default(), &[1, 2, 3])
+            .await
+            .unwrap();
+
+        assert!(!verified);
+    }
+
+    #[tokio::test]
+    async fn test_verify_metadata_node_error() {
+        let (ism, _node) = ism(serde_json::json!({
+            "error": {"code": -32000, "message": "server busy"}
+        }))
+        .await;
+
+        let result = ism
+            .verify_metadata(&HyperlaneMessage::default(), &[1, 2, 3])
+            .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use ethers::{
+    abi::{self, ParamType, Token},
+    utils::{hex, id},
+};
+
+/// Selector of `Error(string)`, the revert of `require` with a message
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`, the revert of failed assertions and arithmetic
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Custom errors of the Hyperlane contracts, named in revert reasons
+const KNOWN_CUSTOM_ERRORS: &[&str] = &[
+    // Mailbox
+    "AlreadyDelivered()",
+    "BadDestination()",
+    "BadVersion()",
+    "NonDeliveredMessage()",
+    "Paused()",
+    // ISMs
+    "InvalidMetadata()",
+    "ThresholdNotMet()",
+    "VerificationFailed()",
+    "OffchainLookup(address,string[],bytes,bytes4,bytes)",
+    // Hooks
+    "InsufficientGasPayment()",
+    "MessageNotDispatching()",
+    "RateLimitExceeded(uint256,uint256)",
+];
+
+/// Human readable reason of a revert with the given data: the message of a
+/// `require`, the code of a panic, the name of a known custom error, or the raw
+/// data as hex
+pub(crate) fn decode_revert_reason(data: &[u8]) -> String {
+    let Some((selector, args)) = data.split_first_chunk::<4>() else {
+        return format!("0x{}", hex::encode(data));
+    };
+
+    match *selector {
+        ERROR_SELECTOR => {
+            if let Ok(Some(Token::String(message))) =
+                abi::decode(&[ParamType::String], args).map(|tokens| tokens.into_iter().next())
+            {
+                return message;
+            }
+        }
+        PANIC_SELECTOR => {
+            if let Ok(Some(Token::Uint(code))) =
+                abi::decode(&[ParamType::Uint(256)], args).map(|tokens| tokens.into_iter().next())
+            {
+                return format!("Panic({code:#x})");
+            }
+        }
+        _ => {
+            if let Some(signature) = KNOWN_CUSTOM_ERRORS
+                .iter()
+                .find(|signature| id(signature) == *selector)
+            {
+                return signature
+                    .split_once('(')
+                    .map_or(*signature, |(name, _)| name)
+                    .to_owned();
+            }
+        }
+    }
+    format!("0x{}", hex::encode(data))
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::abi::encode;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_revert_reason() {
+        // RateLimitExceeded(1000, 500)
+        let mut data = id("RateLimitExceeded(uint256,uint256)").to_vec();
+        data.extend(encode(&[Token::Uint(1000.into()), Token::Uint(500.into())]));
+        assert_eq!(decode_revert_reason(&data), "RateLimitExceeded");
+
+        let mut data = ERROR_SELECTOR.to_vec();
+        data.extend(encode(&[Token::String(
+            "Mailbox: already delivered".into(),
+        )]));
+        assert_eq!(decode_revert_reason(&data), "Mailbox: already delivered");
+
+        // Arithmetic overflow
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(encode(&[Token::Uint(0x11.into())]));
+        assert_eq!(decode_revert_reason(&data), "Panic(0x11)");
+
+        // Unknown custom errors and malformed data are kept as hex
+        assert_eq!(
+            decode_revert_reason(&[0xde, 0xad, 0xbe, 0xef]),
+            "0xdeadbeef"
+        );
+        assert_eq!(decode_revert_reason(&ERROR_SELECTOR), "0x08c379a0");
+        assert_eq!(decode_revert_reason(&[]), "0x");
+    }
+
+    #[test]
+    fn test_decode_mailbox_custom_error() {
+        let reason = decode_revert_reason(&id("BadDestination()"));
+        assert_eq!(reason, "BadDestination");
+
+        let err = crate::HyperlaneTronError::Reverted {
+            context: "process".into(),
+            reason,
+        };
+        assert_eq!(err.to_string(), "process reverted: BadDestination");
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+/// Orders the submission of messages to the same destination by nonce, for recipients
+/// expecting them in order. Among the messages queued at a time, the one with the
+/// lowest nonce is broadcast first. A message queued after a later one was broadcast
+/// can't be broadcast before it.
+#[derive(Debug, Default)]
+pub(crate) struct SubmissionQueue {
+    /// Nonces of the messages queued, by destination
+    queued: Mutex<HashMap<u32, BTreeSet<u32>>>,
+    dequeued: Notify,
+}
+
+impl SubmissionQueue {
+    /// Queues a message. It leaves the queue when the returned ticket is dropped.
+    pub fn enqueue(self: &Arc<Self>, destination: u32, nonce: u32) -> SubmissionTicket {
+        self.queued
+            .lock()
+            .unwrap()
+            .entry(destination)
+            .or_default()
+            .insert(nonce);
+
+        SubmissionTicket {
+            queue: self.clone(),
+            destination,
+            nonce,
+        }
+    }
+
+    fn is_first(&self, destination: u32, nonce: u32) -> bool {
+        self.queued
+            .lock()
+            .unwrap()
+            .get(&destination)
+            .and_then(|nonces| nonces.first())
+            .map_or(true, |first| *first == nonce)
+    }
+}
+
+/// Place of a message in a [`SubmissionQueue`], to be dropped once the message is
+/// broadcast
+#[derive(Debug)]
+pub(crate) struct SubmissionTicket {
+    queue: Arc<SubmissionQueue>,
+    destination: u32,
+    nonce: u32,
+}
+
+impl SubmissionTicket {
+    /// Waits until no message with a lower nonce to the same destination is queued
+    pub async fn wait_turn(&self) {
+        loop {
+            // Created before checking, so that a dequeue in between isn't missed
+            let dequeued = self.queue.dequeued.notified();
+            if self.queue.is_first(self.destination, self.nonce) {
+                return;
+            }
+            dequeued.await;
+        }
+    }
+}
+
+impl Drop for SubmissionTicket {
+    fn drop(&mut self) {
+        let mut queued = self.queue.queued.lock().unwrap();
+        if let Some(nonces) = queued.get_mut(&self.destination) {
+            nonces.remove(&self.nonce);
+            if nonces.is_empty() {
+                queued.remove(&self.destination);
+            }
+        }
+        drop(queued);
+        self.queue.dequeued.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lowest_nonce_goes_first() {
+        let queue = Arc::new(SubmissionQueue::default());
+        let first = queue.enqueue(1, 7);
+        let second = queue.enqueue(1, 5);
+        // Other destinations aren't ordered with these
+        let other_destination = queue.enqueue(2, 9);
+
+        second.wait_turn().await;
+        other_destination.wait_turn().await;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), first.wait_turn())
+                .await
+                .is_err()
+        );
+
+        drop(second);
+        first.wait_turn().await;
+    }
+}
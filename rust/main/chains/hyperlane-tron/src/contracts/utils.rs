@@ -1,16 +1,185 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
 use ethers::{
-    abi::{Detokenize, RawLog},
-    contract::{builders::ContractCall, EthCall, EthEvent, LogMeta as EthersLogMeta},
+    abi::{AbiError, Detokenize, Function, InvalidOutputType, Param, ParamType, RawLog},
+    contract::{
+        builders::ContractCall, ContractError, EthCall, EthEvent, LogMeta as EthersLogMeta,
+    },
     providers::Middleware,
     types::H160 as EthersH160,
+    utils::hex,
 };
-use heliosphere::MethodCall;
+use futures::future::join_all;
+use heliosphere::{AccountResources, MethodCall};
+use heliosphere_core::transaction::{Transaction, TransactionId};
 use heliosphere_signer::signer::Signer as _;
-use tracing::instrument;
+use tokio::sync::Semaphore;
+use tracing::{debug, instrument, trace, warn};
+
+use hyperlane_core::{
+    ChainResult, HyperlaneMessage, LogMeta, RawHyperlaneMessage, ReorgPeriod, TxOutcome, H256,
+    H512, U256,
+};
+
+use crate::interfaces::i_interchain_security_module::IInterchainSecurityModule as InterchainSecurityModuleContract;
+use crate::{
+    decode_revert_reason, is_expiration_error, set_expiration, sun_to_fixed_point,
+    transaction_json, HyperlaneTronError, Signer, TronAddress, TronProvider, TronTxResult,
+};
+
+use super::submission_queue::SubmissionTicket;
+
+/// Attempts at fetching the logs of a transaction unknown to the node before giving up
+const TX_NOT_FOUND_ATTEMPTS: u32 = 5;
+
+/// Bytes of a signed transaction besides its call parameter: raw data fields,
+/// addresses, signature and protobuf framing, with some margin
+pub(crate) const TRANSACTION_ENVELOPE_SIZE: usize = 512;
+
+/// Target of the events tracing the lifecycle of sent transactions, kept stable for
+/// monitoring to filter on
+pub const TX_LIFECYCLE_TARGET: &str = "hyperlane_tron::tx_lifecycle";
+
+/// Stage of a sent transaction, reported in the `stage` field of lifecycle events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TxLifecycleStage {
+    /// Pricing and building the transaction
+    Estimating,
+    /// Signed, about to be broadcast
+    Broadcasting,
+    /// Accepted by the node
+    Broadcast,
+    /// Executed successfully on chain
+    Confirmed,
+    /// Rejected by the node, or not executed successfully
+    Failed,
+}
+
+impl TxLifecycleStage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TxLifecycleStage::Estimating => "estimating",
+            TxLifecycleStage::Broadcasting => "broadcasting",
+            TxLifecycleStage::Broadcast => "broadcast",
+            TxLifecycleStage::Confirmed => "confirmed",
+            TxLifecycleStage::Failed => "failed",
+        }
+    }
+}
+
+/// Emits a lifecycle event, which costs nothing unless a subscriber enables the target
+fn tx_lifecycle_event(stage: TxLifecycleStage, txid: Option<&TransactionId>) {
+    debug!(
+        target: TX_LIFECYCLE_TARGET,
+        stage = stage.as_str(),
+        txid = txid.map(tracing::field::debug),
+        "Tron transaction {}",
+        stage.as_str()
+    );
+}
+
+/// Calls a contract view, naming the called method in decoding errors so that a
+/// drift between the deployed contract and the generated bindings is diagnosable
+pub(crate) async fn call_with_abi_context<M, D>(call: ContractCall<M, D>) -> ChainResult<D>
+where
+    M: Middleware + 'static,
+    D: Detokenize,
+{
+    call.call().await.map_err(|err| match err {
+        ContractError::AbiError(err) => {
+            HyperlaneTronError::abi(function_context(&call.function), err).into()
+        }
+        ContractError::DecodingError(err) => HyperlaneTronError::abi(
+            function_context(&call.function),
+            AbiError::DecodingError(err),
+        )
+        .into(),
+        ContractError::Revert(data) => HyperlaneTronError::Reverted {
+            context: call.function.name.clone(),
+            reason: decode_revert_reason(&data),
+        }
+        .into(),
+        err => err.into(),
+    })
+}
 
-use hyperlane_core::{ChainResult, LogMeta, ReorgPeriod, TxOutcome, H256, H512, U256};
+/// Checks that a contract view returns data of the shape its binding expects.
+/// Stricter than decoding, which silently truncates e.g. a `uint256` returned
+/// where the binding expects a `uint32`.
+pub(crate) async fn verify_return_shape<M, D>(call: &ContractCall<M, D>) -> ChainResult<()>
+where
+    M: Middleware + 'static,
+    D: Detokenize,
+{
+    let data = call
+        .call_raw_bytes()
+        .await
+        .map_err(HyperlaneTronError::from)?;
+
+    check_return_shape(&call.function.outputs, &data).map_err(|reason| {
+        HyperlaneTronError::abi(
+            function_context(&call.function),
+            AbiError::DetokenizationError(InvalidOutputType(reason)),
+        )
+        .into()
+    })
+}
+
+/// Checks the ABI encoded `data` against the expected `outputs`, word by word for
+/// the elementary types
+fn check_return_shape(outputs: &[Param], data: &[u8]) -> Result<(), String> {
+    if data.len() % 32 != 0 || data.len() / 32 < outputs.len() {
+        return Err(format!(
+            "expected {} words, got {} bytes",
+            outputs.len(),
+            data.len()
+        ));
+    }
+
+    let elementary = |kind: &ParamType| {
+        matches!(
+            kind,
+            ParamType::Address | ParamType::Bool | ParamType::Uint(_) | ParamType::FixedBytes(_)
+        )
+    };
+    if outputs.iter().all(|output| elementary(&output.kind)) && data.len() / 32 != outputs.len() {
+        return Err(format!(
+            "expected {} words, got {}",
+            outputs.len(),
+            data.len() / 32
+        ));
+    }
+
+    for (output, word) in outputs.iter().zip(data.chunks(32)) {
+        let padding = match output.kind {
+            ParamType::Address => 12,
+            ParamType::Bool => 31,
+            ParamType::Uint(bits) => 32 - bits / 8,
+            _ => 0,
+        };
+        let valid_bool = !matches!(output.kind, ParamType::Bool) || word[31] <= 1;
+        if word[..padding].iter().any(|byte| *byte != 0) || !valid_bool {
+            return Err(format!(
+                "0x{} is not a valid {}",
+                hex::encode(word),
+                output.kind
+            ));
+        }
+    }
+
+    Ok(())
+}
 
-use crate::{HyperlaneTronError, Signer, TronAddress, TronProvider};
+fn function_context(function: &Function) -> String {
+    format!(
+        "call to {} (selector 0x{})",
+        function.signature(),
+        hex::encode(function.short_signature())
+    )
+}
 
 pub(crate) async fn estimate_energy<T: EthCall>(
     provider: &TronProvider,
@@ -24,11 +193,86 @@ pub(crate) async fn estimate_energy<T: EthCall>(
         parameter: &call_args.encode(),
     };
 
-    provider
-        .rpc_client
-        .estimate_energy(&method_call)
-        .await
-        .map_err(Into::<HyperlaneTronError>::into)
+    let energy = estimate_call_energy(provider, &method_call).await?;
+    Ok(apply_min_energy_limit(provider, energy))
+}
+
+/// Energy used by a contract call, executed by the node without being broadcast.
+/// Fails with the decoded revert reason if the call reverts, rather than returning
+/// the energy used until the revert.
+async fn estimate_call_energy(
+    provider: &TronProvider,
+    method_call: &MethodCall<'_>,
+) -> Result<u64, HyperlaneTronError> {
+    let resp = provider
+        .retry_policy()
+        .retry(move || {
+            provider
+                .rpc_client
+                .call(|client| client.trigger_constant_contract(method_call))
+        })
+        .await?;
+
+    match resp.revert_reason() {
+        Some(reason) => Err(HyperlaneTronError::Reverted {
+            context: function_name(method_call.selector).to_owned(),
+            reason,
+        }),
+        None => Ok(resp.energy_used),
+    }
+}
+
+/// Name of the function with the given signature, e.g. `process` for
+/// `process(bytes,bytes)`
+fn function_name(signature: &str) -> &str {
+    signature
+        .split_once('(')
+        .map_or(signature, |(name, _)| name)
+}
+
+/// Caps a fee limit to the largest one the chain accepts, above which transactions
+/// fail validation
+fn clamp_fee_limit(fee_limit: u64, max_fee_limit: Option<u64>) -> u64 {
+    match max_fee_limit {
+        Some(max_fee_limit) if fee_limit > max_fee_limit => {
+            warn!(
+                fee_limit,
+                max_fee_limit, "Fee limit exceeds the maximum of the chain, clamping it"
+            );
+            max_fee_limit
+        }
+        _ => fee_limit,
+    }
+}
+
+/// Caps a fee limit to `fraction` of the signer's `balance` (in sun), so that a single
+/// transaction can't drain most of the account
+fn clamp_fee_limit_to_balance(fee_limit: u64, balance: u64, fraction: f64) -> u64 {
+    let max_fee_limit = (balance as f64 * fraction) as u64;
+    if fee_limit > max_fee_limit {
+        warn!(
+            fee_limit,
+            max_fee_limit,
+            balance,
+            "Fee limit exceeds the allowed fraction of the signer's balance, clamping it"
+        );
+        return max_fee_limit;
+    }
+    fee_limit
+}
+
+/// Raises an energy limit to the configured minimum, if any
+fn apply_min_energy_limit(provider: &TronProvider, energy_limit: u64) -> u64 {
+    match provider.min_energy_limit() {
+        Some(min_energy_limit) if energy_limit < min_energy_limit => {
+            debug!(
+                energy_limit,
+                min_energy_limit, "Raising energy limit to the configured minimum"
+            );
+            min_energy_limit
+        }
+        _ => energy_limit,
+    }
 }
 
 #[instrument(level = "trace", err, ret, skip(provider))]
@@ -39,11 +283,10 @@ pub(crate) async fn get_finalized_block_number(
     let number = match reorg_period {
         ReorgPeriod::None | ReorgPeriod::Blocks(_) => {
             let block = provider
-                .rpc_client
-                .get_latest_block()
+                .retry_policy()
+                .retry(move || provider.rpc_client.call(|client| client.get_latest_block()))
                 .await
-                .map(|blocks| blocks.block_number())
-                .map_err(Into::<HyperlaneTronError>::into)?;
+                .map(|blocks| blocks.block_number())?;
 
             if let ReorgPeriod::Blocks(lag) = reorg_period {
                 block.saturating_sub(lag.get() as u64)
@@ -51,14 +294,21 @@ pub(crate) async fn get_finalized_block_number(
                 block
             }
         }
-        ReorgPeriod::Tag(_) => provider
-            .rpc_client
-            .get_finalized_block_number()
-            .await
-            .map_err(Into::<HyperlaneTronError>::into)?,
+        ReorgPeriod::Tag(_) => {
+            provider
+                .retry_policy()
+                .retry(move || {
+                    provider
+                        .rpc_client
+                        .call(|client| client.get_finalized_block_number())
+                })
+                .await?
+        }
     };
 
-    Ok(number.try_into().unwrap())
+    let number =
+        u32::try_from(number).map_err(|_| HyperlaneTronError::BlockNumberOverflow(number))?;
+    Ok(number)
 }
 
 pub(crate) async fn call_with_reorg_period<M, T>(
@@ -73,8 +323,164 @@ where
         let block = get_finalized_block_number(provider, reorg_period).await? as u64;
         Ok(call.block(block))
     } else {
-        Ok(call)
+        pin_to_finalized(provider, call).await
+    }
+}
+
+/// Pins a call not pinned to a block yet to the latest finalized block, if the
+/// provider prefers finalized reads
+pub(crate) async fn pin_to_finalized<M, T>(
+    provider: &TronProvider,
+    call: ContractCall<M, T>,
+) -> ChainResult<ContractCall<M, T>>
+where
+    T: Detokenize,
+{
+    if !provider.prefer_finalized_reads() || call.block.is_some() {
+        return Ok(call);
+    }
+
+    let block = provider
+        .retry_policy()
+        .retry(move || {
+            provider
+                .rpc_client
+                .call(|client| client.get_finalized_block_number())
+        })
+        .await?;
+    Ok(call.block(block))
+}
+
+/// Fetches logs over `range`, splitting it into chunks no wider than the provider's
+/// log range limit, at most `max_concurrent_requests` of which are queried at once.
+/// If the node rejects a chunk as too wide, the limit it reports (or half the
+/// rejected range if it doesn't report one) is cached on the provider and the chunk
+/// is split again. Logs are returned in block order.
+pub(crate) async fn fetch_logs_in_chunks<T, F, Fut>(
+    provider: &TronProvider,
+    range: RangeInclusive<u32>,
+    mut fetch: F,
+) -> ChainResult<Vec<T>>
+where
+    F: FnMut(RangeInclusive<u32>) -> Fut,
+    Fut: Future<Output = ChainResult<Vec<T>>>,
+{
+    let semaphore = Semaphore::new(provider.max_concurrent_requests() as usize);
+    let mut pending = split_range(range, provider.max_log_range());
+    let mut chunks = BTreeMap::new();
+
+    while !pending.is_empty() {
+        let queries = pending.drain(..).map(|chunk| {
+            let query = fetch(chunk.clone());
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore.acquire().await;
+                (chunk, query.await)
+            }
+        });
+
+        for (chunk, result) in join_all(queries).await {
+            let err = match result {
+                Ok(logs) => {
+                    chunks.insert(*chunk.start(), logs);
+                    continue;
+                }
+                Err(err) => err,
+            };
+
+            let message = err.to_string();
+            let span = chunk.end() - chunk.start() + 1;
+            if !is_log_range_error(&message) || span == 1 {
+                return Err(err);
+            }
+
+            let max_range = parse_log_range_limit(&message)
+                .filter(|max_range| *max_range > 0 && *max_range < span)
+                .unwrap_or(span / 2);
+            warn!(
+                max_range,
+                rejected_range = ?chunk,
+                "Node rejected log query range, narrowing it"
+            );
+            provider.set_max_log_range(max_range);
+            pending.extend(split_range(chunk, Some(max_range)));
+        }
+    }
+
+    Ok(chunks.into_values().flatten().collect())
+}
+
+/// Splits `range` into consecutive chunks spanning at most `max_range` blocks
+fn split_range(range: RangeInclusive<u32>, max_range: Option<u32>) -> Vec<RangeInclusive<u32>> {
+    let (mut from, end) = range.into_inner();
+    let mut chunks = vec![];
+
+    while from <= end {
+        let to = match max_range {
+            Some(max_range) => end.min(from.saturating_add(max_range - 1)),
+            None => end,
+        };
+        chunks.push(from..=to);
+
+        if to == u32::MAX {
+            break;
+        }
+        from = to + 1;
+    }
+
+    chunks
+}
+
+/// Whether an error is a node rejecting a log query because its block range is too wide
+fn is_log_range_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("block range") || message.contains("range too large")
+}
+
+/// Extracts the maximum allowed block range from a range limit error,
+/// e.g. `exceed max block range: 5000`
+fn parse_log_range_limit(message: &str) -> Option<u32> {
+    message
+        .rsplit(|c: char| !c.is_ascii_digit())
+        .find(|s| !s.is_empty())
+        .and_then(|s| s.parse().ok())
+}
+
+/// Whether `metadata` passes the verification of `message` by the ISM at `ism`,
+/// checked with a constant call against the state at `block`, or the latest one
+pub(crate) async fn verify_metadata(
+    provider: &TronProvider,
+    ism: EthersH160,
+    message: &HyperlaneMessage,
+    metadata: &[u8],
+    block: Option<u64>,
+) -> ChainResult<bool> {
+    let ism = InterchainSecurityModuleContract::new(ism, provider.eth_client.clone());
+    let mut call = ism.verify(
+        metadata.to_vec().into(),
+        RawHyperlaneMessage::from(message).to_vec().into(),
+    );
+    if let Some(block) = block {
+        call = call.block(block);
     }
+
+    match call.call().await {
+        Ok(verified) => Ok(verified),
+        // ISMs revert rather than return false on invalid metadata
+        Err(ContractError::Revert(_)) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Transaction sent to the chain, along with what was broadcast
+#[derive(Debug, Clone)]
+pub(crate) struct SentTransaction {
+    pub outcome: TxOutcome,
+    /// Result of the execution, if awaited and confirmed
+    pub result: Option<TronTxResult>,
+    /// Signed transaction exactly as broadcast, serialized as the JSON body sent to
+    /// the node
+    pub signed_tx: Vec<u8>,
 }
 
 pub(crate) async fn send_transaction<T: EthCall>(
@@ -84,6 +490,34 @@ pub(crate) async fn send_transaction<T: EthCall>(
     signer: &Signer,
     energy_limit: Option<u64>,
 ) -> Result<TxOutcome, HyperlaneTronError> {
+    send_signed_transaction(
+        provider,
+        contract,
+        call_args,
+        signer,
+        energy_limit,
+        None,
+        None,
+    )
+    .await
+    .map(|sent| sent.outcome)
+}
+
+/// Sends a transaction, returning the serialized signed transaction for audit logs.
+/// It holds the signature, never the private key.
+///
+/// The fee limit is the cost of the energy limit at the current energy price, unless
+/// overridden with `fee_limit` (in sun). The `submission_ticket` is released as soon as
+/// the node accepts the transaction, without awaiting its confirmation.
+pub(crate) async fn send_signed_transaction<T: EthCall>(
+    provider: &TronProvider,
+    contract: &TronAddress,
+    call_args: T,
+    signer: &Signer,
+    energy_limit: Option<u64>,
+    fee_limit: Option<u64>,
+    submission_ticket: Option<SubmissionTicket>,
+) -> Result<SentTransaction, HyperlaneTronError> {
     let method_call = MethodCall {
         caller: &signer.0.address(),
         contract: contract.as_ref(),
@@ -91,64 +525,1460 @@ pub(crate) async fn send_transaction<T: EthCall>(
         parameter: &call_args.encode(),
     };
 
-    let fee_limit = match energy_limit {
-        Some(energy_limit) => {
-            let energy_price = provider.rpc_client.get_energy_fee().await?;
-            Some(energy_limit * energy_price)
+    let retry_policy = provider.retry_policy();
+
+    tx_lifecycle_event(TxLifecycleStage::Estimating, None);
+    // Fetched once for both the fee limit and the outcome, before broadcasting so
+    // that a failure can't hide a sent transaction
+    let energy_price = provider.energy_price().await?;
+    let gas_price = sun_to_fixed_point(energy_price)?;
+    let fee_limit = match (fee_limit, energy_limit) {
+        (None, Some(energy_limit)) => {
+            // Saturating, as a relayer gas limit can be up to `u64::MAX` energy, and
+            // the fee limit is clamped to the chain maximum below anyway
+            Some(apply_min_energy_limit(provider, energy_limit).saturating_mul(energy_price))
+        }
+        (fee_limit, _) => fee_limit,
+    };
+    // The fee limit, and the highest one it may be bumped to
+    let (fee_limit, max_fee_limit) = match fee_limit {
+        Some(fee_limit) => {
+            let max_fee_limit = retry_policy
+                .retry(move || {
+                    provider
+                        .rpc_client
+                        .call(|client| client.get_max_fee_limit_cached())
+                })
+                .await?;
+            let fee_limit = clamp_fee_limit(fee_limit, max_fee_limit);
+            match provider.max_fee_fraction_of_balance() {
+                Some(fraction) => {
+                    let address = &signer.0.address();
+                    let balance = retry_policy
+                        .retry(move || {
+                            provider
+                                .rpc_client
+                                .call(|client| client.get_account_balance(address))
+                        })
+                        .await?;
+                    let max_for_balance = (balance as f64 * fraction) as u64;
+                    (
+                        Some(clamp_fee_limit_to_balance(fee_limit, balance, fraction)),
+                        Some(max_fee_limit.map_or(max_for_balance, |max| max.min(max_for_balance))),
+                    )
+                }
+                None => (Some(fee_limit), max_fee_limit),
+            }
+        }
+        None => (None, None),
+    };
+
+    let method_call = &method_call;
+    let required_energy = match provider.fee_payer() {
+        Some(fee_payer) => {
+            ensure_energy_delegated(provider, fee_payer, signer).await?;
+
+            let required = match energy_limit {
+                Some(energy_limit) => apply_min_energy_limit(provider, energy_limit),
+                None => estimate_call_energy(provider, method_call).await?,
+            };
+            Some(required)
         }
         None => None,
     };
+    // Without a fee payer and with burning allowed, missing resources are only ever
+    // paid for in TRX, so there is nothing to check
+    if required_energy.is_some() || !provider.allow_energy_burn() {
+        let address = &signer.0.address();
+        let resources = retry_policy
+            .retry(move || {
+                provider
+                    .rpc_client
+                    .call(|client| client.get_account_resources(address))
+            })
+            .await?;
+        if let Some(required) = required_energy {
+            ensure_energy_available(provider, signer, &resources, required)?;
+        }
+        let tx_size = TRANSACTION_ENVELOPE_SIZE + method_call.parameter.len();
+        ensure_bandwidth_available(provider, signer, &resources, tx_size as u64)?;
+    }
+
+    let sent = broadcast_call(provider, method_call, signer, fee_limit).await?;
+    drop(submission_ticket);
+
+    let (sent, result) = if provider.wait_for_confirmation() {
+        let (sent, result) = await_with_fee_limit_bumps(
+            provider,
+            method_call,
+            signer,
+            sent,
+            fee_limit,
+            max_fee_limit,
+        )
+        .await;
+        let stage = if result == Some(TronTxResult::Success) {
+            TxLifecycleStage::Confirmed
+        } else {
+            TxLifecycleStage::Failed
+        };
+        tx_lifecycle_event(stage, Some(&sent.txid));
+        (sent, result)
+    } else {
+        (sent, None)
+    };
+    let executed = result == Some(TronTxResult::Success);
+    let transaction_id = H256::from(sent.txid.0).into();
+    // Failed executions consume energy too
+    let gas_used = match result {
+        Some(_) => provider.gas_used(transaction_id).await,
+        None => U256::zero(),
+    };
+
+    let outcome = TxOutcome {
+        transaction_id,
+        executed,
+        gas_used,
+        gas_price,
+    };
+
+    Ok(SentTransaction {
+        outcome,
+        result,
+        signed_tx: sent.signed_tx,
+    })
+}
+
+/// Transaction accepted by the node
+struct BroadcastTx {
+    txid: TransactionId,
+    /// Serialized signed transaction
+    signed_tx: Vec<u8>,
+    /// Time (ms) past which the transaction can't be included anymore
+    expiration: u64,
+}
+
+/// Builds, signs and broadcasts a transaction making the call
+async fn broadcast_call(
+    provider: &TronProvider,
+    method_call: &MethodCall<'_>,
+    signer: &Signer,
+    fee_limit: Option<u64>,
+) -> Result<BroadcastTx, HyperlaneTronError> {
+    let retry_policy = provider.retry_policy();
+
+    let mut rebuilt = false;
+    let sent = loop {
+        let mut tx = retry_policy
+            .retry(move || {
+                provider.rpc_client.call(move |client| {
+                    client.trigger_contract_as(method_call, fee_limit, provider.permission_id())
+                })
+            })
+            .await?;
+
+        if let Some(tx_expiration) = provider.tx_expiration() {
+            set_expiration(&mut tx, tx_expiration)?;
+        }
+        signer.0.sign_transaction(&mut tx)?;
+        let signed_tx = serialize_signed_transaction(&tx)?;
+        trace!(txid = ?tx.tx_id, signed_tx = %String::from_utf8_lossy(&signed_tx), "Broadcasting transaction");
+        tx_lifecycle_event(TxLifecycleStage::Broadcasting, Some(&tx.tx_id));
+
+        let broadcast = provider.broadcast(&tx).await;
+
+        // Retries may outlast the validity of the transaction, in which case it is
+        // built again with a fresh expiration
+        match broadcast {
+            Err(err) if is_expiration_error(&err) && !rebuilt => {
+                warn!(txid = ?tx.tx_id, "Transaction expired before being broadcast, rebuilding it");
+                rebuilt = true;
+            }
+            Err(err) => {
+                tx_lifecycle_event(TxLifecycleStage::Failed, Some(&tx.tx_id));
+                return Err(err);
+            }
+            Ok(txid) => {
+                break BroadcastTx {
+                    txid,
+                    signed_tx,
+                    expiration: tx.raw_data.expiration,
+                };
+            }
+        }
+    };
+    tx_lifecycle_event(TxLifecycleStage::Broadcast, Some(&sent.txid));
+    // Before awaiting the confirmation, which may be cancelled
+    provider.notify_broadcast(H256::from(sent.txid.0).into());
+
+    Ok(sent)
+}
+
+/// Awaits the result of a broadcast transaction. With fee limit bumping configured, a
+/// transaction that ran out of energy, or that expired without being included, is
+/// reissued with a higher fee limit, up to `max_fee_limit`. Tron transactions can't be
+/// replaced, so one is only reissued once it can't be included anymore.
+async fn await_with_fee_limit_bumps(
+    provider: &TronProvider,
+    method_call: &MethodCall<'_>,
+    signer: &Signer,
+    mut sent: BroadcastTx,
+    mut fee_limit: Option<u64>,
+    max_fee_limit: Option<u64>,
+) -> (BroadcastTx, Option<TronTxResult>) {
+    let mut bumps = 0;
+    loop {
+        let result = provider
+            .rpc_client
+            .await_tx_result(sent.txid, Some(sent.expiration))
+            .await;
+        let reissuable = matches!(
+            result,
+            Ok(TronTxResult::OutOfEnergy) | Err(HyperlaneTronError::TransactionDropped(_))
+        );
+        // Without a fee limit, the node estimates one, which can't be bumped
+        let bumped = match (provider.fee_limit_bump(), fee_limit) {
+            (Some(bump), Some(fee_limit))
+                if reissuable
+                    && bumps < bump.max_bumps
+                    && max_fee_limit.map_or(true, |max| fee_limit < max) =>
+            {
+                bump_fee_limit(fee_limit, bump.multiplier, max_fee_limit)
+            }
+            _ => return (sent, result.ok()),
+        };
+
+        bumps += 1;
+        warn!(
+            txid = ?sent.txid,
+            ?result,
+            fee_limit = bumped,
+            bumps,
+            "Transaction can't be included anymore, reissuing it with a higher fee limit"
+        );
+        match broadcast_call(provider, method_call, signer, Some(bumped)).await {
+            Ok(reissued) => {
+                sent = reissued;
+                fee_limit = Some(bumped);
+            }
+            Err(err) => {
+                warn!(?err, "Failed to reissue transaction");
+                return (sent, result.ok());
+            }
+        }
+    }
+}
 
-    let mut tx = provider
-        .rpc_client
-        .trigger_contract(&method_call, 0, fee_limit)
+/// Fee limit raised by `multiplier`, capped to `max_fee_limit`
+fn bump_fee_limit(fee_limit: u64, multiplier: f64, max_fee_limit: Option<u64>) -> u64 {
+    let bumped = (fee_limit as f64 * multiplier) as u64;
+    max_fee_limit.map_or(bumped, |max| bumped.min(max))
+}
+
+/// Checks that the fee payer delegates energy to the signer, which otherwise pays for
+/// its transactions itself
+async fn ensure_energy_delegated(
+    provider: &TronProvider,
+    fee_payer: H256,
+    signer: &Signer,
+) -> Result<(), HyperlaneTronError> {
+    let from = TronAddress::try_from(fee_payer)?;
+    let (from, to) = (&from, &signer.0.address());
+    let staked = provider
+        .retry_policy()
+        .retry(move || {
+            provider
+                .rpc_client
+                .call(move |client| client.get_delegated_energy_stake(from.as_ref(), to))
+        })
         .await?;
 
-    signer.0.sign_transaction(&mut tx)?;
+    if staked == 0 {
+        return Err(HyperlaneTronError::FeePayerDelegationMissing {
+            fee_payer,
+            signer: signer.address(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that the energy available to the signer, staked or delegated to it, covers
+/// the energy required by a transaction. The signer burns TRX for the rest, which is
+/// an error if burning is disabled.
+fn ensure_energy_available(
+    provider: &TronProvider,
+    signer: &Signer,
+    resources: &AccountResources,
+    required: u64,
+) -> Result<(), HyperlaneTronError> {
+    let available = resources.energy_limit.saturating_sub(resources.energy_used);
+    if available >= required {
+        return Ok(());
+    }
+    if !provider.allow_energy_burn() {
+        return Err(HyperlaneTronError::InsufficientDelegatedEnergy {
+            signer: signer.address(),
+            required,
+            available,
+        });
+    }
+    warn!(
+        signer = signer.address(),
+        required,
+        available,
+        "Delegated energy doesn't cover the transaction, burning TRX for the rest"
+    );
+    Ok(())
+}
 
-    let txid = provider.rpc_client.broadcast_transaction(&tx).await?;
+/// Checks that the bandwidth of the signer covers the `required` bytes of a
+/// transaction. Bandwidth is taken either from the staked or from the free allowance,
+/// never split between them, and the whole transaction burns TRX if neither covers it,
+/// which is an error if burning is disabled.
+fn ensure_bandwidth_available(
+    provider: &TronProvider,
+    signer: &Signer,
+    resources: &AccountResources,
+    required: u64,
+) -> Result<(), HyperlaneTronError> {
+    let staked = resources.net_limit.saturating_sub(resources.net_used);
+    let free = resources
+        .free_net_limit
+        .saturating_sub(resources.free_net_used);
+    let available = staked.max(free);
+    if available >= required {
+        return Ok(());
+    }
+    if !provider.allow_energy_burn() {
+        return Err(HyperlaneTronError::InsufficientBandwidth {
+            signer: signer.address(),
+            required,
+            available,
+        });
+    }
+    warn!(
+        signer = signer.address(),
+        required, available, "Bandwidth doesn't cover the transaction, burning TRX for it"
+    );
+    Ok(())
+}
 
-    let confirmed = provider.rpc_client.await_confirmation(txid).await.is_ok();
+fn serialize_signed_transaction(tx: &Transaction) -> Result<Vec<u8>, HyperlaneTronError> {
+    let json = transaction_json(tx)?;
+    serde_json::to_vec(&json)
+        .map_err(|err| HyperlaneTronError::MalformedTransaction(err.to_string()))
+}
 
-    Ok(TxOutcome {
-        transaction_id: H256::from(txid.0).into(),
-        executed: confirmed,
-        // TODO: calculate gas
-        gas_used: U256::zero(),
-        gas_price: U256::zero().try_into().unwrap(),
-    })
+/// Whether a transaction failed on chain by running out of energy, i.e. because its
+/// fee limit was too low
+pub(crate) async fn ran_out_of_energy(
+    provider: &TronProvider,
+    transaction_id: H512,
+) -> ChainResult<bool> {
+    let txid = TransactionId(H256::from_slice(&transaction_id.as_bytes()[32..]).0);
+    let info = provider
+        .retry_policy()
+        .retry(move || {
+            provider
+                .rpc_client
+                .call(move |client| client.get_tx_info_by_id(txid))
+        })
+        .await?;
+
+    Ok(info
+        .and_then(|info| info.ret.into_iter().next())
+        .is_some_and(|ret| {
+            TronTxResult::from(ret.contract_ret.as_str()) == TronTxResult::OutOfEnergy
+        }))
 }
 
-pub(crate) async fn fetch_raw_logs_and_meta<T: EthEvent>(
+/// Logs of `T` events emitted by `contract_address` in a transaction, retried while
+/// its receipt is pending. A transaction unknown to the node may not have reached it
+/// yet, so is only retried a few times, and a wrong hash doesn't retry forever.
+pub(crate) async fn fetch_tx_logs_with_retries<T: EthEvent>(
     provider: &TronProvider,
     contract_address: EthersH160,
     tx_hash: H512,
+    retry_interval: Duration,
 ) -> ChainResult<Vec<(T, LogMeta)>> {
-    let receipt = provider
-        .eth_client
-        .get_transaction_receipt(tx_hash)
-        .await?
-        .ok_or(HyperlaneTronError::CoreError(
-            heliosphere_core::Error::InvalidTransactionId,
-        ))?;
-
-    let logs: Vec<(T, LogMeta)> = receipt
+    let mut not_found = 0;
+    loop {
+        let err = match fetch_raw_logs_and_meta(provider, contract_address, tx_hash).await {
+            Ok(logs) => return Ok(logs),
+            Err(err @ HyperlaneTronError::TransactionNotFound(_)) => {
+                not_found += 1;
+                if not_found >= TX_NOT_FOUND_ATTEMPTS {
+                    return Err(err.into());
+                }
+                err
+            }
+            Err(err) => err,
+        };
+        warn!(?tx_hash, error = %err, "Retrying fetching the logs of a transaction");
+        tokio::time::sleep(retry_interval).await;
+    }
+}
+
+async fn fetch_raw_logs_and_meta<T: EthEvent>(
+    provider: &TronProvider,
+    contract_address: EthersH160,
+    tx_hash: H512,
+) -> Result<Vec<(T, LogMeta)>, HyperlaneTronError> {
+    let Some(receipt) = provider.eth_client.get_transaction_receipt(tx_hash).await? else {
+        // No receipt either for a pending transaction or an unknown one
+        return match provider.eth_client.get_transaction(tx_hash).await? {
+            Some(_) => Err(HyperlaneTronError::ReceiptPending(tx_hash)),
+            None => Err(HyperlaneTronError::TransactionNotFound(tx_hash)),
+        };
+    };
+
+    let logs = receipt
         .logs
         .into_iter()
-        .filter_map(|log| {
-            // Filter out logs that aren't emitted by this contract
-            if log.address != contract_address {
-                return None;
-            }
+        // Filter out logs that aren't emitted by this contract or aren't `T` events
+        .filter(|log| {
+            log.address == contract_address && log.topics.first() == Some(&T::signature())
+        })
+        .map(|log| {
             let raw_log = RawLog {
                 topics: log.topics.clone(),
                 data: log.data.to_vec(),
             };
             let log_meta: EthersLogMeta = (&log).into();
-            let event_filter = T::decode_log(&raw_log).ok();
-            event_filter.map(|log| (log, log_meta.into()))
+            let event = T::decode_log(&raw_log).map_err(|err| {
+                HyperlaneTronError::abi(
+                    format!("event {}", T::abi_signature()),
+                    AbiError::DecodingError(err),
+                )
+            })?;
+            Ok((event, log_meta.into()))
         })
-        .collect();
+        .collect::<Result<Vec<(T, LogMeta)>, HyperlaneTronError>>()?;
 
     Ok(logs)
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use hyperlane_core::{ChainCommunicationError, FixedPointNumber};
+    use tracing_test::traced_test;
+    use url::Url;
+
+    use crate::interfaces::i_mailbox::{IMailbox, ProcessIdFilter};
+    use crate::interfaces::i_multisig_ism::ModuleTypeCall;
+    use crate::test_utils::{self, MockTronNode};
+    use crate::{energy_to_gas, BroadcastListener, ConnectionConf, FeeLimitBumpConf};
+
+    use super::*;
+
+    fn provider(max_log_range: Option<u32>) -> TronProvider {
+        let conf = ConnectionConf {
+            max_log_range,
+            ..test_utils::conf(Url::parse("http://localhost:8090").unwrap())
+        };
+        test_utils::provider(conf)
+    }
+
+    #[test]
+    fn test_parse_log_range_limit() {
+        assert_eq!(
+            parse_log_range_limit("exceed max block range: 5000"),
+            Some(5000)
+        );
+        assert_eq!(parse_log_range_limit("query block range too large"), None);
+        assert!(is_log_range_error(
+            "(code: -32005, message: exceed max block range: 5000, data: None)"
+        ));
+        assert!(!is_log_range_error("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_in_chunks_discovers_limit() {
+        let provider = provider(None);
+        let queried = Mutex::new(vec![]);
+
+        let logs = fetch_logs_in_chunks(&provider, 0..=249, |range| {
+            queried.lock().unwrap().push(range.clone());
+            async move {
+                if range.end() - range.start() >= 100 {
+                    return Err(ChainCommunicationError::from_other_str(
+                        "exceed max block range: 100",
+                    ));
+                }
+                Ok(range.collect::<Vec<_>>())
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(logs, (0..=249).collect::<Vec<_>>());
+        assert_eq!(provider.max_log_range(), Some(100));
+        assert_eq!(
+            *queried.lock().unwrap(),
+            vec![0..=249, 0..=99, 100..=199, 200..=249]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_in_chunks_bounds_concurrency() {
+        let provider = test_utils::provider(ConnectionConf {
+            max_log_range: Some(10),
+            max_concurrent_requests: 3,
+            ..test_utils::conf(Url::parse("http://localhost:8090").unwrap())
+        });
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let logs = fetch_logs_in_chunks(&provider, 0..=99, |range| {
+            let (running, max_running) = (&running, &max_running);
+            async move {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now_running, Ordering::SeqCst);
+                // Later chunks complete first
+                tokio::time::sleep(Duration::from_millis(100 - *range.start() as u64)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(range.collect::<Vec<_>>())
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(logs, (0..=99).collect::<Vec<_>>());
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_in_chunks_other_error() {
+        let provider = provider(Some(50));
+
+        let result = fetch_logs_in_chunks(&provider, 0..=99, |_| async {
+            Err::<Vec<u32>, _>(ChainCommunicationError::from_other_str(
+                "connection refused",
+            ))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(provider.max_log_range(), Some(50));
+    }
+
+    /// Node that knows the transaction `0x11..11` after `pending_polls` polls of its
+    /// receipt, with a `ProcessId` log, and no other transaction. Returns the
+    /// number of receipt polls.
+    async fn node_with_pending_receipt(pending_polls: usize) -> (MockTronNode, Arc<AtomicUsize>) {
+        let known_tx = format!("{:#x}", H256::repeat_byte(0x11));
+        let receipt_polls = Arc::new(AtomicUsize::new(0));
+
+        let node = {
+            let receipt_polls = receipt_polls.clone();
+            MockTronNode::start(move |_, body| {
+                let known = body["params"][0] == known_tx;
+                let result = match body["method"].as_str().unwrap() {
+                    "eth_getTransactionReceipt" => {
+                        let polls = receipt_polls.fetch_add(1, Ordering::SeqCst);
+                        if !known || polls < pending_polls {
+                            serde_json::Value::Null
+                        } else {
+                            let mut log = test_utils::json_rpc_log(
+                                &[
+                                    format!("{:#x}", ProcessIdFilter::signature()),
+                                    format!("{:#x}", H256::repeat_byte(0x22)),
+                                ],
+                                "0x",
+                            );
+                            log["transactionHash"] = known_tx.clone().into();
+                            test_utils::json_rpc_receipt(&known_tx, vec![log])
+                        }
+                    }
+                    "eth_getTransactionByHash" if known => {
+                        // Pending until its receipt is available
+                        let mut tx = test_utils::json_rpc_transaction(&known_tx, "0x");
+                        tx["blockHash"] = serde_json::Value::Null;
+                        tx["blockNumber"] = serde_json::Value::Null;
+                        tx["transactionIndex"] = serde_json::Value::Null;
+                        tx
+                    }
+                    "eth_getTransactionByHash" => serde_json::Value::Null,
+                    method => panic!("unexpected call to {method}"),
+                };
+                test_utils::json_rpc_result(&body, result)
+            })
+            .await
+        };
+        (node, receipt_polls)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tx_logs_retries_pending_receipt() {
+        let (node, receipt_polls) = node_with_pending_receipt(3).await;
+
+        let logs = fetch_tx_logs_with_retries::<ProcessIdFilter>(
+            &node.provider(),
+            EthersH160::zero(),
+            H256::repeat_byte(0x11).into(),
+            Duration::from_millis(1),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0.message_id, [0x22; 32]);
+        assert_eq!(receipt_polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tx_logs_gives_up_on_unknown_tx() {
+        let (node, receipt_polls) = node_with_pending_receipt(0).await;
+
+        let err = fetch_tx_logs_with_retries::<ProcessIdFilter>(
+            &node.provider(),
+            EthersH160::zero(),
+            H256::repeat_byte(0x33).into(),
+            Duration::from_millis(1),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("not found"), "{err}");
+        assert_eq!(
+            receipt_polls.load(Ordering::SeqCst),
+            TX_NOT_FOUND_ATTEMPTS as usize
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_energy_raised_to_minimum() {
+        let node = MockTronNode::start(|path, _| {
+            assert_eq!(path, "/wallet/triggerconstantcontract");
+            test_utils::constant_call(1000)
+        })
+        .await;
+        let contract = TronAddress::from(EthersH160::zero());
+
+        let mut conf = node.conf();
+        conf.min_energy_limit = Some(5000);
+        let provider = test_utils::provider(conf);
+        let energy = estimate_energy(&provider, &contract, ModuleTypeCall)
+            .await
+            .unwrap();
+        assert_eq!(energy, 5000);
+
+        // Estimates above the minimum are kept
+        let mut conf = node.conf();
+        conf.min_energy_limit = Some(500);
+        let provider = test_utils::provider(conf);
+        let energy = estimate_energy(&provider, &contract, ModuleTypeCall)
+            .await
+            .unwrap();
+        assert_eq!(energy, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_energy_decodes_revert_reason() {
+        let node = MockTronNode::start(|path, _| {
+            assert_eq!(path, "/wallet/triggerconstantcontract");
+            let mut revert_data = ethers::utils::id("RateLimitExceeded(uint256,uint256)").to_vec();
+            revert_data.extend([0; 64]);
+            serde_json::json!({
+                "result": {
+                    "code": "CONTRACT_EXE_ERROR",
+                    "message": hex::encode("REVERT opcode executed")
+                },
+                "energy_used": 1000,
+                "constant_result": [hex::encode(revert_data)]
+            })
+        })
+        .await;
+        let contract = TronAddress::from(EthersH160::zero());
+
+        let err = estimate_energy(&node.provider(), &contract, ModuleTypeCall)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "moduleType reverted: RateLimitExceeded");
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_without_confirmation() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let queried = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let queried = queried.clone();
+            MockTronNode::start(move |path, body| {
+                queried.lock().unwrap().push(path.to_owned());
+                // Never confirmed
+                test_utils::send_call_response(path, &body, &txid)
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let outcome = tokio::time::timeout(
+            Duration::from_secs(5),
+            send_transaction(
+                &provider,
+                &TronAddress::try_from(H256::zero()).unwrap(),
+                ModuleTypeCall,
+                &signer,
+                Some(100_000),
+            ),
+        )
+        .await
+        .expect("fire-and-forget send awaited confirmation")
+        .unwrap();
+
+        assert_eq!(outcome.transaction_id, H256::from_low_u64_be(1).into());
+        assert!(!outcome.executed);
+        assert_eq!(outcome.gas_price, FixedPointNumber::from(210u64));
+        assert_eq!(
+            *queried.lock().unwrap(),
+            vec![
+                "/wallet/getchainparameters",
+                "/wallet/triggersmartcontract",
+                "/wallet/broadcasttransaction"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_under_permission() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let requests = requests.clone();
+            MockTronNode::start(move |path, body| {
+                requests
+                    .lock()
+                    .unwrap()
+                    .push((path.to_owned(), body.clone()));
+                match path {
+                    "/wallet/triggersmartcontract" => {
+                        let mut tx = test_utils::transaction(&txid);
+                        // Signed under the permission set in the request
+                        tx["raw_data"]["contract"] = serde_json::json!([{
+                            "parameter": {},
+                            "type": "TriggerSmartContract",
+                            "Permission_id": body["Permission_id"]
+                        }]);
+                        tx["raw_data_hex"] = "0a02c3d25a04081f2802".into();
+                        serde_json::json!({ "transaction": tx })
+                    }
+                    _ => test_utils::send_call_response(path, &body, &txid),
+                }
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            permission_id: Some(2),
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        send_transaction(
+            &provider,
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+        )
+        .await
+        .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let body = |path: &str| {
+            requests
+                .iter()
+                .find(|(p, _)| p == path)
+                .map(|(_, body)| body.clone())
+                .unwrap()
+        };
+        assert_eq!(body("/wallet/triggersmartcontract")["Permission_id"], 2);
+        assert_eq!(
+            body("/wallet/broadcasttransaction")["raw_data"]["contract"][0]["Permission_id"],
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_clamps_fee_limit_to_chain_maximum() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let fee_limits = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let fee_limits = fee_limits.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getchainparameters" => serde_json::json!({
+                    "chainParameter": [
+                        {"key": "getEnergyFee", "value": 420},
+                        {"key": "getMaxFeeLimit", "value": 15_000_000_000u64}
+                    ]
+                }),
+                "/wallet/triggersmartcontract" => {
+                    fee_limits.lock().unwrap().push(body["fee_limit"].clone());
+                    test_utils::triggered_transaction(&txid)
+                }
+                "/wallet/broadcasttransaction" => test_utils::broadcast_result(txid.as_str()),
+                _ => panic!("unexpected request to {path}"),
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let contract = TronAddress::from(EthersH160::zero());
+        let send = |energy_limit| {
+            send_transaction(
+                &provider,
+                &contract,
+                ModuleTypeCall,
+                &signer,
+                Some(energy_limit),
+            )
+        };
+
+        // 420 sun per energy for 50M energy is 21000 TRX, above the 15000 TRX maximum
+        send(50_000_000).await.unwrap();
+        send(100_000).await.unwrap();
+        // A cost beyond `u64::MAX` sun
+        send(u64::MAX).await.unwrap();
+
+        assert_eq!(
+            *fee_limits.lock().unwrap(),
+            vec![
+                serde_json::json!(15_000_000_000u64),
+                serde_json::json!(100_000 * 420),
+                serde_json::json!(15_000_000_000u64)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_clamps_fee_limit_to_fraction_of_balance() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let fee_limits = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let fee_limits = fee_limits.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getchainparameters" => test_utils::chain_parameters(420),
+                // 1000 TRX
+                "/wallet/getaccount" => serde_json::json!({"balance": 1_000_000_000u64}),
+                "/wallet/triggersmartcontract" => {
+                    fee_limits.lock().unwrap().push(body["fee_limit"].clone());
+                    test_utils::triggered_transaction(&txid)
+                }
+                "/wallet/broadcasttransaction" => test_utils::broadcast_result(txid.as_str()),
+                _ => panic!("unexpected request to {path}"),
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            max_fee_fraction_of_balance: Some(0.1),
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let contract = TronAddress::from(EthersH160::zero());
+        let send = |energy_limit| {
+            send_transaction(
+                &provider,
+                &contract,
+                ModuleTypeCall,
+                &signer,
+                Some(energy_limit),
+            )
+        };
+
+        // 420 sun per energy for 1M energy is 420 TRX, above 10% of the balance
+        send(1_000_000).await.unwrap();
+        send(100_000).await.unwrap();
+
+        assert_eq!(
+            *fee_limits.lock().unwrap(),
+            vec![
+                serde_json::json!(100_000_000u64),
+                serde_json::json!(100_000 * 420)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_rebuilds_expired_transaction() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let broadcasts = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let broadcasts = broadcasts.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/broadcasttransaction" => {
+                    let mut broadcasts = broadcasts.lock().unwrap();
+                    broadcasts.push(body["raw_data"]["expiration"].clone());
+                    if broadcasts.len() == 1 {
+                        serde_json::json!({
+                            "code": "TRANSACTION_EXPIRATION_ERROR",
+                            "message": hex::encode("Transaction expired"),
+                            "txid": body["txID"]
+                        })
+                    } else {
+                        test_utils::broadcast_result(body["txID"].clone())
+                    }
+                }
+                _ => test_utils::send_call_response(path, &body, &txid),
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            tx_expiration: Some(Duration::from_secs(3600)),
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let outcome = send_transaction(
+            &provider,
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+        )
+        .await
+        .unwrap();
+
+        // Built twice, both times with the configured expiration
+        let expiration = serde_json::json!(1722858393000u64 + 3_600_000);
+        assert_eq!(
+            *broadcasts.lock().unwrap(),
+            vec![expiration.clone(), expiration]
+        );
+        assert_ne!(outcome.transaction_id, H256::from_low_u64_be(1).into());
+    }
+
+    /// Node executing the transactions it is sent with the given results, in order,
+    /// the last one repeatedly. Returns the fee limits of the transactions.
+    async fn node_with_tx_results(
+        results: Vec<&'static str>,
+    ) -> (MockTronNode, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let txid = |n: usize| format!("{:#x}", H256::from_low_u64_be(n as u64));
+        let fee_limits = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let fee_limits = fee_limits.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+                "/wallet/triggersmartcontract" => {
+                    let mut fee_limits = fee_limits.lock().unwrap();
+                    fee_limits.push(body["fee_limit"].clone());
+                    serde_json::json!({
+                        "transaction": test_utils::transaction(&txid(fee_limits.len()))
+                    })
+                }
+                "/wallet/broadcasttransaction" => {
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                "/walletsolidity/gettransactionbyid" => {
+                    let n = (1..=results.len())
+                        .find(|n| body["value"] == txid(*n))
+                        .unwrap_or(results.len());
+                    let mut tx = test_utils::transaction(&txid(n));
+                    tx["ret"] = serde_json::json!([{"contractRet": results[n - 1]}]);
+                    tx
+                }
+                _ => serde_json::json!({}),
+            })
+            .await
+        };
+        (node, fee_limits)
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_bumps_fee_limit_when_out_of_energy() {
+        let (node, fee_limits) =
+            node_with_tx_results(vec!["OUT_OF_ENERGY", "OUT_OF_ENERGY", "SUCCESS"]).await;
+        let provider = test_utils::provider(ConnectionConf {
+            fee_limit_bump: Some(FeeLimitBumpConf {
+                multiplier: 2.0,
+                max_bumps: 3,
+            }),
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let outcome = send_transaction(
+            &provider,
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+        )
+        .await
+        .unwrap();
+
+        assert!(outcome.executed);
+        assert_eq!(outcome.transaction_id, H256::from_low_u64_be(3).into());
+        assert_eq!(
+            *fee_limits.lock().unwrap(),
+            vec![
+                serde_json::json!(100_000 * 210),
+                serde_json::json!(100_000 * 210 * 2),
+                serde_json::json!(100_000 * 210 * 4)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_doesnt_reissue_reverted_transaction() {
+        let (node, fee_limits) = node_with_tx_results(vec!["REVERT", "SUCCESS"]).await;
+        let provider = test_utils::provider(ConnectionConf {
+            fee_limit_bump: Some(FeeLimitBumpConf::default()),
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let outcome = send_transaction(
+            &provider,
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+        )
+        .await
+        .unwrap();
+
+        // A higher fee limit wouldn't help a call that reverts
+        assert!(!outcome.executed);
+        assert_eq!(outcome.transaction_id, H256::from_low_u64_be(1).into());
+        assert_eq!(fee_limits.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_bump_fee_limit() {
+        assert_eq!(bump_fee_limit(1_000, 1.5, None), 1_500);
+        assert_eq!(bump_fee_limit(1_000, 1.5, Some(1_200)), 1_200);
+    }
+
+    #[tokio::test]
+    async fn test_send_signed_transaction_returns_broadcast_transaction() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let broadcast = Arc::new(Mutex::new(None));
+
+        let node = {
+            let broadcast = broadcast.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/broadcasttransaction" => {
+                    *broadcast.lock().unwrap() = Some(body.clone());
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                _ => test_utils::send_call_response(path, &body, &txid),
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let sent = send_signed_transaction(
+            &provider,
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let tx: Transaction = serde_json::from_slice(&sent.signed_tx).unwrap();
+        assert_eq!(
+            serde_json::to_value(&tx).unwrap(),
+            broadcast.lock().unwrap().clone().unwrap()
+        );
+        assert_eq!(sent.outcome.transaction_id, H256::from(tx.tx_id.0).into());
+        assert_eq!(tx.signature.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_outcome_recovered_after_cancellation() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let confirmed = Arc::new(AtomicBool::new(false));
+
+        let node = {
+            let confirmed = confirmed.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/walletsolidity/gettransactionbyid" if confirmed.load(Ordering::SeqCst) => {
+                    let mut tx = test_utils::transaction(body["value"].as_str().unwrap());
+                    tx["ret"] = serde_json::json!([{"contractRet": "SUCCESS"}]);
+                    tx
+                }
+                // Included in a block that isn't solidified yet
+                "/wallet/gettransactionbyid" => {
+                    test_utils::transaction(body["value"].as_str().unwrap())
+                }
+                _ => test_utils::send_call_response(path, &body, &txid),
+            })
+            .await
+        };
+        let (broadcast_tx, broadcast_rx) = tokio::sync::oneshot::channel();
+        let broadcast_tx = Mutex::new(Some(broadcast_tx));
+        let provider = node
+            .provider()
+            .with_broadcast_listener(BroadcastListener::new(move |txid| {
+                if let Some(broadcast_tx) = broadcast_tx.lock().unwrap().take() {
+                    broadcast_tx.send(txid).unwrap();
+                }
+            }));
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let send = {
+            let provider = provider.clone();
+            tokio::spawn(async move {
+                send_transaction(
+                    &provider,
+                    &TronAddress::try_from(H256::zero()).unwrap(),
+                    ModuleTypeCall,
+                    &signer,
+                    Some(100_000),
+                )
+                .await
+            })
+        };
+        // Cancelled while awaiting the confirmation
+        let txid = broadcast_rx.await.unwrap();
+        send.abort();
+        assert!(send.await.unwrap_err().is_cancelled());
+
+        confirmed.store(true, Ordering::SeqCst);
+        let outcome = provider
+            .await_outcome_with_timeout(txid, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.transaction_id, txid);
+        assert!(outcome.executed);
+        assert_eq!(outcome.gas_price, FixedPointNumber::from(210u64));
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_with_fee_payer() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let delegating_payer = H256::from_low_u64_be(0xfee);
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let signer_hex = signer.0.address().as_hex();
+        let delegation_queries = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let delegation_queries = delegation_queries.clone();
+            let delegating_payer = TronAddress::try_from(delegating_payer).unwrap();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getdelegatedresourcev2" => {
+                    delegation_queries.lock().unwrap().push(body.clone());
+                    assert_eq!(body["toAddress"], signer_hex);
+                    if body["fromAddress"] == delegating_payer.as_ref().as_hex() {
+                        serde_json::json!({"delegatedResource": [{
+                            "from": body["fromAddress"],
+                            "to": body["toAddress"],
+                            "frozen_balance_for_energy": 1_000_000_000
+                        }]})
+                    } else {
+                        serde_json::json!({})
+                    }
+                }
+                _ => test_utils::send_call_response(path, &body, &txid),
+            })
+            .await
+        };
+        let send = |fee_payer| {
+            let provider = test_utils::provider(ConnectionConf {
+                wait_for_confirmation: false,
+                fee_payer: Some(fee_payer),
+                ..node.conf()
+            });
+            let signer = signer.clone();
+            async move {
+                send_transaction(
+                    &provider,
+                    &TronAddress::try_from(H256::zero()).unwrap(),
+                    ModuleTypeCall,
+                    &signer,
+                    Some(100_000),
+                )
+                .await
+            }
+        };
+
+        send(delegating_payer).await.unwrap();
+
+        let err = send(H256::from_low_u64_be(0xbad)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            HyperlaneTronError::FeePayerDelegationMissing { fee_payer, .. }
+                if fee_payer == H256::from_low_u64_be(0xbad)
+        ));
+        assert_eq!(delegation_queries.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_with_insufficient_delegated_energy() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let broadcasts = Arc::new(AtomicUsize::new(0));
+
+        let node = {
+            let broadcasts = broadcasts.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/getdelegatedresourcev2" => serde_json::json!({"delegatedResource": [{
+                    "from": body["fromAddress"],
+                    "to": body["toAddress"],
+                    "frozen_balance_for_energy": 1_000_000_000
+                }]}),
+                "/wallet/getaccountresource" => serde_json::json!({
+                    "freeNetLimit": 600,
+                    "EnergyLimit": 80_000,
+                    "EnergyUsed": 30_000
+                }),
+                "/wallet/broadcasttransaction" => {
+                    broadcasts.fetch_add(1, Ordering::SeqCst);
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                _ => test_utils::send_call_response(path, &body, &txid),
+            })
+            .await
+        };
+        let send = |allow_energy_burn, energy_limit| {
+            let provider = test_utils::provider(ConnectionConf {
+                wait_for_confirmation: false,
+                fee_payer: Some(H256::from_low_u64_be(0xfee)),
+                allow_energy_burn,
+                ..node.conf()
+            });
+            async move {
+                send_transaction(
+                    &provider,
+                    &TronAddress::try_from(H256::zero()).unwrap(),
+                    ModuleTypeCall,
+                    &Signer::from_bytes(&[1; 32]).unwrap(),
+                    Some(energy_limit),
+                )
+                .await
+            }
+        };
+
+        let err = send(false, 100_000).await.unwrap_err();
+        assert!(matches!(
+            err,
+            HyperlaneTronError::InsufficientDelegatedEnergy {
+                required: 100_000,
+                available: 50_000,
+                ..
+            }
+        ));
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 0);
+
+        // Covered by the delegated energy, or burning allowed
+        send(false, 50_000).await.unwrap();
+        send(true, 100_000).await.unwrap();
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_with_insufficient_bandwidth() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let broadcasts = Arc::new(AtomicUsize::new(0));
+
+        let node = {
+            let broadcasts = broadcasts.clone();
+            MockTronNode::start(move |path, body| match path {
+                // Neither allowance covers the transaction on its own
+                "/wallet/getaccountresource" => serde_json::json!({
+                    "freeNetLimit": 600,
+                    "freeNetUsed": 200,
+                    "NetLimit": 300
+                }),
+                "/wallet/broadcasttransaction" => {
+                    broadcasts.fetch_add(1, Ordering::SeqCst);
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                _ => test_utils::send_call_response(path, &body, &txid),
+            })
+            .await
+        };
+        let send = |allow_energy_burn| {
+            let provider = test_utils::provider(ConnectionConf {
+                wait_for_confirmation: false,
+                allow_energy_burn,
+                ..node.conf()
+            });
+            async move {
+                send_transaction(
+                    &provider,
+                    &TronAddress::try_from(H256::zero()).unwrap(),
+                    ModuleTypeCall,
+                    &Signer::from_bytes(&[1; 32]).unwrap(),
+                    Some(50_000),
+                )
+                .await
+            }
+        };
+
+        let err = send(false).await.unwrap_err();
+        assert!(matches!(
+            err,
+            HyperlaneTronError::InsufficientBandwidth {
+                required,
+                available: 400,
+                ..
+            } if required == (TRANSACTION_ENVELOPE_SIZE + 4) as u64
+        ));
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 0);
+
+        // Burning TRX for the bandwidth
+        send(true).await.unwrap();
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_reports_gas_used() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+
+        let node = MockTronNode::start(move |path, body| match path {
+            "/walletsolidity/gettransactionbyid" => {
+                let mut tx = test_utils::transaction(body["value"].as_str().unwrap());
+                tx["ret"] = serde_json::json!([{"contractRet": "SUCCESS"}]);
+                tx
+            }
+            "/wallet/gettransactioninfobyid" => serde_json::json!({
+                "id": body["value"],
+                "receipt": {"energy_usage_total": 64_285, "result": "SUCCESS"}
+            }),
+            _ => test_utils::send_call_response(path, &body, &txid),
+        })
+        .await;
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let outcome = send_transaction(
+            &node.provider(),
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+        )
+        .await
+        .unwrap();
+
+        assert!(outcome.executed);
+        assert_eq!(outcome.gas_used, energy_to_gas(64_285));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_send_transaction_lifecycle_events() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+
+        let node = MockTronNode::start(move |path, body| match path {
+            "/walletsolidity/gettransactionbyid" => {
+                let mut tx = test_utils::transaction(body["value"].as_str().unwrap());
+                tx["ret"] = serde_json::json!([{"contractRet": "SUCCESS"}]);
+                tx
+            }
+            _ => test_utils::send_call_response(path, &body, &txid),
+        })
+        .await;
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        let outcome = send_transaction(
+            &node.provider(),
+            &TronAddress::try_from(H256::zero()).unwrap(),
+            ModuleTypeCall,
+            &signer,
+            Some(100_000),
+        )
+        .await
+        .unwrap();
+        assert!(outcome.executed);
+
+        logs_assert(|lines: &[&str]| {
+            let stages: Vec<_> = lines
+                .iter()
+                .filter(|line| line.contains(TX_LIFECYCLE_TARGET))
+                .filter_map(|line| line.split("stage=\"").nth(1)?.split('"').next())
+                .collect();
+            match stages[..] {
+                ["estimating", "broadcasting", "broadcast", "confirmed"] => Ok(()),
+                _ => Err(format!("unexpected lifecycle stages {stages:?}")),
+            }
+        });
+    }
+
+    #[test]
+    fn test_check_return_shape() {
+        let outputs = |kinds: Vec<ParamType>| -> Vec<Param> {
+            kinds
+                .into_iter()
+                .map(|kind| Param {
+                    name: String::new(),
+                    kind,
+                    internal_type: None,
+                })
+                .collect()
+        };
+        let word = |value: u64| H256::from_low_u64_be(value).to_fixed_bytes();
+
+        let uint32 = outputs(vec![ParamType::Uint(32)]);
+        assert!(check_return_shape(&uint32, &word(7)).is_ok());
+        assert!(check_return_shape(&uint32, &word(u64::MAX)).is_err());
+        assert!(check_return_shape(&uint32, &[word(7), word(7)].concat()).is_err());
+        assert!(check_return_shape(&uint32, &[]).is_err());
+
+        let bool_address = outputs(vec![ParamType::Bool, ParamType::Address]);
+        assert!(check_return_shape(&bool_address, &[word(1), word(u64::MAX)].concat()).is_ok());
+        assert!(check_return_shape(&bool_address, &[word(2), word(0)].concat()).is_err());
+        assert!(check_return_shape(&bool_address, &[word(1), [0xff; 32]].concat()).is_err());
+
+        // Dynamic data past the head words
+        let bytes = outputs(vec![ParamType::Bytes]);
+        assert!(check_return_shape(&bytes, &[word(32), word(1), [0xff; 32]].concat()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_finalized_block_number_beyond_u32() {
+        let node = MockTronNode::start(|path, _| {
+            assert_eq!(path, "/wallet/getnowblock");
+            test_utils::block(u32::MAX as u64 + 1)
+        })
+        .await;
+
+        let err = get_finalized_block_number(&node.provider(), &ReorgPeriod::None)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("doesn't fit in a u32"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_call_decoding_error_names_the_call() {
+        // A single byte can't be decoded as the `uint32` nonce
+        let node = MockTronNode::start(|_, body| test_utils::json_rpc_result(&body, "0x01")).await;
+        let provider = node.provider();
+        let mailbox = IMailbox::new(EthersH160::zero(), provider.eth_client.clone());
+
+        let err = call_with_abi_context(mailbox.nonce()).await.unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("ABI error in call to nonce():(uint32) (selector 0xaffed0e0)"),
+            "{err}"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use std::sync::Arc;
-use tracing::instrument;
+use tracing::{instrument, warn};
 
 use hyperlane_core::{
     Announcement, ChainCommunicationError, ChainResult, ContractLocator, HyperlaneChain,
@@ -11,9 +11,18 @@ use hyperlane_core::{
 use crate::interfaces::i_validator_announce::{
     AnnounceCall, IValidatorAnnounce as ValidatorAnnounceContract,
 };
-use crate::{ConnectionConf, Signer, TronAddress, TronEthClient, TronProvider};
+use crate::{ConnectionConf, HyperlaneTronError, Signer, TronAddress, TronEthClient, TronProvider};
 
-use super::utils::send_transaction;
+use super::utils::{call_with_abi_context, estimate_energy, ran_out_of_energy, send_transaction};
+
+/// Number of times an announcement is sent, raising its energy limit each time it
+/// runs out of energy
+const MAX_ANNOUNCE_ATTEMPTS: u32 = 3;
+
+/// Energy limit of an announcement raised by half after running out of energy
+fn bump_energy_limit(energy_limit: u64) -> u64 {
+    energy_limit.saturating_add(energy_limit / 2)
+}
 
 /// A reference to a ValidatorAnnounce contract on some Tron chain
 #[derive(Debug)]
@@ -42,6 +51,104 @@ impl TronValidatorAnnounce {
             signer,
         })
     }
+
+    /// Validators that announced a storage location, as 32-byte words
+    #[instrument(err, skip(self))]
+    pub async fn announced_validators(&self) -> ChainResult<Vec<H256>> {
+        let validators = call_with_abi_context(self.contract.get_announced_validators()).await?;
+
+        Ok(validators
+            .into_iter()
+            .map(|v| H160::from(v).into())
+            .collect())
+    }
+
+    /// Whether the validator already announced the storage location
+    #[instrument(err, skip(self))]
+    pub async fn is_location_announced(
+        &self,
+        validator: H256,
+        location: &str,
+    ) -> ChainResult<bool> {
+        let locations = self.get_announced_storage_locations(&[validator]).await?;
+
+        Ok(locations
+            .into_iter()
+            .flatten()
+            .any(|announced| announced == location))
+    }
+
+    /// Announces a storage location with the given energy limit, estimated if unset.
+    ///
+    /// Announcing a location twice reverts, so an already announced location fails
+    /// with [`HyperlaneTronError::LocationAlreadyAnnounced`] without sending anything.
+    ///
+    /// Under congestion an announcement may run out of energy, in which case it is
+    /// sent again with a raised limit. This requires waiting for the confirmation of
+    /// transactions, otherwise the first outcome is returned as is.
+    #[instrument(err, ret, skip(self))]
+    pub async fn announce_with_energy_limit(
+        &self,
+        announcement: SignedType<Announcement>,
+        energy_limit: Option<u64>,
+    ) -> ChainResult<TxOutcome> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or(ChainCommunicationError::SignerUnavailable)?;
+
+        let validator = announcement.value.validator.into();
+        let location = &announcement.value.storage_location;
+        if self.is_location_announced(validator, location).await? {
+            return Err(HyperlaneTronError::LocationAlreadyAnnounced {
+                validator,
+                location: location.clone(),
+            }
+            .into());
+        }
+
+        let serialized_signature: [u8; 65] = announcement.signature.into();
+        let call = AnnounceCall {
+            validator: announcement.value.validator.into(),
+            storage_location: announcement.value.storage_location,
+            signature: serialized_signature.into(),
+        };
+        let contract: TronAddress = self.contract.address().into();
+
+        let mut energy_limit = match energy_limit {
+            Some(energy_limit) => energy_limit,
+            None => estimate_energy(&self.provider, &contract, call.clone()).await?,
+        };
+        let mut attempt = 1;
+        loop {
+            let outcome = send_transaction(
+                &self.provider,
+                &contract,
+                call.clone(),
+                signer,
+                Some(energy_limit),
+            )
+            .await?;
+
+            if outcome.executed
+                || !self.provider.wait_for_confirmation()
+                || attempt >= MAX_ANNOUNCE_ATTEMPTS
+                || !ran_out_of_energy(&self.provider, outcome.transaction_id).await?
+            {
+                return Ok(outcome);
+            }
+
+            let bumped = bump_energy_limit(energy_limit);
+            warn!(
+                txid = ?outcome.transaction_id,
+                energy_limit,
+                bumped,
+                "Announcement ran out of energy, sending it again with a raised limit"
+            );
+            energy_limit = bumped;
+            attempt += 1;
+        }
+    }
 }
 
 impl HyperlaneContract for TronValidatorAnnounce {
@@ -67,37 +174,15 @@ impl ValidatorAnnounce for TronValidatorAnnounce {
         validators: &[H256],
     ) -> ChainResult<Vec<Vec<String>>> {
         let validators = validators.iter().map(|v| H160::from(*v).into()).collect();
-        let locations = self
-            .contract
-            .get_announced_storage_locations(validators)
-            .call()
-            .await?;
+        let locations =
+            call_with_abi_context(self.contract.get_announced_storage_locations(validators))
+                .await?;
 
         Ok(locations)
     }
 
-    #[instrument(err, ret, skip(self))]
     async fn announce(&self, announcement: SignedType<Announcement>) -> ChainResult<TxOutcome> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(ChainCommunicationError::SignerUnavailable)?;
-
-        let serialized_signature: [u8; 65] = announcement.signature.into();
-
-        send_transaction(
-            &self.provider,
-            &self.contract.address().into(),
-            AnnounceCall {
-                validator: announcement.value.validator.into(),
-                storage_location: announcement.value.storage_location,
-                signature: serialized_signature.into(),
-            },
-            signer,
-            None,
-        )
-        .await
-        .map_err(Into::into)
+        self.announce_with_energy_limit(announcement, None).await
     }
 
     #[instrument(ret, skip(self))]
@@ -109,3 +194,188 @@ impl ValidatorAnnounce for TronValidatorAnnounce {
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    use ethers::abi::Token;
+    use ethers::contract::EthCall;
+    use ethers::utils::hex;
+    use hyperlane_core::{KnownHyperlaneDomain, Signature};
+
+    use crate::interfaces::i_validator_announce::{
+        GetAnnouncedStorageLocationsCall, GetAnnouncedValidatorsCall,
+    };
+
+    use crate::test_utils::{self, MockTronNode};
+
+    use super::*;
+
+    fn announcement() -> SignedType<Announcement> {
+        SignedType {
+            value: Announcement {
+                validator: H160::from_low_u64_be(0xaa),
+                mailbox_address: H256::from_low_u64_be(0xbb),
+                mailbox_domain: 728126428,
+                storage_location: "s3://validator/tron".to_owned(),
+            },
+            signature: Signature {
+                r: U256::one(),
+                s: U256::one(),
+                v: 27,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_announced_validators() {
+        let node = MockTronNode::start(|_, body| {
+            assert_eq!(
+                body["params"][0]["data"],
+                format!("0x{}", hex::encode(GetAnnouncedValidatorsCall::selector()))
+            );
+            // `address[]` of two validators
+            let result = concat!(
+                "0x",
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "000000000000000000000000bf1e4b4a8e4e5ae7cd2d13d1b9a1b52bd3e6a1a1",
+                "0000000000000000000000005c3d5a1d1e1b1f1a1c1d1e1f1a1b1c1d1e1f1a1b"
+            );
+            test_utils::json_rpc_result(&body, result)
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let validator_announce = TronValidatorAnnounce::new(
+            node.conf(),
+            ContractLocator::new(&domain, H256::zero()),
+            None,
+        )
+        .unwrap();
+
+        let validators = validator_announce.announced_validators().await.unwrap();
+
+        assert_eq!(
+            validators,
+            vec![
+                H160::from_str("0xbf1e4b4a8e4e5ae7cd2d13d1b9a1b52bd3e6a1a1")
+                    .unwrap()
+                    .into(),
+                H160::from_str("0x5c3d5a1d1e1b1f1a1c1d1e1f1a1b1c1d1e1f1a1b")
+                    .unwrap()
+                    .into(),
+            ]
+        );
+    }
+
+    /// `string[][]` of the locations announced by a single validator
+    fn encode_locations(locations: &[&str]) -> String {
+        let locations = locations
+            .iter()
+            .map(|l| Token::String(l.to_string()))
+            .collect();
+        let encoded = ethers::abi::encode(&[Token::Array(vec![Token::Array(locations)])]);
+        format!("0x{}", hex::encode(encoded))
+    }
+
+    #[tokio::test]
+    async fn test_is_location_announced() {
+        let node = MockTronNode::start(|_, body| {
+            let data = body["params"][0]["data"].as_str().unwrap();
+            assert!(data.starts_with(&format!(
+                "0x{}",
+                hex::encode(GetAnnouncedStorageLocationsCall::selector())
+            )));
+            let result = encode_locations(&["s3://validator/tron", "file:///tmp/validator"]);
+            test_utils::json_rpc_result(&body, result)
+        })
+        .await;
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let va = TronValidatorAnnounce::new(
+            node.conf(),
+            ContractLocator::new(&domain, H256::zero()),
+            Some(Signer::from_bytes(&[1; 32]).unwrap()),
+        )
+        .unwrap();
+        let validator = H256::from(H160::from_low_u64_be(0xaa));
+
+        assert!(va
+            .is_location_announced(validator, "s3://validator/tron")
+            .await
+            .unwrap());
+        assert!(!va
+            .is_location_announced(validator, "s3://validator/tron-2")
+            .await
+            .unwrap());
+
+        // Announcing it again fails before sending anything
+        let err = va.announce(announcement()).await.unwrap_err();
+        assert!(err.to_string().contains("already announced"), "{err}");
+    }
+
+    #[test]
+    fn test_bump_energy_limit() {
+        assert_eq!(bump_energy_limit(1000), 1500);
+        assert_eq!(bump_energy_limit(u64::MAX), u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_announce_bumps_energy_limit_after_running_out_of_energy() {
+        let fee_limits = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let fee_limits = fee_limits.clone();
+            MockTronNode::start(move |path, body| {
+                let txid = |attempt: usize| format!("{:#x}", H256::from_low_u64_be(attempt as u64));
+                match path {
+                    "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+                    "/wallet/triggerconstantcontract" => test_utils::constant_call(1000),
+                    "/wallet/triggersmartcontract" => {
+                        let mut fee_limits = fee_limits.lock().unwrap();
+                        fee_limits.push(body["fee_limit"].clone());
+                        serde_json::json!({
+                            "transaction": test_utils::transaction(&txid(fee_limits.len()))
+                        })
+                    }
+                    "/wallet/broadcasttransaction" => {
+                        test_utils::broadcast_result(body["txID"].clone())
+                    }
+                    "/" => {
+                        let result = encode_locations(&[]);
+                        test_utils::json_rpc_result(&body, result)
+                    }
+                    "/walletsolidity/gettransactionbyid" => {
+                        // Only the first attempt runs out of energy
+                        let ret = if body["value"] == txid(1) {
+                            "OUT_OF_ENERGY"
+                        } else {
+                            "SUCCESS"
+                        };
+                        let mut tx = test_utils::transaction(body["value"].as_str().unwrap());
+                        tx["ret"] = serde_json::json!([{"contractRet": ret}]);
+                        tx
+                    }
+                    _ => serde_json::json!({}),
+                }
+            })
+            .await
+        };
+        let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+        let va = TronValidatorAnnounce::new(
+            node.conf(),
+            ContractLocator::new(&domain, H256::zero()),
+            Some(Signer::from_bytes(&[1; 32]).unwrap()),
+        )
+        .unwrap();
+
+        let outcome = va.announce(announcement()).await.unwrap();
+
+        assert!(outcome.executed);
+        assert_eq!(
+            *fee_limits.lock().unwrap(),
+            vec![serde_json::json!(1000 * 210), serde_json::json!(1500 * 210)]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use hyperlane_core::{ChainResult, FixedPointNumber, U256};
+
+/// Number of decimals of TRX, 1 TRX = 1_000_000 sun
+pub const TRX_DECIMALS: u32 = 6;
+
+/// Units of EVM gas per unit of Tron energy.
+///
+/// It is one, so the conversions below are identities, for two reasons. The TVM
+/// charges energy per instruction following the EVM gas schedule, so an execution
+/// consumes about as much energy on Tron as it does gas on an EVM chain. And gas
+/// prices are reported in sun per energy (see [`sun_to_fixed_point`]), so the
+/// relayer's `gas_used * gas_price` is only the cost in sun with a ratio of one.
+/// The conversions are kept so that gas and energy don't get mixed up silently in
+/// signatures, and so that a different ratio only needs changing here.
+pub const GAS_PER_ENERGY: u64 = 1;
+
+/// Converts Tron energy into the equivalent EVM gas, e.g. for `TxCostEstimate` and
+/// `TxOutcome`
+pub fn energy_to_gas(energy: u64) -> U256 {
+    U256::from(energy) * GAS_PER_ENERGY
+}
+
+/// Converts EVM gas, e.g. a gas limit requested by the relayer, into Tron energy.
+/// Saturates at `u64::MAX`.
+pub fn gas_to_energy(gas: U256) -> u64 {
+    let energy = gas / GAS_PER_ENERGY;
+    if energy > U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        energy.as_u64()
+    }
+}
+
+/// Converts a sun-denominated energy price into a `FixedPointNumber`.
+///
+/// The relayer multiplies `gas_used` by `gas_price` and compares the result with
+/// the gas payments made on the origin chain, which are accounted in the smallest
+/// unit of the native token. Sun is already the smallest unit of TRX (see
+/// [`TRX_DECIMALS`]), so the price is kept unscaled to keep that math in sun.
+pub fn sun_to_fixed_point(price_sun: u64) -> ChainResult<FixedPointNumber> {
+    FixedPointNumber::try_from(U256::from(price_sun))
+}
+
+/// Formats an amount of sun as TRX for display, e.g. `12340000` as `"12.34 TRX"`.
+/// Trailing zeros of the fractional part are omitted.
+pub fn format_sun(sun: u128) -> String {
+    let scale = 10u128.pow(TRX_DECIMALS);
+    let (whole, fraction) = (sun / scale, sun % scale);
+    if fraction == 0 {
+        return format!("{whole} TRX");
+    }
+    let fraction = format!("{fraction:0width$}", width = TRX_DECIMALS as usize);
+    format!("{whole}.{} TRX", fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_sun_to_fixed_point() {
+        assert_eq!(sun_to_fixed_point(0).unwrap(), FixedPointNumber::zero());
+        assert_eq!(
+            sun_to_fixed_point(420).unwrap(),
+            FixedPointNumber::from_str("420").unwrap()
+        );
+        assert_eq!(
+            sun_to_fixed_point(u64::MAX).unwrap(),
+            FixedPointNumber::from_str("18446744073709551615").unwrap()
+        );
+        // 420 sun per energy for 100k energy is 42 TRX
+        let cost = sun_to_fixed_point(420).unwrap() * 100_000u64;
+        assert_eq!(
+            cost / 10u64.pow(TRX_DECIMALS),
+            FixedPointNumber::from_str("42").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_format_sun() {
+        assert_eq!(format_sun(0), "0 TRX");
+        assert_eq!(format_sun(1), "0.000001 TRX");
+        assert_eq!(format_sun(12_340_000), "12.34 TRX");
+        assert_eq!(format_sun(42_000_000), "42 TRX");
+        // 100k energy at 420 sun per energy
+        assert_eq!(format_sun(420 * 100_000), "42 TRX");
+        assert_eq!(format_sun(210 * 64_285), "13.49985 TRX");
+    }
+
+    #[test]
+    fn test_energy_gas_conversions() {
+        assert_eq!(energy_to_gas(0), U256::zero());
+        // Typical energy used by the delivery of a message
+        assert_eq!(energy_to_gas(64_285), U256::from(64_285));
+        assert_eq!(energy_to_gas(u64::MAX), U256::from(u64::MAX));
+
+        assert_eq!(gas_to_energy(U256::zero()), 0);
+        assert_eq!(gas_to_energy(U256::from(150_000)), 150_000);
+        assert_eq!(gas_to_energy(U256::from(u64::MAX) + 1), u64::MAX);
+
+        for energy in [1, 64_285, 3_000_000] {
+            assert_eq!(gas_to_energy(energy_to_gas(energy)), energy);
+        }
+    }
+}
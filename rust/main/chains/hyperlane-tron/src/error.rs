@@ -1,4 +1,7 @@
-use hyperlane_core::ChainCommunicationError;
+use ethers::providers::{HttpClientError, ProviderError};
+use hyperlane_core::{ChainCommunicationError, H256, H512};
+
+use crate::http_client_error;
 
 /// Errors from the crates specific to the hyperlane-tron implementation.
 /// This error can then be converted into the broader error type
@@ -11,15 +14,171 @@ pub enum HyperlaneTronError {
     /// SignatureError error
     #[error("{0}")]
     SignatureError(#[from] heliosphere_signer::keypair::KeypairSignError),
+    /// Mnemonic or key derivation error
+    #[error("{0}")]
+    WalletError(#[from] ethers::signers::WalletError),
     /// ClientError error
     #[error("{0}")]
     ClientError(#[from] heliosphere::Error),
     /// ProviderError error
     #[error("{0}")]
     ProviderError(#[from] ethers::providers::ProviderError),
-    /// ABI error
+    /// HttpClientError error
     #[error("{0}")]
-    AbiError(#[from] ethers::core::abi::AbiError),
+    HttpClientError(#[from] reqwest::Error),
+    /// Error from hyperlane-core, e.g. a failed amount conversion
+    #[error("{0}")]
+    ChainCommunicationError(#[from] ChainCommunicationError),
+    /// Invalid connection configuration
+    #[error("Invalid connection configuration: {0}")]
+    InvalidConnectionConf(String),
+    /// Metadata of a message doesn't pass the verification of its ISM
+    #[error("Metadata of message {message_id:?} fails verification by ISM {ism:?}")]
+    MetadataVerificationFailed {
+        /// Id of the message
+        message_id: H256,
+        /// ISM of the message recipient
+        ism: H256,
+    },
+    /// Transaction unknown to the node past its expiration, so it will never be executed
+    #[error("Transaction {0:?} was dropped")]
+    TransactionDropped(H256),
+    /// Transaction that can't be reissued, e.g. because it may still be executed
+    #[error("Transaction {txid:?} can't be reissued: {reason}")]
+    NotReissuable {
+        /// Id of the transaction
+        txid: H256,
+        /// Why it can't be reissued
+        reason: &'static str,
+    },
+    /// Transaction unknown to the node, e.g. because its hash is wrong
+    #[error("Transaction {0:?} not found")]
+    TransactionNotFound(H512),
+    /// Transaction known to the node, whose receipt isn't available yet
+    #[error("Receipt of transaction {0:?} not available yet")]
+    ReceiptPending(H512),
+    /// The configured fee payer doesn't delegate energy to the signer
+    #[error("Fee payer {fee_payer:?} doesn't delegate energy to signer {signer}")]
+    FeePayerDelegationMissing {
+        /// Account expected to delegate energy
+        fee_payer: H256,
+        /// Base58 address of the signer
+        signer: String,
+    },
+    /// The energy available to the signer doesn't cover a transaction, and burning
+    /// TRX for the rest is disabled
+    #[error("Signer {signer} has {available} energy available, {required} required, and burning TRX for energy is disabled")]
+    InsufficientDelegatedEnergy {
+        /// Base58 address of the signer
+        signer: String,
+        /// Energy the transaction is expected to consume
+        required: u64,
+        /// Energy available to the signer
+        available: u64,
+    },
+    /// The bandwidth available to the signer doesn't cover a transaction, and burning
+    /// TRX for it is disabled
+    #[error("Signer {signer} has {available} bandwidth available, {required} required, and burning TRX for bandwidth is disabled")]
+    InsufficientBandwidth {
+        /// Base58 address of the signer
+        signer: String,
+        /// Estimated size of the transaction, in bytes
+        required: u64,
+        /// Bandwidth available to the signer
+        available: u64,
+    },
+    /// The message is destined to another domain than the one of the mailbox, which
+    /// would revert processing it
+    #[error("Message {message_id:?} is destined to domain {destination}, not to domain {domain} of the mailbox")]
+    WrongDestination {
+        /// Id of the message
+        message_id: H256,
+        /// Destination domain of the message
+        destination: u32,
+        /// Domain of the mailbox
+        domain: u32,
+    },
+    /// The mailbox is paused, so processing messages would revert
+    #[error("Mailbox {0:?} is paused")]
+    MailboxPaused(H256),
+    /// The validator already announced the storage location, so announcing it again
+    /// would revert
+    #[error("Validator {validator:?} already announced {location}")]
+    LocationAlreadyAnnounced {
+        /// Address of the validator
+        validator: H256,
+        /// The announced storage location
+        location: String,
+    },
+    /// The reorg period is a tag, which resolves to the latest solidified block, but the
+    /// node doesn't serve solidified blocks
+    #[error("Reorg period tag `{tag}` requires a node serving solidified blocks (`/walletsolidity`), which the node doesn't: {reason}")]
+    FinalityUnsupported {
+        /// The configured tag
+        tag: String,
+        /// Why querying the solidified block failed
+        reason: String,
+    },
+    /// Recipient of a message has no code, so can't handle it
+    #[error("Recipient {0:?} is not a contract")]
+    RecipientNotAContract(H256),
+    /// No contract is deployed at the address
+    #[error("No contract deployed at {0:?}")]
+    ContractNotFound(H256),
+    /// Calls to the node are short-circuited after it failed repeatedly
+    #[error("Circuit breaker open after repeated failures of the node, retrying in {retry_in:?}")]
+    CircuitOpen {
+        /// Time left until the node is called again
+        retry_in: std::time::Duration,
+    },
+    /// Contract call that reverts, with the decoded revert reason
+    #[error("{context} reverted: {reason}")]
+    Reverted {
+        /// Name of the called function
+        context: String,
+        /// Message of a `require`, name of a known custom error, or the revert data
+        reason: String,
+    },
+    /// Transaction whose raw data can't be decoded
+    #[error("Malformed transaction: {0}")]
+    MalformedTransaction(String),
+    /// Block number beyond the `u32` range of the indexers
+    #[error("Block number {0} doesn't fit in a u32")]
+    BlockNumberOverflow(u64),
+    /// ABI error
+    #[error("ABI error in {context}: {source}")]
+    AbiError {
+        /// Call or event that failed to be encoded or decoded
+        context: String,
+        /// Underlying error
+        #[source]
+        source: ethers::core::abi::AbiError,
+    },
+}
+
+impl HyperlaneTronError {
+    /// Whether the call failed to reach the node or to get its answer, rather than
+    /// being answered with an error, so that making it again may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            HyperlaneTronError::ClientError(heliosphere::Error::RpcError(_))
+            | HyperlaneTronError::HttpClientError(_)
+            | HyperlaneTronError::ProviderError(ProviderError::HTTPError(_)) => true,
+            HyperlaneTronError::ProviderError(err) => matches!(
+                http_client_error(err),
+                Some(HttpClientError::ReqwestError(_) | HttpClientError::SerdeJson { .. })
+            ),
+            _ => false,
+        }
+    }
+
+    /// ABI error in the given call or event
+    pub fn abi(context: impl Into<String>, source: ethers::core::abi::AbiError) -> Self {
+        HyperlaneTronError::AbiError {
+            context: context.into(),
+            source,
+        }
+    }
 }
 
 // Can't use macro because `heliosphere_core::Error` doesn't implement `Error` trait
@@ -31,6 +190,9 @@ impl From<heliosphere_core::Error> for HyperlaneTronError {
 
 impl From<HyperlaneTronError> for ChainCommunicationError {
     fn from(value: HyperlaneTronError) -> Self {
-        ChainCommunicationError::from_other(value)
+        match value {
+            HyperlaneTronError::ChainCommunicationError(err) => err,
+            value => ChainCommunicationError::from_other(value),
+        }
     }
 }
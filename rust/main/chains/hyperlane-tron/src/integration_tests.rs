@@ -0,0 +1,134 @@
+//! Read paths exercised against contracts deployed on a Tron network, e.g. Nile or
+//! Shasta. Ignored by default, as they need the network, and skipped unless the
+//! following environment variables are set:
+//!
+//! - `TRON_TEST_RPC_URL`: JSON-RPC endpoint of a full node, whose HTTP API is served
+//!   on the same host, e.g. `https://nile.trongrid.io/jsonrpc`
+//! - `TRON_TEST_MAILBOX`: address of a mailbox deployed on that network, base58 or hex
+//!
+//! and optionally:
+//!
+//! - `TRON_TEST_API_KEY`: TronGrid API key, to avoid being rate limited
+//! - `TRON_TEST_DELIVERED_MESSAGE_ID`: id of a message delivered by the mailbox
+//!
+//! and, for the merkle tree hook test, which is skipped without it:
+//!
+//! - `TRON_TEST_MERKLE_TREE_HOOK`: address of the merkle tree hook of the mailbox,
+//!   into which at least one message was inserted
+//!
+//! Run them with `cargo test -p hyperlane-tron integration_tests -- --ignored`.
+
+use url::Url;
+
+use hyperlane_core::{
+    ContractLocator, HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneDomainProtocol,
+    HyperlaneDomainTechnicalStack, HyperlaneDomainType, Mailbox, MerkleTreeHook, ReorgPeriod, H256,
+};
+
+use crate::{ConnectionConf, ConnectionConfBuilder, TronAddress, TronMailbox, TronMerkleTreeHook};
+
+struct TestEnv {
+    conf: ConnectionConf,
+    mailbox: H256,
+    merkle_tree_hook: Option<H256>,
+    delivered_message_id: Option<H256>,
+}
+
+/// Test environment from the environment variables, `None` to skip the test if the
+/// required ones are unset
+fn test_env() -> Option<TestEnv> {
+    let (Ok(url), Ok(mailbox)) = (
+        std::env::var("TRON_TEST_RPC_URL"),
+        std::env::var("TRON_TEST_MAILBOX"),
+    ) else {
+        return None;
+    };
+
+    let mut conf = ConnectionConfBuilder::new(Url::parse(&url).expect("invalid TRON_TEST_RPC_URL"));
+    if let Ok(api_key) = std::env::var("TRON_TEST_API_KEY") {
+        conf = conf.with_api_key(api_key);
+    }
+    let address = |var: &str, value: &str| {
+        TronAddress::parse_any(value)
+            .unwrap_or_else(|err| panic!("invalid {var}: {err}"))
+            .into()
+    };
+
+    Some(TestEnv {
+        conf: conf.build().unwrap(),
+        mailbox: address("TRON_TEST_MAILBOX", &mailbox),
+        merkle_tree_hook: std::env::var("TRON_TEST_MERKLE_TREE_HOOK")
+            .ok()
+            .map(|hook| address("TRON_TEST_MERKLE_TREE_HOOK", &hook)),
+        delivered_message_id: std::env::var("TRON_TEST_DELIVERED_MESSAGE_ID")
+            .ok()
+            .map(|id| id.parse().expect("invalid TRON_TEST_DELIVERED_MESSAGE_ID")),
+    })
+}
+
+fn domain(domain_id: u32) -> HyperlaneDomain {
+    HyperlaneDomain::Unknown {
+        domain_id,
+        domain_name: "tron-testnet".to_owned(),
+        domain_type: HyperlaneDomainType::Testnet,
+        domain_protocol: HyperlaneDomainProtocol::Tron,
+        domain_technical_stack: HyperlaneDomainTechnicalStack::Other,
+    }
+}
+
+/// Mailbox of the test environment, on a domain with its actual id
+async fn mailbox(env: &TestEnv) -> TronMailbox {
+    let placeholder = domain(0);
+    let locator = ContractLocator::new(&placeholder, env.mailbox);
+    let mailbox = TronMailbox::new(env.conf.clone(), locator, None).unwrap();
+
+    let domain = domain(mailbox.local_domain().await.unwrap());
+    let locator = ContractLocator::new(&domain, env.mailbox);
+    TronMailbox::new(env.conf.clone(), locator, None).unwrap()
+}
+
+#[tokio::test]
+#[ignore = "Requires a Tron network"]
+async fn test_mailbox_reads() {
+    let Some(env) = test_env() else {
+        return;
+    };
+    let mailbox = mailbox(&env).await;
+
+    assert_eq!(mailbox.address(), env.mailbox);
+    let count = mailbox.count(&ReorgPeriod::None).await.unwrap();
+    let finalized_count = mailbox.count(&ReorgPeriod::from_blocks(20)).await.unwrap();
+    assert!(finalized_count <= count, "{finalized_count} > {count}");
+
+    assert!(!mailbox.delivered(H256::repeat_byte(0xee)).await.unwrap());
+    if let Some(id) = env.delivered_message_id {
+        assert!(mailbox.delivered(id).await.unwrap());
+    }
+}
+
+#[tokio::test]
+#[ignore = "Requires a Tron network"]
+async fn test_merkle_tree_hook_reads() {
+    let Some(env) = test_env() else {
+        return;
+    };
+    let Some(hook_address) = env.merkle_tree_hook else {
+        return;
+    };
+    let mailbox = mailbox(&env).await;
+    let locator = ContractLocator::new(mailbox.domain(), hook_address);
+    let hook = TronMerkleTreeHook::new(env.conf.clone(), locator).unwrap();
+
+    assert!(hook.verify_mailbox(env.mailbox).await.unwrap());
+    assert_eq!(hook.local_domain().await.unwrap(), mailbox.domain().id());
+
+    let checkpoint = hook.latest_checkpoint(&ReorgPeriod::None).await.unwrap();
+    assert_eq!(checkpoint.merkle_tree_hook_address, hook_address);
+    assert_eq!(checkpoint.mailbox_domain, mailbox.domain().id());
+
+    let count = hook.count(&ReorgPeriod::None).await.unwrap();
+    // Messages may be dispatched between the two reads
+    assert!(count > checkpoint.index, "{count} <= {}", checkpoint.index);
+    // Only dispatches of its mailbox are inserted into the tree of the hook
+    assert!(count <= mailbox.count(&ReorgPeriod::None).await.unwrap());
+}
@@ -1,11 +1,16 @@
-pub use {config::*, contracts::*, error::*, rpc_client::*, signer::*};
+pub use {config::*, contracts::*, conversions::*, error::*, rpc_client::*, signer::*};
 
 pub(crate) use address::*;
 
 mod address;
 mod config;
 mod contracts;
+mod conversions;
 mod error;
+#[cfg(test)]
+mod integration_tests;
 mod interfaces;
 mod rpc_client;
 mod signer;
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,180 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::serde_duration;
+use crate::HyperlaneTronError;
+
+/// When calls to a failing node are short-circuited
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CircuitBreakerConf {
+    /// Number of consecutive failures opening the circuit
+    pub failure_threshold: u32,
+    /// How long calls are short-circuited before the node is tried again
+    #[serde(rename = "cooldownSecs", with = "serde_duration::secs")]
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConf {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Calls go through
+    Closed { failures: u32 },
+    /// Calls are short-circuited until the deadline
+    Open { until: Instant },
+    /// The cooldown elapsed and a single probe call was let through, whose result
+    /// decides whether the circuit closes. Other calls are short-circuited meanwhile,
+    /// or until the deadline if the probe never reports back, e.g. when cancelled.
+    HalfOpen { probe_until: Instant },
+}
+
+/// Stops calling a node after consecutive failures, for a cooldown period
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    conf: CircuitBreakerConf,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub fn new(conf: CircuitBreakerConf) -> Self {
+        Self {
+            conf,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether a call may go through, half-opening the circuit for a single probe call
+    /// once the cooldown elapsed
+    pub fn check(&self) -> Result<(), HyperlaneTronError> {
+        let mut state = self.state.lock().unwrap();
+        let until = match *state {
+            State::Closed { .. } => return Ok(()),
+            State::Open { until } => until,
+            State::HalfOpen { probe_until } => probe_until,
+        };
+        let now = Instant::now();
+        if now < until {
+            return Err(HyperlaneTronError::CircuitOpen {
+                retry_in: until - now,
+            });
+        }
+        *state = State::HalfOpen {
+            probe_until: now + self.conf.cooldown,
+        };
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        *self.state.lock().unwrap() = State::Closed { failures: 0 };
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            State::HalfOpen { .. } => self.conf.failure_threshold,
+            // Calls started before the circuit opened
+            State::Open { .. } => return,
+        };
+
+        *state = if failures >= self.conf.failure_threshold {
+            warn!(
+                failures,
+                cooldown = ?self.conf.cooldown,
+                "Tron node keeps failing, short-circuiting calls to it"
+            );
+            State::Open {
+                until: Instant::now() + self.conf.cooldown,
+            }
+        } else {
+            State::Closed { failures }
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConf {
+            failure_threshold: 3,
+            cooldown: Duration::from_millis(50),
+        })
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker();
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+
+        breaker.record_failure();
+        assert!(matches!(
+            breaker.check(),
+            Err(HyperlaneTronError::CircuitOpen { .. })
+        ));
+    }
+
+    #[test]
+    fn test_half_opens_after_cooldown() {
+        let breaker = breaker();
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        assert!(breaker.check().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        assert!(matches!(
+            *breaker.state.lock().unwrap(),
+            State::HalfOpen { .. }
+        ));
+        // Only the probe goes through until it reports back
+        assert!(breaker.check().is_err());
+
+        // A single failure while half-open opens the circuit again
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+        assert_eq!(
+            *breaker.state.lock().unwrap(),
+            State::Closed { failures: 0 }
+        );
+    }
+
+    #[test]
+    fn test_lets_another_probe_through_if_one_never_reports() {
+        let breaker = breaker();
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_err());
+
+        // The probe was cancelled without recording its result
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+    }
+}
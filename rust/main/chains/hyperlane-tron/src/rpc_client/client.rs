@@ -1,18 +1,213 @@
-use heliosphere::{Error, RpcClient};
-use heliosphere_core::block::Block;
+use async_trait::async_trait;
+use ethers::utils::hex;
+use heliosphere::{Error, MethodCall, RpcClient, RpcClientBuilder};
+use heliosphere_core::block::{Block, BlockId};
+use heliosphere_core::transaction::{Transaction, TransactionId};
+use heliosphere_core::Address;
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter};
+use std::future::Future;
 use std::ops::Deref;
-use url::Url;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-pub(crate) struct TronRpcClient(RpcClient);
+use hyperlane_core::H256;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{trace, warn};
+
+use crate::{ConnectionConf, HyperlaneTronError};
+
+use super::circuit_breaker::CircuitBreaker;
+use super::http::rpc_http_client;
+use super::transaction::{permission_id, transaction_json};
+use super::types::{
+    parse_optional_block_response, AccountPermissions, AccountPermissionsResponse,
+    AccountTransactionsResponse, BlockResourceUsage, BroadcastResponse, ChainParameters,
+    ConstantCallResponse, ContractEvent, ContractEventsResponse, ContractResponse,
+    ContractTransactionsResponse, DelegatedResourceResponse, InternalTransactionsResponse,
+    InternalTx, ListProposalsResponse, NodeInfoResponse, TransactionInfoBlock,
+    TransactionInfoReceipt, TriggerContractResponse, TronTxResult,
+};
+
+/// How often the status of a sent transaction is polled, about once per block
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Validity window the node gives transactions by default
+const DEFAULT_TX_EXPIRATION: Duration = Duration::from_secs(60);
+
+/// Margin given to a transaction past its expiration before considering it dropped,
+/// covering the clock skew between the node and this agent
+pub(crate) const DROPPED_TX_GRACE: Duration = Duration::from_secs(10);
+
+/// Path of a query to the TronGrid events API. The event server only knows base58
+/// addresses, and answers queries for any other form with no events.
+fn contract_events_path(address: &Address, block_number: u64, fingerprint: Option<&str>) -> String {
+    let mut path = format!(
+        "/v1/contracts/{}/events?only_confirmed=true&block_number={block_number}&limit=200",
+        address.as_base58()
+    );
+    if let Some(fingerprint) = fingerprint {
+        path.push_str("&fingerprint=");
+        path.push_str(fingerprint);
+    }
+    path
+}
+
+/// Whether a response is the one java-tron, and TronGrid, send along with a 404 status
+/// for an endpoint they don't serve, e.g. `{"Error": "404 page not found"}`
+fn is_endpoint_missing(resp: &serde_json::Value) -> bool {
+    resp.get("Error")
+        .and_then(|err| err.as_str())
+        .is_some_and(|err| err.contains("404") || err.to_lowercase().contains("not found"))
+}
+
+/// Largest page of the TronGrid account transactions API
+const ACCOUNT_TRANSACTIONS_PAGE_SIZE: usize = 200;
+
+/// Path of a query to the TronGrid account transactions API, newest first
+fn account_transactions_path(address: &Address, limit: usize, fingerprint: Option<&str>) -> String {
+    let mut path = format!(
+        "/v1/accounts/{}/transactions?order_by=block_timestamp,desc&limit={limit}",
+        address.as_base58()
+    );
+    if let Some(fingerprint) = fingerprint {
+        path.push_str("&fingerprint=");
+        path.push_str(fingerprint);
+    }
+    path
+}
+
+/// Where a sent transaction stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TxStatus {
+    /// In a solidified block, along with the result of its execution
+    Solidified { contract_ret: Option<String> },
+    /// In a block that isn't solidified yet
+    InBlock,
+    /// In the pending pool of the node
+    Pending,
+    /// Unknown to the node, either not propagated to it yet or dropped
+    Unknown,
+}
+
+/// Queries of the node that the logic built on top of them depends on, so that tests
+/// can exercise that logic against a mock instead of a node
+#[async_trait]
+pub(crate) trait TronRpc: Send + Sync {
+    /// Where the transaction stands, see [`TronRpcClient::get_tx_status`]
+    async fn get_tx_status(&self, txid: TransactionId) -> Result<TxStatus, HyperlaneTronError>;
+}
+
+/// How long fetched chain parameters, e.g. the energy fee, are reused. They only
+/// change through governance proposals, so it is safe to cache them for a while.
+const CHAIN_PARAMETERS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+pub(crate) struct TronRpcClient {
+    client: RpcClient,
+    /// Last fetched chain parameters and when they were fetched
+    chain_parameters_cache: Mutex<Option<(Instant, BTreeMap<String, i64>)>>,
+    chain_parameters_ttl: Duration,
+    /// Energy price used when the node doesn't report `getEnergyFee`
+    fallback_energy_fee: Option<u64>,
+    /// Energy price used instead of the one of the node
+    energy_price_override: Option<u64>,
+    /// Shared with the JSON-RPC transport of the same node
+    circuit_breaker: Arc<CircuitBreaker>,
+    confirmation_poll_interval: Duration,
+    trace_rpc: bool,
+}
 
 impl TronRpcClient {
-    pub fn new(rpc_endpoint: Url) -> Result<Self, Error> {
-        Ok(TronRpcClient(RpcClient::new(rpc_endpoint)?))
+    pub fn new(conf: &ConnectionConf) -> Result<Self, Error> {
+        let client = RpcClientBuilder::new(conf.url.clone())?
+            .with_client(rpc_http_client(conf)?)
+            .build();
+
+        Ok(TronRpcClient {
+            client,
+            chain_parameters_cache: Mutex::new(None),
+            chain_parameters_ttl: CHAIN_PARAMETERS_CACHE_TTL,
+            fallback_energy_fee: conf.fallback_energy_fee,
+            energy_price_override: conf.energy_price_override,
+            circuit_breaker: Arc::new(CircuitBreaker::new(conf.circuit_breaker.clone())),
+            confirmation_poll_interval: CONFIRMATION_POLL_INTERVAL,
+            trace_rpc: conf.trace_rpc,
+        })
+    }
+
+    /// Makes a call through the circuit breaker, which short-circuits calls once the
+    /// node failed repeatedly. Only transport errors count as failures of the node.
+    /// The outcome is logged if configured, also for the helpers of [`RpcClient`]
+    /// whose requests [`Self::api_post`] doesn't see.
+    pub async fn call<'a, T, F, Fut>(&'a self, f: F) -> Result<T, HyperlaneTronError>
+    where
+        T: Debug,
+        F: FnOnce(&'a Self) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.circuit_breaker.check()?;
+
+        let res = f(self).await;
+        if self.trace_rpc {
+            trace!(outcome = ?res, "Tron API call");
+        }
+        match &res {
+            Err(Error::RpcError(_)) => self.circuit_breaker.record_failure(),
+            _ => self.circuit_breaker.record_success(),
+        }
+        Ok(res?)
+    }
+
+    /// Circuit breaker of the node, for the other clients calling it
+    pub fn circuit_breaker(&self) -> &Arc<CircuitBreaker> {
+        &self.circuit_breaker
+    }
+
+    /// Sends a POST request to the node's HTTP API, logging it along with the raw
+    /// response if configured. Shadows [`RpcClient::api_post`], which the helpers of
+    /// [`RpcClient`] itself call instead, see [`Self::call`] for those.
+    pub async fn api_post<P, R>(&self, method: &str, payload: &P) -> Result<R, Error>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        if !self.trace_rpc {
+            return self.client.api_post(method, payload).await;
+        }
+
+        let request = serde_json::to_string(payload).unwrap_or_default();
+        trace!(method, %request, "Tron API request");
+        let response: serde_json::Value = self.client.api_post(method, payload).await?;
+        trace!(method, %response, "Tron API response");
+        serde_json::from_value(response).map_err(|err| Error::UnknownResponse(err.to_string()))
+    }
+
+    /// Sends a GET request to the node's HTTP API, logging the response if configured.
+    /// See [`Self::api_post`].
+    pub async fn api_get<R>(&self, method: &str) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
+    {
+        if !self.trace_rpc {
+            return self.client.api_get(method).await;
+        }
+
+        trace!(method, "Tron API request");
+        let response: serde_json::Value = self.client.api_get(method).await?;
+        trace!(method, %response, "Tron API response");
+        serde_json::from_value(response).map_err(|err| Error::UnknownResponse(err.to_string()))
     }
 
     pub async fn get_finalized_block_number(&self) -> Result<u64, Error> {
-        let resp: Block = self
+        self.get_finalized_block_number_if_served()
+            .await?
+            .ok_or_else(|| Error::UnknownResponse("/walletsolidity/getblock not found".to_owned()))
+    }
+
+    /// Number of the latest solidified block, `None` if the node doesn't serve the
+    /// `/walletsolidity` API, as private nodes may not
+    pub async fn get_finalized_block_number_if_served(&self) -> Result<Option<u64>, Error> {
+        let resp: serde_json::Value = self
             .api_post(
                 "/walletsolidity/getblock",
                 &serde_json::json!({
@@ -20,16 +215,619 @@ impl TronRpcClient {
                 }),
             )
             .await?;
+        if is_endpoint_missing(&resp) {
+            return Ok(None);
+        }
 
-        Ok(resp.block_number())
+        let block: Block =
+            serde_json::from_value(resp).map_err(|err| Error::UnknownResponse(err.to_string()))?;
+        Ok(Some(block.block_number()))
     }
 
     pub async fn get_energy_fee(&self) -> Result<u64, Error> {
+        if let Some(energy_price) = self.energy_price_override {
+            return Ok(energy_price);
+        }
+        let params = self.get_chain_parameters().await?;
+        self.energy_fee(&params)
+    }
+
+    /// Energy fee among chain parameters, or the configured fallback if the node
+    /// doesn't report it, as some custom networks don't
+    fn energy_fee(&self, params: &BTreeMap<String, i64>) -> Result<u64, Error> {
+        match (params.get("getEnergyFee"), self.fallback_energy_fee) {
+            (Some(fee), _) => Ok(*fee as u64),
+            (None, Some(fallback)) => {
+                warn!(
+                    fallback,
+                    "getEnergyFee not found among chain parameters, using the fallback"
+                );
+                Ok(fallback)
+            }
+            (None, None) => Err(Error::UnknownResponse("getEnergyFee not found".to_owned())),
+        }
+    }
+
+    /// All chain parameters, failing if the node doesn't report one of them
+    pub async fn get_typed_chain_parameters(&self) -> Result<ChainParameters, Error> {
+        let params = self.get_chain_parameters().await?;
+        ChainParameters::try_from(&params)
+    }
+
+    /// Chain parameters, fetched at most once per [`CHAIN_PARAMETERS_CACHE_TTL`]
+    async fn get_chain_parameters_cached(&self) -> Result<BTreeMap<String, i64>, Error> {
+        if let Some((fetched_at, params)) = &*self.chain_parameters_cache.lock().unwrap() {
+            if fetched_at.elapsed() < self.chain_parameters_ttl {
+                return Ok(params.clone());
+            }
+        }
+
         let params = self.get_chain_parameters().await?;
+        *self.chain_parameters_cache.lock().unwrap() = Some((Instant::now(), params.clone()));
+        Ok(params)
+    }
+
+    /// Energy fee, fetched at most once per [`CHAIN_PARAMETERS_CACHE_TTL`]
+    pub async fn get_energy_fee_cached(&self) -> Result<u64, Error> {
+        if let Some(energy_price) = self.energy_price_override {
+            return Ok(energy_price);
+        }
+        let params = self.get_chain_parameters_cached().await?;
+        self.energy_fee(&params)
+    }
+
+    /// Price of bandwidth in sun per byte, fetched at most once per
+    /// [`CHAIN_PARAMETERS_CACHE_TTL`]
+    pub async fn get_transaction_fee_cached(&self) -> Result<u64, Error> {
+        let params = self.get_chain_parameters_cached().await?;
         params
-            .get("getEnergyFee")
+            .get("getTransactionFee")
             .map(|v| *v as u64)
-            .ok_or_else(|| Error::UnknownResponse("getEnergyFee not found".to_owned()))
+            .ok_or_else(|| Error::UnknownResponse("getTransactionFee not found".to_owned()))
+    }
+
+    /// Largest fee limit (in sun) a transaction may set, `None` if the node doesn't
+    /// report it. Fetched at most once per [`CHAIN_PARAMETERS_CACHE_TTL`].
+    pub async fn get_max_fee_limit_cached(&self) -> Result<Option<u64>, Error> {
+        let params = self.get_chain_parameters_cached().await?;
+        Ok(params.get("getMaxFeeLimit").map(|v| *v as u64))
+    }
+
+    /// Energy fee changes enacted by governance as (timestamp in ms, fee in sun) pairs,
+    /// oldest first
+    pub async fn get_energy_fee_history(&self) -> Result<Vec<(u64, u64)>, Error> {
+        let resp: ListProposalsResponse = self.api_get("/wallet/listproposals").await?;
+
+        Ok(resp.energy_fee_history())
+    }
+
+    /// Resources consumed by the latest block, relative to the network limits, which
+    /// are fetched at most once per [`CHAIN_PARAMETERS_CACHE_TTL`]
+    pub async fn get_block_resource_usage(&self) -> Result<BlockResourceUsage, Error> {
+        let block_number = self.get_latest_block().await?.block_number();
+        let tx_infos: Vec<TransactionInfoReceipt> = self
+            .api_post(
+                "/wallet/gettransactioninfobyblocknum",
+                &serde_json::json!({
+                    "num": block_number
+                }),
+            )
+            .await?;
+
+        let params = self.get_chain_parameters_cached().await?;
+        let param = |key: &str| {
+            params
+                .get(key)
+                .map(|v| *v as u64)
+                .ok_or_else(|| Error::UnknownResponse(format!("{key} not found")))
+        };
+
+        Ok(BlockResourceUsage::from_tx_infos(
+            block_number,
+            &tx_infos,
+            param("getTotalEnergyCurrentLimit")?,
+            param("getTransactionFee")?,
+        ))
+    }
+
+    /// Id of the transaction that deployed the contract at `address`, `None` if there
+    /// is no such contract. Relies on the TronGrid transaction history API, which
+    /// plain full nodes don't serve.
+    pub async fn get_contract_creation_tx_id(
+        &self,
+        address: &Address,
+    ) -> Result<Option<TransactionId>, Error> {
+        let resp: ContractTransactionsResponse = self
+            .api_get(&format!(
+                "/v1/contracts/{}/transactions?only_confirmed=true&order_by=block_timestamp,asc&limit=1",
+                address.as_base58()
+            ))
+            .await?;
+
+        resp.creation_tx_id()
+            .map(|tx_id| {
+                tx_id
+                    .parse()
+                    .map_err(|_| Error::UnknownResponse(tx_id.to_owned()))
+            })
+            .transpose()
+    }
+
+    /// Confirmed events emitted by the contract at `address` in a block, following
+    /// the pages of the TronGrid event server, which plain full nodes don't serve
+    pub async fn get_contract_events(
+        &self,
+        address: &Address,
+        block_number: u64,
+    ) -> Result<Vec<ContractEvent>, Error> {
+        let mut events = vec![];
+        let mut fingerprint = None;
+
+        loop {
+            let resp: ContractEventsResponse = self
+                .api_get(&contract_events_path(
+                    address,
+                    block_number,
+                    fingerprint.as_deref(),
+                ))
+                .await?;
+            events.extend(resp.data);
+
+            fingerprint = resp.meta.and_then(|meta| meta.fingerprint);
+            if fingerprint.is_none() {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// Ids of the latest transactions of the account at `address`, newest first and at
+    /// most `limit` of them, following the pages of the TronGrid API, which plain full
+    /// nodes don't serve. Empty for an account that doesn't exist on chain.
+    pub async fn get_account_transactions(
+        &self,
+        address: &Address,
+        limit: usize,
+    ) -> Result<Vec<TransactionId>, Error> {
+        let mut tx_ids = vec![];
+        let mut fingerprint = None;
+
+        while tx_ids.len() < limit {
+            let page_size = (limit - tx_ids.len()).min(ACCOUNT_TRANSACTIONS_PAGE_SIZE);
+            let resp: AccountTransactionsResponse = self
+                .api_get(&account_transactions_path(
+                    address,
+                    page_size,
+                    fingerprint.as_deref(),
+                ))
+                .await?;
+            if resp.is_account_not_found() {
+                break;
+            }
+            if !resp.success {
+                return Err(Error::UnknownResponse(resp.error.unwrap_or_default()));
+            }
+
+            for tx in resp.data.iter().take(limit - tx_ids.len()) {
+                let tx_id = tx
+                    .tx_id
+                    .parse()
+                    .map_err(|_| Error::UnknownResponse(tx.tx_id.clone()))?;
+                tx_ids.push(tx_id);
+            }
+
+            fingerprint = resp.meta.and_then(|meta| meta.fingerprint);
+            if fingerprint.is_none() {
+                break;
+            }
+        }
+        Ok(tx_ids)
+    }
+
+    /// Calls made by contracts while executing a transaction, empty if it is unknown
+    pub async fn get_internal_transactions(
+        &self,
+        txid: TransactionId,
+    ) -> Result<Vec<InternalTx>, Error> {
+        let resp: InternalTransactionsResponse = self
+            .api_post(
+                "/wallet/gettransactioninfobyid",
+                &serde_json::json!({ "value": txid }),
+            )
+            .await?;
+
+        Ok(resp
+            .internal_transactions
+            .into_iter()
+            .map(InternalTx::from)
+            .collect())
+    }
+
+    /// Permissions of the account at `address`
+    pub async fn get_account_permissions(
+        &self,
+        address: &Address,
+    ) -> Result<AccountPermissions, Error> {
+        let resp: AccountPermissionsResponse = self
+            .api_post(
+                "/wallet/getaccount",
+                &serde_json::json!({ "address": address.as_hex() }),
+            )
+            .await?;
+
+        resp.into_permissions().ok_or(Error::AccountNotFound)
+    }
+
+    /// Where the transaction stands, looking it up from the most to the least advanced
+    /// stage
+    pub async fn get_tx_status(&self, txid: TransactionId) -> Result<TxStatus, Error> {
+        if let Some(info) = self.get_tx_info_by_id(txid).await? {
+            return Ok(TxStatus::Solidified {
+                contract_ret: info.ret.into_iter().next().map(|ret| ret.contract_ret),
+            });
+        }
+
+        let found = |resp: serde_json::Value| resp.get("txID").is_some();
+        let params = serde_json::json!({ "value": txid });
+        if found(self.api_post("/wallet/gettransactionbyid", &params).await?) {
+            return Ok(TxStatus::InBlock);
+        }
+        if found(
+            self.api_post("/wallet/gettransactionfrompending", &params)
+                .await?,
+        ) {
+            return Ok(TxStatus::Pending);
+        }
+        Ok(TxStatus::Unknown)
+    }
+
+    /// Number of the block including a transaction, solidified or not, `None` if it
+    /// isn't in a block
+    pub async fn get_tx_block_number(&self, txid: TransactionId) -> Result<Option<u64>, Error> {
+        let resp: TransactionInfoBlock = self
+            .api_post(
+                "/wallet/gettransactioninfobyid",
+                &serde_json::json!({ "value": txid }),
+            )
+            .await?;
+
+        Ok(resp.block_number)
+    }
+
+    /// Energy consumed by a transaction, zero if it isn't in a block
+    pub async fn get_tx_energy_used(&self, txid: TransactionId) -> Result<u64, Error> {
+        let resp: TransactionInfoReceipt = self
+            .api_post(
+                "/wallet/gettransactioninfobyid",
+                &serde_json::json!({ "value": txid }),
+            )
+            .await?;
+
+        Ok(resp.receipt.energy_usage_total)
+    }
+
+    /// Executes a contract call on the node without broadcasting it. Unlike
+    /// [`RpcClient::query_contract`], keeps the outcome of a call that reverts, see
+    /// [`ConstantCallResponse::revert_reason`].
+    pub async fn trigger_constant_contract(
+        &self,
+        method_call: &MethodCall<'_>,
+    ) -> Result<ConstantCallResponse, Error> {
+        let resp: ConstantCallResponse = self
+            .api_post(
+                "/wallet/triggerconstantcontract",
+                &serde_json::json!({
+                    "owner_address": method_call.caller.as_hex(),
+                    "contract_address": method_call.contract.as_hex(),
+                    "function_selector": method_call.selector,
+                    "parameter": hex::encode(method_call.parameter),
+                }),
+            )
+            .await?;
+        if let Some(code) = resp.code {
+            return Err(Error::ContractQueryFailed(code, resp.message));
+        }
+        if resp.constant_result.is_empty() && resp.result.code.is_none() {
+            return Err(Error::ContractNotFound);
+        }
+
+        Ok(resp)
+    }
+
+    /// ABI stored on chain for the contract at `address`, `None` if there is no contract
+    /// there. A contract deployed without ABI has an empty one.
+    pub async fn get_contract_abi(
+        &self,
+        address: &Address,
+    ) -> Result<Option<serde_json::Value>, Error> {
+        let resp: ContractResponse = self
+            .api_post(
+                "/wallet/getcontract",
+                &serde_json::json!({ "value": address.as_hex() }),
+            )
+            .await?;
+
+        Ok(resp
+            .bytecode
+            .map(|_| resp.abi.unwrap_or_else(|| serde_json::json!({}))))
+    }
+
+    /// Awaits the execution of a transaction, failing if it reverts or is dropped.
+    /// See [`Self::await_tx_result`].
+    pub async fn await_confirmation(
+        &self,
+        txid: TransactionId,
+        expiration: Option<u64>,
+    ) -> Result<(), HyperlaneTronError> {
+        match self.await_tx_result(txid, expiration).await? {
+            TronTxResult::Success => Ok(()),
+            result => Err(Error::TxFailed(result.to_string()).into()),
+        }
+    }
+
+    /// Awaits the execution of a transaction, returning its result, or failing if it
+    /// is dropped.
+    ///
+    /// A transaction is only considered dropped once the node doesn't know it past its
+    /// `expiration` (in ms), after which it can't be included anymore. Until then it
+    /// may still be propagating, or be broadcast again by a peer after being evicted
+    /// from the pending pool. If the expiration is unknown, the default validity
+    /// window is counted from when the transaction was first found missing.
+    pub async fn await_tx_result(
+        &self,
+        txid: TransactionId,
+        expiration: Option<u64>,
+    ) -> Result<TronTxResult, HyperlaneTronError> {
+        await_tx_result(self, txid, expiration, self.confirmation_poll_interval).await
+    }
+
+    /// TRX (sun) staked by `from` for energy delegated to `to`, zero if none
+    pub async fn get_delegated_energy_stake(
+        &self,
+        from: &Address,
+        to: &Address,
+    ) -> Result<u64, Error> {
+        let resp: DelegatedResourceResponse = self
+            .api_post(
+                "/wallet/getdelegatedresourcev2",
+                &serde_json::json!({
+                    "fromAddress": from.as_hex(),
+                    "toAddress": to.as_hex(),
+                }),
+            )
+            .await?;
+
+        Ok(resp.staked_for_energy())
+    }
+
+    /// Version of the java-tron node
+    pub async fn get_node_version(&self) -> Result<String, Error> {
+        let resp: NodeInfoResponse = self.api_get("/wallet/getnodeinfo").await?;
+        Ok(resp.config_node_info.code_version)
+    }
+
+    /// Unsigned transfer of `amount` sun from `from` to `to`. Unlike
+    /// [`RpcClient::trx_transfer`], it carries no memo, which would cost bandwidth.
+    pub async fn create_trx_transfer(
+        &self,
+        from: &Address,
+        to: &Address,
+        amount: u64,
+    ) -> Result<Transaction, Error> {
+        self.api_post(
+            "/wallet/createtransaction",
+            &serde_json::json!({
+                "owner_address": from.as_hex(),
+                "to_address": to.as_hex(),
+                "amount": amount,
+            }),
+        )
+        .await
+    }
+
+    /// Transaction with the given id, `None` if the node doesn't know it or only has
+    /// it in its pending pool
+    pub async fn get_transaction_by_id(
+        &self,
+        txid: TransactionId,
+    ) -> Result<Option<Transaction>, Error> {
+        let resp: serde_json::Value = self
+            .api_post(
+                "/wallet/gettransactionbyid",
+                &serde_json::json!({ "value": txid }),
+            )
+            .await?;
+        if resp.get("txID").is_none() {
+            return Ok(None);
+        }
+
+        serde_json::from_value(resp)
+            .map(Some)
+            .map_err(|err| Error::UnknownResponse(err.to_string()))
+    }
+
+    /// Unsigned transaction calling a contract, to be signed under the permission
+    /// `permission_id` of the caller, the owner permission if `None`. The fee limit is
+    /// estimated if not given.
+    pub async fn trigger_contract_as(
+        &self,
+        method_call: &MethodCall<'_>,
+        fee_limit: Option<u64>,
+        permission_id: Option<u32>,
+    ) -> Result<Transaction, Error> {
+        let Some(permission_id) = permission_id else {
+            return self
+                .client
+                .trigger_contract(method_call, 0, fee_limit)
+                .await;
+        };
+        let fee_limit = match fee_limit {
+            Some(fee_limit) => fee_limit,
+            None => self.estimate_fee_limit(method_call).await?,
+        };
+
+        let resp: TriggerContractResponse = self
+            .api_post(
+                "/wallet/triggersmartcontract",
+                &serde_json::json!({
+                    "owner_address": method_call.caller.as_hex(),
+                    "contract_address": method_call.contract.as_hex(),
+                    "function_selector": method_call.selector,
+                    "parameter": hex::encode(method_call.parameter),
+                    "fee_limit": fee_limit,
+                    "call_value": 0,
+                    "Permission_id": permission_id,
+                }),
+            )
+            .await?;
+        Ok(resp.transaction)
+    }
+
+    /// Broadcasts a signed transaction. Shadows [`RpcClient::broadcast_transaction`],
+    /// which drops the permission id of a transaction signed under another permission
+    /// than the owner's, failing its signature check.
+    pub async fn broadcast_transaction(&self, tx: &Transaction) -> Result<TransactionId, Error> {
+        let body = transaction_json(tx).map_err(|err| Error::UnknownResponse(err.to_string()))?;
+        let resp: BroadcastResponse = self.api_post("/wallet/broadcasttransaction", &body).await?;
+
+        match resp.code {
+            Some(code) => Err(Error::TxConstructionFailed(
+                code,
+                hex::decode(&resp.message)
+                    .ok()
+                    .and_then(|message| String::from_utf8(message).ok())
+                    .unwrap_or(resp.message),
+            )),
+            None => Ok(resp.txid),
+        }
+    }
+
+    /// Unsigned transaction making the same call or transfer as `original`, with the
+    /// same fee limit, but a new reference block and expiration
+    pub async fn rebuild_transaction(&self, original: &Transaction) -> Result<Transaction, Error> {
+        let contract = original
+            .raw_data
+            .contract
+            .first()
+            .ok_or_else(|| Error::UnknownResponse("transaction without contract".to_owned()))?;
+        let value = &contract.parameter["value"];
+
+        match contract.r#type.as_str() {
+            "TriggerSmartContract" => {
+                let resp: TriggerContractResponse = self
+                    .api_post(
+                        "/wallet/triggersmartcontract",
+                        &serde_json::json!({
+                            "owner_address": value["owner_address"],
+                            "contract_address": value["contract_address"],
+                            "data": value["data"],
+                            "call_value": value["call_value"].as_u64().unwrap_or_default(),
+                            "fee_limit": original.raw_data.fee_limit,
+                            "Permission_id": permission_id(original)
+                                .map_err(|err| Error::UnknownResponse(err.to_string()))?
+                                .unwrap_or_default(),
+                        }),
+                    )
+                    .await?;
+                Ok(resp.transaction)
+            }
+            "TransferContract" => {
+                self.api_post(
+                    "/wallet/createtransaction",
+                    &serde_json::json!({
+                        "owner_address": value["owner_address"],
+                        "to_address": value["to_address"],
+                        "amount": value["amount"],
+                    }),
+                )
+                .await
+            }
+            other => Err(Error::UnknownResponse(format!(
+                "can't rebuild {other} transactions"
+            ))),
+        }
+    }
+
+    /// Block at the given height, `None` if the node doesn't have it yet
+    pub async fn get_block_by_number(&self, number: u64) -> Result<Option<Block>, Error> {
+        let resp: serde_json::Value = self
+            .api_post(
+                "/wallet/getblockbynum",
+                &serde_json::json!({ "num": number }),
+            )
+            .await?;
+
+        parse_optional_block_response(resp).map_err(|err| Error::UnknownResponse(err.to_string()))
+    }
+
+    /// Block with the given id (hash), `None` if the node doesn't know it
+    pub async fn get_block_by_id(&self, id: BlockId) -> Result<Option<Block>, Error> {
+        let resp: serde_json::Value = self
+            .api_post(
+                "/wallet/getblockbyid",
+                &serde_json::json!({
+                    "value": id.to_string()
+                }),
+            )
+            .await?;
+
+        parse_optional_block_response(resp).map_err(|err| Error::UnknownResponse(err.to_string()))
+    }
+}
+
+#[async_trait]
+impl TronRpc for TronRpcClient {
+    async fn get_tx_status(&self, txid: TransactionId) -> Result<TxStatus, HyperlaneTronError> {
+        self.call(|client| TronRpcClient::get_tx_status(client, txid))
+            .await
+    }
+}
+
+/// Polls the status of a transaction until it is executed or dropped, see
+/// [`TronRpcClient::await_tx_result`]
+pub(crate) async fn await_tx_result<R: TronRpc + ?Sized>(
+    rpc: &R,
+    txid: TransactionId,
+    expiration: Option<u64>,
+    poll_interval: Duration,
+) -> Result<TronTxResult, HyperlaneTronError> {
+    let mut missing_since: Option<Instant> = None;
+    let mut was_pending = false;
+    loop {
+        match rpc.get_tx_status(txid).await? {
+            TxStatus::Solidified { contract_ret } => {
+                return Ok(TronTxResult::from(
+                    contract_ret.as_deref().unwrap_or("empty ret"),
+                ));
+            }
+            TxStatus::InBlock => missing_since = None,
+            TxStatus::Pending => {
+                missing_since = None;
+                was_pending = true;
+            }
+            TxStatus::Unknown => {
+                if was_pending {
+                    warn!(
+                        ?txid,
+                        "Transaction evicted from the pending pool of the node"
+                    );
+                    was_pending = false;
+                }
+                let missing_since = *missing_since.get_or_insert_with(Instant::now);
+                let expired = match expiration {
+                    Some(expiration) => {
+                        let now = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default();
+                        now > Duration::from_millis(expiration) + DROPPED_TX_GRACE
+                    }
+                    None => missing_since.elapsed() > DEFAULT_TX_EXPIRATION + DROPPED_TX_GRACE,
+                };
+                if expired {
+                    return Err(HyperlaneTronError::TransactionDropped(H256::from(txid.0)));
+                }
+            }
+        }
+        tokio::time::sleep(poll_interval).await;
     }
 }
 
@@ -43,6 +841,351 @@ impl Deref for TronRpcClient {
     type Target = RpcClient;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.client
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use tracing_test::traced_test;
+    use url::Url;
+
+    use crate::test_utils::{self, MockTronNode, MockTronRpc};
+    use crate::CircuitBreakerConf;
+
+    use super::*;
+
+    async fn node(requests: Arc<AtomicUsize>) -> MockTronNode {
+        MockTronNode::start(move |path, _| {
+            assert_eq!(path, "/wallet/getchainparameters");
+            let fee = 100 * (requests.fetch_add(1, Ordering::SeqCst) + 1);
+            serde_json::json!({
+                "chainParameter": [{"key": "getEnergyFee", "value": fee}]
+            })
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_trace_rpc() {
+        let node = MockTronNode::start(|path, _| match path {
+            "/walletsolidity/getblock" => test_utils::block(42),
+            "/wallet/getnowblock" => test_utils::block(43),
+            _ => panic!("unexpected request to {path}"),
+        })
+        .await;
+
+        let client = TronRpcClient::new(&node.conf()).unwrap();
+        client.get_finalized_block_number().await.unwrap();
+        client
+            .call(|client| client.get_latest_block())
+            .await
+            .unwrap();
+        assert!(!logs_contain("Tron API"));
+
+        let client = TronRpcClient::new(&ConnectionConf {
+            trace_rpc: true,
+            api_key: Some("secret-key".to_owned().into()),
+            ..node.conf()
+        })
+        .unwrap();
+        assert_eq!(client.get_finalized_block_number().await.unwrap(), 42);
+
+        assert!(logs_contain(
+            r#"Tron API request method="/walletsolidity/getblock" request={"detail":false}"#
+        ));
+        assert!(logs_contain(
+            r#"Tron API response method="/walletsolidity/getblock" response={"blockID""#
+        ));
+        assert!(!logs_contain("secret-key"));
+
+        // Helpers of the underlying client, which bypass `api_post`, are logged by the
+        // call wrapper
+        let latest = client
+            .call(|client| client.get_latest_block())
+            .await
+            .unwrap();
+        assert_eq!(latest.block_number(), 43);
+        assert!(!logs_contain("/wallet/getnowblock"));
+        assert!(logs_contain("Tron API call outcome=Ok(Block"));
+    }
+
+    #[tokio::test]
+    async fn test_get_typed_chain_parameters() {
+        // Sample of a mainnet response. Zero values are left out, like for
+        // `getAllowUpdateAccountName`.
+        let node = MockTronNode::start(|path, _| {
+            assert_eq!(path, "/wallet/getchainparameters");
+            serde_json::json!({
+                "chainParameter": [
+                    {"key": "getMaintenanceTimeInterval", "value": 21600000},
+                    {"key": "getAccountUpgradeCost", "value": 9999000000u64},
+                    {"key": "getCreateAccountFee", "value": 100000},
+                    {"key": "getTransactionFee", "value": 1000},
+                    {"key": "getAssetIssueFee", "value": 1024000000},
+                    {"key": "getWitnessPayPerBlock", "value": 8000000},
+                    {"key": "getWitnessStandbyAllowance", "value": 115200000000u64},
+                    {"key": "getCreateNewAccountFeeInSystemContract", "value": 1000000},
+                    {"key": "getCreateNewAccountBandwidthRate", "value": 1},
+                    {"key": "getAllowCreationOfContracts", "value": 1},
+                    {"key": "getRemoveThePowerOfTheGr", "value": -1},
+                    {"key": "getEnergyFee", "value": 210},
+                    {"key": "getExchangeCreateFee", "value": 1024000000},
+                    {"key": "getMaxCpuTimeOfOneTx", "value": 80},
+                    {"key": "getAllowUpdateAccountName"},
+                    {"key": "getTotalEnergyLimit", "value": 90000000000u64},
+                    {"key": "getTotalEnergyCurrentLimit", "value": 180000000000u64},
+                    {"key": "getFreeNetLimit", "value": 600},
+                    {"key": "getTotalNetLimit", "value": 43200000000u64},
+                    {"key": "getMaxFeeLimit", "value": 15000000000u64},
+                    {"key": "getMemoFee", "value": 1000000},
+                    {"key": "getAllowDynamicEnergy", "value": 1},
+                    {"key": "getDynamicEnergyThreshold", "value": 5000000000u64},
+                    {"key": "getDynamicEnergyIncreaseFactor", "value": 2000},
+                    {"key": "getDynamicEnergyMaxFactor", "value": 34000}
+                ]
+            })
+        })
+        .await;
+        let client = TronRpcClient::new(&node.conf()).unwrap();
+
+        let params = client.get_typed_chain_parameters().await.unwrap();
+
+        assert_eq!(
+            params,
+            ChainParameters {
+                energy_fee: 210,
+                transaction_fee: 1000,
+                max_fee_limit: 15_000_000_000,
+                create_account_fee: 100_000,
+                create_new_account_fee_in_system_contract: 1_000_000,
+                memo_fee: 1_000_000,
+                max_cpu_time_of_one_tx: 80,
+                total_energy_current_limit: 180_000_000_000,
+                free_net_limit: 600,
+                total_net_limit: 43_200_000_000,
+                maintenance_time_interval: 21_600_000,
+                allow_dynamic_energy: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_typed_chain_parameters_missing_parameter() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node = node(requests).await;
+        let client = TronRpcClient::new(&node.conf()).unwrap();
+
+        let err = client.get_typed_chain_parameters().await.unwrap_err();
+
+        assert!(
+            matches!(&err, Error::UnknownResponse(message) if message == "getTransactionFee not found"),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_energy_fee_cached_within_ttl() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node = node(requests.clone()).await;
+        let client = TronRpcClient::new(&node.conf()).unwrap();
+
+        assert_eq!(client.get_energy_fee_cached().await.unwrap(), 100);
+        assert_eq!(client.get_energy_fee_cached().await.unwrap(), 100);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_energy_fee_refetched_after_ttl() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node = node(requests.clone()).await;
+        let mut client = TronRpcClient::new(&node.conf()).unwrap();
+        client.chain_parameters_ttl = Duration::ZERO;
+
+        assert_eq!(client.get_energy_fee_cached().await.unwrap(), 100);
+        assert_eq!(client.get_energy_fee_cached().await.unwrap(), 200);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_block_resource_usage_uses_cached_chain_parameters() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node = {
+            let requests = requests.clone();
+            MockTronNode::start(move |path, _| match path {
+                "/wallet/getnowblock" => test_utils::block(42),
+                "/wallet/gettransactioninfobyblocknum" => serde_json::json!([]),
+                "/wallet/getchainparameters" => {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    serde_json::json!({
+                        "chainParameter": [
+                            {"key": "getTotalEnergyCurrentLimit", "value": 28_800_000_000u64},
+                            {"key": "getTransactionFee", "value": 1000}
+                        ]
+                    })
+                }
+                _ => panic!("unexpected request to {path}"),
+            })
+            .await
+        };
+        let client = TronRpcClient::new(&node.conf()).unwrap();
+
+        for _ in 0..2 {
+            let usage = client.get_block_resource_usage().await.unwrap();
+            assert_eq!(usage.block_number, 42);
+            assert_eq!(usage.energy_budget, 1_000_000);
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_energy_fee_fallback() {
+        let node = MockTronNode::start(|path, _| match path {
+            "/wallet/getchainparameters" => serde_json::json!({
+                "chainParameter": [{"key": "getCustomEnergyFee", "value": 100}]
+            }),
+            _ => serde_json::json!({}),
+        })
+        .await;
+
+        let client = TronRpcClient::new(&node.conf()).unwrap();
+        let err = client.get_energy_fee_cached().await.unwrap_err();
+        assert!(
+            matches!(&err, Error::UnknownResponse(message) if message == "getEnergyFee not found"),
+            "{err:?}"
+        );
+
+        let client = TronRpcClient::new(&ConnectionConf {
+            fallback_energy_fee: Some(420),
+            ..node.conf()
+        })
+        .unwrap();
+        assert_eq!(client.get_energy_fee_cached().await.unwrap(), 420);
+        assert_eq!(client.get_energy_fee().await.unwrap(), 420);
+        assert!(logs_contain("using the fallback"));
+    }
+
+    #[tokio::test]
+    async fn test_energy_price_override() {
+        let node = MockTronNode::start(|path, _| panic!("unexpected request to {path}")).await;
+
+        let client = TronRpcClient::new(&ConnectionConf {
+            energy_price_override: Some(100),
+            ..node.conf()
+        })
+        .unwrap();
+        assert_eq!(client.get_energy_fee().await.unwrap(), 100);
+        assert_eq!(client.get_energy_fee_cached().await.unwrap(), 100);
+        assert_eq!(node.connections(), 0);
+    }
+
+    /// Client of a node reporting the transaction at the given stages, one per poll,
+    /// the last one repeatedly
+    async fn node_with_tx_stages(stages: Vec<&'static str>) -> (MockTronNode, TronRpcClient) {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let node = MockTronNode::start(move |path, body| {
+            let poll = match path {
+                "/walletsolidity/gettransactionbyid" => polls.fetch_add(1, Ordering::SeqCst),
+                _ => polls.load(Ordering::SeqCst) - 1,
+            };
+            let stage = stages[poll.min(stages.len() - 1)];
+            let txid = body["value"].as_str().unwrap();
+            let mut tx = test_utils::transaction(txid);
+            match (stage, path) {
+                ("solidified", "/walletsolidity/gettransactionbyid") => {
+                    tx["ret"] = serde_json::json!([{"contractRet": "SUCCESS"}]);
+                    tx
+                }
+                ("in_block", "/wallet/gettransactionbyid") => tx,
+                ("pending", "/wallet/gettransactionfrompending") => tx,
+                _ => serde_json::json!({}),
+            }
+        })
+        .await;
+        let mut client = TronRpcClient::new(&node.conf()).unwrap();
+        client.confirmation_poll_interval = Duration::from_millis(10);
+
+        (node, client)
+    }
+
+    #[tokio::test]
+    async fn test_await_confirmation_keeps_polling_not_yet_available_tx() {
+        let (_node, client) =
+            node_with_tx_stages(vec!["unknown", "pending", "in_block", "solidified"]).await;
+        let expiration = SystemTime::now() + Duration::from_secs(60);
+        let expiration = expiration.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+
+        client
+            .await_confirmation(TransactionId([1; 32]), Some(expiration))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_await_confirmation_fails_dropped_tx() {
+        // Evicted from the pending pool, and expired since
+        let (_node, client) = node_with_tx_stages(vec!["pending", "unknown"]).await;
+        let expiration = SystemTime::now() - DROPPED_TX_GRACE - Duration::from_secs(1);
+        let expiration = expiration.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+
+        let res = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.await_confirmation(TransactionId([1; 32]), Some(expiration)),
+        )
+        .await
+        .expect("dropped transaction polled indefinitely");
+
+        assert!(matches!(
+            res,
+            Err(HyperlaneTronError::TransactionDropped(txid)) if txid == H256([1; 32])
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_await_tx_result_with_mock_rpc() {
+        let rpc = MockTronRpc::with_tx_statuses([
+            TxStatus::Unknown,
+            TxStatus::Pending,
+            TxStatus::InBlock,
+            TxStatus::Solidified {
+                contract_ret: Some("OUT_OF_ENERGY".to_owned()),
+            },
+        ]);
+
+        let result = await_tx_result(&rpc, TransactionId([1; 32]), None, Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(result, TronTxResult::OutOfEnergy);
+        assert_eq!(rpc.tx_status_queries.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_short_circuits_unreachable_node() {
+        let conf = ConnectionConf {
+            circuit_breaker: CircuitBreakerConf {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            },
+            // Nothing listens on port 1
+            ..test_utils::conf(Url::parse("http://127.0.0.1:1").unwrap())
+        };
+        let client = TronRpcClient::new(&conf).unwrap();
+
+        for _ in 0..2 {
+            let res = client.call(|client| client.get_latest_block()).await;
+            assert!(matches!(
+                res,
+                Err(HyperlaneTronError::ClientError(Error::RpcError(_)))
+            ));
+        }
+
+        let res = client.call(|client| client.get_latest_block()).await;
+        assert!(matches!(res, Err(HyperlaneTronError::CircuitOpen { .. })));
     }
 }
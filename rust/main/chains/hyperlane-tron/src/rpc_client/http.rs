@@ -0,0 +1,196 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, ProviderError};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{ConnectionConf, HyperlaneTronError, TRON_API_KEY_HEADER};
+
+use super::circuit_breaker::CircuitBreaker;
+
+/// Builds an HTTP client with the pool, timeout and API key settings of a
+/// connection, with either version of reqwest, as ethers and heliosphere depend on
+/// different ones
+macro_rules! http_client {
+    ($reqwest:ident, $conf:expr) => {{
+        use $reqwest::header::{HeaderMap, HeaderValue};
+
+        let conf: &ConnectionConf = $conf;
+        let pool = &conf.connection_pool;
+        let mut headers = HeaderMap::new();
+        // Invalid keys are rejected when validating the configuration
+        if let Some(Ok(mut api_key)) = conf.api_key.as_deref().map(HeaderValue::from_str) {
+            api_key.set_sensitive(true);
+            headers.insert(TRON_API_KEY_HEADER, api_key);
+        }
+
+        let mut builder = $reqwest::Client::builder()
+            .pool_max_idle_per_host(pool.max_idle_per_host)
+            .pool_idle_timeout(pool.idle_timeout)
+            .tcp_keepalive(pool.tcp_keepalive)
+            .default_headers(headers);
+        if let Some(timeout) = conf.timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder.build()
+    }};
+}
+
+/// HTTP client of the ethers JSON-RPC provider
+pub(crate) fn eth_http_client(conf: &ConnectionConf) -> reqwest::Result<reqwest::Client> {
+    http_client!(reqwest, conf)
+}
+
+/// HTTP client of the heliosphere Tron API client
+pub(crate) fn rpc_http_client(
+    conf: &ConnectionConf,
+) -> heliosphere_reqwest::Result<heliosphere_reqwest::Client> {
+    http_client!(heliosphere_reqwest, conf)
+}
+
+/// JSON-RPC transport of the ethers provider, going through the circuit breaker of
+/// the Tron API client of the same node
+#[derive(Debug, Clone)]
+pub(crate) struct BreakerHttp {
+    inner: Http,
+    circuit_breaker: Arc<CircuitBreaker>,
+}
+
+impl BreakerHttp {
+    pub fn new(inner: Http, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        Self {
+            inner,
+            circuit_breaker,
+        }
+    }
+}
+
+/// Error of a [`BreakerHttp`] request
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BreakerHttpError {
+    #[error(transparent)]
+    Http(#[from] HttpClientError),
+    #[error("Tron node short-circuited after repeated failures, retry in {retry_in:?}")]
+    CircuitOpen { retry_in: Duration },
+}
+
+impl From<BreakerHttpError> for ProviderError {
+    fn from(err: BreakerHttpError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for BreakerHttp {
+    type Error = BreakerHttpError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        if let Err(HyperlaneTronError::CircuitOpen { retry_in }) = self.circuit_breaker.check() {
+            return Err(BreakerHttpError::CircuitOpen { retry_in });
+        }
+
+        // Only transport errors count as failures of the node, like for the Tron API
+        let res = self.inner.request(method, params).await;
+        match &res {
+            Err(HttpClientError::ReqwestError(_)) => self.circuit_breaker.record_failure(),
+            _ => self.circuit_breaker.record_success(),
+        }
+        Ok(res?)
+    }
+}
+
+/// Error of the HTTP transport of the ethers provider behind `err`, none if the call
+/// was short-circuited
+pub(crate) fn http_client_error(err: &ProviderError) -> Option<&HttpClientError> {
+    match err {
+        ProviderError::JsonRpcClientError(err) => match err.downcast_ref::<BreakerHttpError>()? {
+            BreakerHttpError::Http(err) => Some(err),
+            BreakerHttpError::CircuitOpen { .. } => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_utils::MockTronNode, ConnectionPoolConf};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pooled_clients_reuse_connections() {
+        let node = MockTronNode::start(|_, _| serde_json::json!({})).await;
+        let client = eth_http_client(&node.conf()).unwrap();
+        for _ in 0..3 {
+            client
+                .get(node.url.clone())
+                .send()
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap();
+        }
+        assert_eq!(node.connections(), 1);
+
+        let node = MockTronNode::start(|_, _| serde_json::json!({})).await;
+        let client = rpc_http_client(&node.conf()).unwrap();
+        for _ in 0..3 {
+            client
+                .get(node.url.clone())
+                .send()
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap();
+        }
+        assert_eq!(node.connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_clients_without_idle_connections() {
+        let pool = ConnectionPoolConf {
+            max_idle_per_host: 0,
+            ..Default::default()
+        };
+        let conf = |node: &MockTronNode| ConnectionConf {
+            connection_pool: pool.clone(),
+            ..node.conf()
+        };
+
+        let node = MockTronNode::start(|_, _| serde_json::json!({})).await;
+        let client = eth_http_client(&conf(&node)).unwrap();
+        for _ in 0..3 {
+            client
+                .get(node.url.clone())
+                .send()
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap();
+        }
+        assert_eq!(node.connections(), 3);
+
+        let node = MockTronNode::start(|_, _| serde_json::json!({})).await;
+        let client = rpc_http_client(&conf(&node)).unwrap();
+        for _ in 0..3 {
+            client
+                .get(node.url.clone())
+                .send()
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap();
+        }
+        assert_eq!(node.connections(), 3);
+    }
+}
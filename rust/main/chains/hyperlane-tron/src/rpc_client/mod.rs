@@ -1,6 +1,21 @@
+pub use circuit_breaker::CircuitBreakerConf;
 pub use provider::*;
+pub use retry::*;
+pub use transaction::MAX_TX_EXPIRATION;
+pub use types::{
+    AccountPermissions, BlockResourceUsage, ChainParameters, ContractEvent, CursorSnapshot,
+    Diagnostics, ForkReport, InternalTx, Permission, PermissionKey, SignerDiagnostics,
+    TronCostEstimate, TronTxOutcome, TronTxResult,
+};
 
 pub(crate) use client::*;
+pub(crate) use http::http_client_error;
+pub(crate) use transaction::*;
 
+mod circuit_breaker;
 mod client;
+mod http;
 mod provider;
+mod retry;
+mod transaction;
+mod types;
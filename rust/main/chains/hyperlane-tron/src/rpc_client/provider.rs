@@ -1,21 +1,84 @@
+use std::collections::VecDeque;
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use ethers::{
     providers::{Http, Middleware, Provider},
-    types::H160,
+    types::{H160, H256 as EthersH256},
 };
-use tracing::instrument;
+use futures::future::{join_all, try_join_all};
+use futures::stream::{self, Stream};
+use heliosphere_core::{
+    block::{Block, BlockId},
+    transaction::{Transaction, TransactionId},
+};
+use heliosphere_signer::signer::Signer as _;
+use tokio::time::sleep;
+use tracing::{instrument, warn};
+use url::Url;
 
 use hyperlane_core::{
-    BlockInfo, ChainCommunicationError, ChainInfo, ChainResult, HyperlaneChain, HyperlaneDomain,
-    HyperlaneProvider, TxnInfo, H256, H512, U256,
+    BlockInfo, ChainCommunicationError, ChainInfo, ChainResult, FixedPointNumber, HyperlaneChain,
+    HyperlaneDomain, HyperlaneProvider, HyperlaneProviderError, ReorgPeriod, TxOutcome, TxnInfo,
+    TxnReceiptInfo, H256, H512, U256,
+};
+
+use crate::{
+    energy_to_gas, format_sun, sun_to_fixed_point, AccountPermissions, BlockResourceUsage,
+    ChainParameters, ConnectionConf, ContractEvent, CursorSnapshot, Diagnostics, FeeLimitBumpConf,
+    ForkReport, HyperlaneTronError, InternalTx, RetryPolicy, Signer, SignerDiagnostics,
+    TronAddress, TronRpc, TronRpcClient, TronTxResult, TxStatus, DROPPED_TX_GRACE,
+    TRON_SOLIDIFICATION_BLOCKS,
 };
 
-use crate::{ConnectionConf, HyperlaneTronError, TronRpcClient};
+use super::transaction::{is_duplicate_error, set_expiration};
+
+use super::http::{eth_http_client, BreakerHttp};
+
+pub(crate) type TronEthClient = Provider<BreakerHttp>;
+
+/// Storage slot of the implementation of EIP-1967 proxies,
+/// `keccak256("eip1967.proxy.implementation") - 1`
+const EIP1967_IMPLEMENTATION_SLOT: EthersH256 = EthersH256([
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+]);
 
-pub(crate) type TronEthClient = Provider<Http>;
+/// Called with the id of each transaction accepted by the node, before its
+/// confirmation is awaited. Lets callers persist the id so that, if they are
+/// cancelled while awaiting, the outcome can be recovered with
+/// [`TronProvider::await_outcome`].
+#[derive(Clone)]
+pub struct BroadcastListener(Arc<dyn Fn(H512) + Send + Sync>);
+
+impl BroadcastListener {
+    pub fn new(listener: impl Fn(H512) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(listener))
+    }
+}
+
+impl Debug for BroadcastListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BroadcastListener { ... }")
+    }
+}
+
+/// Number of sent transactions remembered to be reissued, see
+/// [`TronProvider::reissue`]
+const MAX_REMEMBERED_TRANSACTIONS: usize = 256;
+
+/// Interval between blocks targeted by Tron, assumed when it can't be measured
+const DEFAULT_BLOCK_TIME: Duration = Duration::from_secs(3);
+
+/// Number of recent blocks over which the block time is averaged
+const BLOCK_TIME_SAMPLE: u64 = 20;
+
+/// How long a transaction is awaited before being considered unconfirmed.
+/// Covers the solidification of the block including it with a wide margin.
+pub const TX_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(180);
 
 /// Abstraction over a connection to a Tron chain
 #[derive(Clone, Debug)]
@@ -23,16 +86,986 @@ pub struct TronProvider {
     domain: HyperlaneDomain,
     pub(crate) eth_client: Arc<TronEthClient>,
     pub(crate) rpc_client: Arc<TronRpcClient>,
+    url: Url,
+    /// Clients of the other nodes the node is checked against for forks
+    reference_clients: Vec<(Url, Arc<TronRpcClient>)>,
+    /// Maximum block range of a log query, `0` if unbounded.
+    /// Shared between clones so that a limit discovered once is used everywhere.
+    max_log_range: Arc<AtomicU32>,
+    max_concurrent_requests: u32,
+    retry_policy: RetryPolicy,
+    wait_for_confirmation: bool,
+    preflight_verify: bool,
+    check_recipient_is_contract: bool,
+    check_paused: bool,
+    prefer_finalized_reads: bool,
+    multicall_address: Option<H256>,
+    fee_payer: Option<H256>,
+    allow_energy_burn: bool,
+    tx_expiration: Option<Duration>,
+    min_energy_limit: Option<u64>,
+    permission_id: Option<u32>,
+    max_fee_fraction_of_balance: Option<f64>,
+    fee_limit_bump: Option<FeeLimitBumpConf>,
+    ordered_submission: bool,
+    broadcast_listener: Option<BroadcastListener>,
+    /// Latest transactions sent, shared between clones
+    sent_transactions: Arc<Mutex<VecDeque<Transaction>>>,
 }
 
 impl TronProvider {
     pub fn new(domain: HyperlaneDomain, conf: ConnectionConf) -> Result<Self, HyperlaneTronError> {
+        let http = eth_http_client(&conf)?;
+        let reference_clients = conf
+            .reference_urls
+            .iter()
+            .map(|url| {
+                let conf = ConnectionConf {
+                    url: url.clone(),
+                    api_key: conf
+                        .reference_api_keys
+                        .get(url)
+                        .or(conf.api_key.as_ref())
+                        .cloned(),
+                    ..conf.clone()
+                };
+                Ok((url.clone(), Arc::new(TronRpcClient::new(&conf)?)))
+            })
+            .collect::<Result<_, HyperlaneTronError>>()?;
+        let rpc_client = TronRpcClient::new(&conf)?;
+        let transport = BreakerHttp::new(
+            Http::new_with_client(conf.url.clone(), http),
+            rpc_client.circuit_breaker().clone(),
+        );
+
         Ok(TronProvider {
             domain,
-            eth_client: Arc::new(Provider::new(Http::new(conf.url.clone()))),
-            rpc_client: Arc::new(TronRpcClient::new(conf.url)?),
+            eth_client: Arc::new(Provider::new(transport)),
+            rpc_client: Arc::new(rpc_client),
+            url: conf.url.clone(),
+            reference_clients,
+            max_log_range: Arc::new(AtomicU32::new(conf.max_log_range.unwrap_or_default())),
+            max_concurrent_requests: conf.max_concurrent_requests,
+            retry_policy: conf.retry_policy,
+            wait_for_confirmation: conf.wait_for_confirmation,
+            preflight_verify: conf.preflight_verify,
+            check_recipient_is_contract: conf.check_recipient_is_contract,
+            check_paused: conf.check_paused,
+            prefer_finalized_reads: conf.prefer_finalized_reads,
+            multicall_address: conf.multicall_address,
+            fee_payer: conf.fee_payer,
+            allow_energy_burn: conf.allow_energy_burn,
+            tx_expiration: conf.tx_expiration,
+            min_energy_limit: conf.min_energy_limit,
+            permission_id: conf.permission_id,
+            max_fee_fraction_of_balance: conf.max_fee_fraction_of_balance,
+            fee_limit_bump: conf.fee_limit_bump.clone(),
+            ordered_submission: conf.ordered_submission,
+            broadcast_listener: None,
+            sent_transactions: Default::default(),
+        })
+    }
+
+    /// Policy applied to calls to the node
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Whether sent transactions are awaited until confirmed
+    pub fn wait_for_confirmation(&self) -> bool {
+        self.wait_for_confirmation
+    }
+
+    /// Whether message metadata is verified against the recipient ISM before processing
+    pub fn preflight_verify(&self) -> bool {
+        self.preflight_verify
+    }
+
+    /// Whether message recipients are checked to be contracts before delivery
+    pub fn check_recipient_is_contract(&self) -> bool {
+        self.check_recipient_is_contract
+    }
+
+    /// Whether the mailbox is checked not to be paused before processing
+    pub fn check_paused(&self) -> bool {
+        self.check_paused
+    }
+
+    /// Whether contract state is read at the latest finalized block rather than the tip
+    pub fn prefer_finalized_reads(&self) -> bool {
+        self.prefer_finalized_reads
+    }
+
+    /// How long sent transactions stay valid after being built, the node's default if unset
+    pub fn tx_expiration(&self) -> Option<Duration> {
+        self.tx_expiration
+    }
+
+    /// Energy limit below which sent transactions are never capped, if any
+    pub fn min_energy_limit(&self) -> Option<u64> {
+        self.min_energy_limit
+    }
+
+    /// Permission sent transactions are signed under, the owner's if unset
+    pub fn permission_id(&self) -> Option<u32> {
+        self.permission_id
+    }
+
+    /// Largest fraction of the signer's balance a fee limit may reach, if any
+    pub fn max_fee_fraction_of_balance(&self) -> Option<f64> {
+        self.max_fee_fraction_of_balance
+    }
+
+    /// How unconfirmed transactions are reissued with a higher fee limit, if at all
+    pub fn fee_limit_bump(&self) -> Option<&FeeLimitBumpConf> {
+        self.fee_limit_bump.as_ref()
+    }
+
+    /// Whether concurrently processed messages are broadcast in nonce order
+    pub fn ordered_submission(&self) -> bool {
+        self.ordered_submission
+    }
+
+    /// Address of the Multicall3 contract batching view calls, if any
+    pub fn multicall_address(&self) -> Option<H256> {
+        self.multicall_address
+    }
+
+    /// Account delegating the energy of sent transactions to the signer, if any
+    pub fn fee_payer(&self) -> Option<H256> {
+        self.fee_payer
+    }
+
+    /// Whether the signer may burn TRX for energy not covered by the fee payer, or for
+    /// bandwidth it lacks
+    pub fn allow_energy_burn(&self) -> bool {
+        self.allow_energy_burn
+    }
+
+    /// Notifies `listener` of the transactions accepted by the node
+    pub fn with_broadcast_listener(mut self, listener: BroadcastListener) -> Self {
+        self.broadcast_listener = Some(listener);
+        self
+    }
+
+    pub(crate) fn notify_broadcast(&self, txid: H512) {
+        if let Some(listener) = &self.broadcast_listener {
+            (listener.0)(txid);
+        }
+    }
+
+    /// Remembers a transaction accepted by the node, so that it can be reissued
+    fn remember_sent(&self, tx: &Transaction) {
+        let mut sent = self.sent_transactions.lock().unwrap();
+        if sent.len() == MAX_REMEMBERED_TRANSACTIONS {
+            sent.pop_front();
+        }
+        sent.push_back(tx.clone());
+    }
+
+    fn remembered_sent(&self, txid: H256) -> Option<Transaction> {
+        self.sent_transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|tx| tx.tx_id.0 == txid.0)
+            .cloned()
+    }
+
+    /// Maximum block range of a single log query, if known
+    pub fn max_log_range(&self) -> Option<u32> {
+        match self.max_log_range.load(Ordering::Relaxed) {
+            0 => None,
+            range => Some(range),
+        }
+    }
+
+    /// Maximum number of log queries run concurrently
+    pub fn max_concurrent_requests(&self) -> u32 {
+        self.max_concurrent_requests
+    }
+
+    pub(crate) fn set_max_log_range(&self, range: u32) {
+        self.max_log_range.store(range, Ordering::Relaxed);
+    }
+
+    /// Stream of the finalized block height, yielding each time it advances. Polls the
+    /// node every `poll_interval`, and only yields the latest height, skipping the
+    /// ones finalized while the consumer lagged behind. Failed polls are retried at
+    /// the next interval.
+    pub fn finalized_block_stream(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = u32> + Send + 'static {
+        stream::unfold((self.clone(), None), move |(provider, last)| async move {
+            if last.is_some() {
+                sleep(poll_interval).await;
+            }
+            loop {
+                match provider
+                    .rpc_client
+                    .call(|client| client.get_finalized_block_number())
+                    .await
+                {
+                    Ok(height) => {
+                        let height = u32::try_from(height).unwrap_or(u32::MAX);
+                        if last.map_or(true, |last| height > last) {
+                            return Some((height, (provider, Some(height))));
+                        }
+                    }
+                    Err(err) => warn!(?err, "Failed to poll the finalized block height"),
+                }
+                sleep(poll_interval).await;
+            }
+        })
+    }
+
+    /// Calls made by contracts while executing a transaction, e.g. to find out why
+    /// a recipient's handle consumed more energy than estimated
+    #[instrument(err, skip(self))]
+    pub async fn get_internal_transactions(&self, tx_hash: H512) -> ChainResult<Vec<InternalTx>> {
+        let txid = TransactionId(H256::from_slice(&tx_hash.as_bytes()[32..]).0);
+        let internal_txs = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_internal_transactions(txid))
+            })
+            .await?;
+
+        Ok(internal_txs)
+    }
+
+    /// Chain parameters currently set by governance
+    #[instrument(err, skip(self))]
+    pub async fn get_chain_parameters(&self) -> ChainResult<ChainParameters> {
+        let params = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_typed_chain_parameters())
+            })
+            .await?;
+
+        Ok(params)
+    }
+
+    /// Cost of `energy` at the current energy price, formatted for display, e.g.
+    /// `"12.34 TRX"`
+    #[instrument(err, skip(self))]
+    pub async fn fee_display(&self, energy: u64) -> ChainResult<String> {
+        let energy_fee = self.energy_price().await?;
+        Ok(format_sun(u128::from(energy) * u128::from(energy_fee)))
+    }
+
+    /// Energy fee changes enacted by governance as (timestamp in ms, fee in sun) pairs,
+    /// oldest first. Useful to anticipate how the cost of relaying evolves.
+    #[instrument(err, skip(self))]
+    pub async fn get_energy_fee_history(&self) -> ChainResult<Vec<(u64, u64)>> {
+        let history = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_energy_fee_history())
+            })
+            .await?;
+
+        Ok(history)
+    }
+
+    /// Energy and bandwidth consumed by the latest block. A block using more energy
+    /// than its budget hints at congestion, under which fee limits can be raised.
+    #[instrument(err, skip(self))]
+    pub async fn get_block_resource_usage(&self) -> ChainResult<BlockResourceUsage> {
+        let usage = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_block_resource_usage())
+            })
+            .await?;
+
+        Ok(usage)
+    }
+
+    /// Id of the transaction that deployed the contract at `address`, `None` if there
+    /// is no such contract. Allows checking that agents point at the expected
+    /// deployment. Requires a node serving the TronGrid API.
+    #[instrument(err, skip(self))]
+    pub async fn get_contract_creation(&self, address: H256) -> ChainResult<Option<H256>> {
+        let address = TronAddress::try_from(address)?;
+        let tx_id = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_contract_creation_tx_id(address.as_ref()))
+            })
+            .await?;
+
+        Ok(tx_id.map(|tx_id| H256(tx_id.0)))
+    }
+
+    /// Confirmed events emitted by `contract` in a block, as indexed by the TronGrid
+    /// event server. Requires a node serving the TronGrid API.
+    #[instrument(err, skip(self))]
+    pub async fn get_contract_events(
+        &self,
+        contract: H160,
+        block_number: u64,
+    ) -> ChainResult<Vec<ContractEvent>> {
+        let address = TronAddress::from(contract);
+        let events = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_contract_events(address.as_ref(), block_number))
+            })
+            .await?;
+
+        Ok(events)
+    }
+
+    /// Ids of the latest transactions of the account at `address`, base58 or hex, newest
+    /// first and at most `limit` of them. Allows auditing what the relayer submits.
+    /// Empty for an account that doesn't exist on chain. Requires a node serving the
+    /// TronGrid API.
+    #[instrument(err, skip(self))]
+    pub async fn get_account_transactions(
+        &self,
+        address: &str,
+        limit: usize,
+    ) -> ChainResult<Vec<H512>> {
+        let address = TronAddress::parse_any(address)?;
+        let tx_ids = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_account_transactions(address.as_ref(), limit))
+            })
+            .await?;
+
+        Ok(tx_ids
+            .into_iter()
+            .map(|tx_id| H512::from(H256(tx_id.0)))
+            .collect())
+    }
+
+    /// Permissions of the account at `address`, base58 or hex. A signer whose account
+    /// requires several signatures ([`AccountPermissions::is_multisig`]) can't send
+    /// transactions on its own, which the node only reports as a signature weight
+    /// failure.
+    #[instrument(err, skip(self))]
+    pub async fn get_account_permissions(&self, address: &str) -> ChainResult<AccountPermissions> {
+        let address = TronAddress::parse_any(address)?;
+        let permissions = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_account_permissions(address.as_ref()))
+            })
+            .await?;
+
+        Ok(permissions)
+    }
+
+    /// Implementation behind the EIP-1967 proxy at `proxy`, `None` if its
+    /// implementation slot is empty. Allows detecting unexpected upgrades.
+    #[instrument(err, skip(self))]
+    pub async fn read_proxy_implementation(&self, proxy: H256) -> ChainResult<Option<H256>> {
+        let slot = self
+            .retry_policy
+            .retry(|| async {
+                Ok(self
+                    .eth_client
+                    .get_storage_at(H160::from(proxy), EIP1967_IMPLEMENTATION_SLOT, None)
+                    .await?)
+            })
+            .await?;
+
+        // The implementation address is stored right-aligned in the slot
+        let implementation = H256(slot.0);
+        Ok((!implementation.is_zero()).then_some(implementation))
+    }
+
+    /// ABI stored on chain for the contract at `contract`, as the node returns it
+    /// (`{"entrys": [...]}`). Allows building calls without bindings, or checking that
+    /// a deployment exposes the expected functions.
+    #[instrument(err, skip(self))]
+    pub async fn get_contract_abi(&self, contract: H256) -> ChainResult<serde_json::Value> {
+        let address = TronAddress::try_from(contract)?;
+        self.retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_contract_abi(address.as_ref()))
+            })
+            .await?
+            .ok_or_else(|| HyperlaneTronError::ContractNotFound(contract).into())
+    }
+
+    /// Gathers the state of the node, and of the signer if any, for bug reports
+    #[instrument(err, skip(self))]
+    pub async fn diagnostics(&self, signer: Option<&Signer>) -> ChainResult<Diagnostics> {
+        let node_version = self
+            .retry_policy
+            .retry(|| self.rpc_client.call(|client| client.get_node_version()))
+            .await?;
+        let latest_block = self
+            .retry_policy
+            .retry(|| self.rpc_client.call(|client| client.get_latest_block()))
+            .await?
+            .block_number();
+        let finalized_block = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_finalized_block_number())
+            })
+            .await?;
+        let energy_fee = self
+            .retry_policy
+            .retry(|| self.rpc_client.call(|client| client.get_energy_fee()))
+            .await?;
+
+        let signer = match signer {
+            Some(signer) => Some(self.signer_diagnostics(signer).await?),
+            None => None,
+        };
+
+        Ok(Diagnostics {
+            node_version,
+            latest_block,
+            finalized_block,
+            energy_fee,
+            signer,
+        })
+    }
+
+    async fn signer_diagnostics(&self, signer: &Signer) -> ChainResult<SignerDiagnostics> {
+        let address = &signer.0.address();
+
+        let balance = match self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_account_balance(address))
+            })
+            .await
+        {
+            Ok(balance) => Some(balance),
+            Err(HyperlaneTronError::ClientError(heliosphere::Error::AccountNotFound)) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let resources = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_account_resources(address))
+            })
+            .await?;
+
+        Ok(SignerDiagnostics {
+            address: address.as_base58(),
+            balance,
+            resources,
+        })
+    }
+
+    /// Transfers `amount` sun from the signer to `to`, e.g. to fund relayer accounts.
+    /// Transfers consume bandwidth only, so the outcome reports no gas.
+    #[instrument(err, skip(self, signer), fields(from = signer.address()))]
+    pub async fn transfer_trx(
+        &self,
+        to: H256,
+        amount: u64,
+        signer: &Signer,
+    ) -> ChainResult<TxOutcome> {
+        let (from, to) = (&signer.0.address(), &TronAddress::try_from(to)?);
+        let tx = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.create_trx_transfer(from, to.as_ref(), amount))
+            })
+            .await?;
+
+        let (transaction_id, executed) = self.sign_and_broadcast(tx, signer).await?;
+        Ok(TxOutcome {
+            transaction_id,
+            executed,
+            gas_used: U256::zero(),
+            gas_price: FixedPointNumber::zero(),
+        })
+    }
+
+    /// Sends again a transaction sent through this provider that expired before being
+    /// included, or that failed, e.g. ran out of energy. The new transaction makes
+    /// the same call or transfer with the same fee limit.
+    ///
+    /// Tron transactions have no nonce, so a transaction can't be canceled or replaced
+    /// while it may still be included: it is only reissued once it failed, or expired
+    /// without being included.
+    #[instrument(err, skip(self, signer))]
+    pub async fn reissue(&self, txid: H256, signer: &Signer) -> ChainResult<TxOutcome> {
+        let not_reissuable = |reason| HyperlaneTronError::NotReissuable { txid, reason };
+        let tx_id = TransactionId(txid.0);
+        let status = self
+            .retry_policy
+            .retry(|| TronRpc::get_tx_status(self.rpc_client.as_ref(), tx_id))
+            .await?;
+
+        let original = match status {
+            TxStatus::Solidified { contract_ret } => {
+                if TronTxResult::from(contract_ret.as_deref().unwrap_or("empty ret"))
+                    == TronTxResult::Success
+                {
+                    return Err(not_reissuable("it was executed").into());
+                }
+                match self.remembered_sent(txid) {
+                    Some(tx) => Some(tx),
+                    None => {
+                        self.retry_policy
+                            .retry(|| {
+                                self.rpc_client
+                                    .call(|client| client.get_transaction_by_id(tx_id))
+                            })
+                            .await?
+                    }
+                }
+            }
+            TxStatus::InBlock => return Err(not_reissuable("it is being confirmed").into()),
+            TxStatus::Pending => {
+                return Err(not_reissuable("it is still pending and can't be canceled").into())
+            }
+            TxStatus::Unknown => {
+                let original = self.remembered_sent(txid);
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                if let Some(tx) = &original {
+                    if now <= Duration::from_millis(tx.raw_data.expiration) + DROPPED_TX_GRACE {
+                        return Err(
+                            not_reissuable("it may still be included until it expires").into()
+                        );
+                    }
+                }
+                original
+            }
+        };
+        let original =
+            original.ok_or_else(|| HyperlaneTronError::TransactionNotFound(txid.into()))?;
+
+        let tx = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.rebuild_transaction(&original))
+            })
+            .await?;
+        // Fetched before broadcasting, so that a failure can't hide a sent transaction
+        let gas_price = self.gas_price().await?;
+        let (transaction_id, executed) = self.sign_and_broadcast(tx, signer).await?;
+        let gas_used = match self.wait_for_confirmation {
+            true => self.gas_used(transaction_id).await,
+            false => U256::zero(),
+        };
+        warn!(?txid, reissued = ?transaction_id, "Reissued transaction");
+
+        Ok(TxOutcome {
+            transaction_id,
+            executed,
+            gas_used,
+            gas_price,
+        })
+    }
+
+    /// Price of a unit of energy in sun, the energy fee, from the cached chain
+    /// parameters
+    pub(crate) async fn energy_price(&self) -> ChainResult<u64> {
+        let energy_price = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_energy_fee_cached())
+            })
+            .await?;
+
+        Ok(energy_price)
+    }
+
+    /// Price of gas in sun, see [`sun_to_fixed_point`]
+    pub(crate) async fn gas_price(&self) -> ChainResult<FixedPointNumber> {
+        sun_to_fixed_point(self.energy_price().await?)
+    }
+
+    /// Gas used by a transaction, converted from the energy it consumed. Zero if it
+    /// isn't in a block, or if its receipt can't be fetched, so that a sent transaction
+    /// isn't reported as failed over its accounting.
+    pub(crate) async fn gas_used(&self, txid: H512) -> U256 {
+        let txid = TransactionId(H256::from_slice(&txid.as_bytes()[32..]).0);
+        let energy = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_tx_energy_used(txid))
+            })
+            .await;
+
+        match energy {
+            Ok(energy) => energy_to_gas(energy),
+            Err(err) => {
+                warn!(
+                    ?txid,
+                    ?err,
+                    "Failed to fetch the energy used by a transaction"
+                );
+                U256::zero()
+            }
+        }
+    }
+
+    /// Signs and broadcasts a transaction, then awaits its execution if configured to,
+    /// returning its id and whether it was executed successfully
+    async fn sign_and_broadcast(
+        &self,
+        mut tx: Transaction,
+        signer: &Signer,
+    ) -> ChainResult<(H512, bool)> {
+        if let Some(tx_expiration) = self.tx_expiration {
+            set_expiration(&mut tx, tx_expiration)?;
+        }
+        signer
+            .0
+            .sign_transaction(&mut tx)
+            .map_err(HyperlaneTronError::from)?;
+        let txid = self.broadcast(&tx).await?;
+        self.notify_broadcast(H256::from(txid.0).into());
+
+        let executed = self.wait_for_confirmation
+            && self
+                .rpc_client
+                .await_tx_result(txid, Some(tx.raw_data.expiration))
+                .await
+                .is_ok_and(|result| result == TronTxResult::Success);
+
+        Ok((H256::from(txid.0).into(), executed))
+    }
+
+    /// Broadcasts a signed transaction, remembering it once accepted so that it can be
+    /// reissued. Only transport errors are retried, and the node rejecting the
+    /// transaction as a duplicate means that an earlier attempt reached it.
+    pub(crate) async fn broadcast(
+        &self,
+        tx: &Transaction,
+    ) -> Result<TransactionId, HyperlaneTronError> {
+        let broadcast = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.broadcast_transaction(tx))
+            })
+            .await;
+        let txid = match broadcast {
+            Ok(txid) => txid,
+            Err(err) if is_duplicate_error(&err) => {
+                warn!(txid = ?tx.tx_id, "Transaction already accepted by the node");
+                tx.tx_id
+            }
+            Err(err) => return Err(err),
+        };
+        self.remember_sent(tx);
+        Ok(txid)
+    }
+
+    /// Compares the finalized blocks of the node with those of the reference nodes, to
+    /// catch a node that is forked or serves wrong data. Blocks are compared at the
+    /// lowest finalized height among the nodes, so that nodes merely lagging behind
+    /// agree. Returns `None` if all nodes agree, or if there are no reference nodes.
+    #[instrument(err, skip(self))]
+    pub async fn detect_fork(&self) -> ChainResult<Option<ForkReport>> {
+        if self.reference_clients.is_empty() {
+            return Ok(None);
+        }
+        let nodes = std::iter::once((&self.url, &self.rpc_client))
+            .chain(
+                self.reference_clients
+                    .iter()
+                    .map(|(url, client)| (url, client)),
+            )
+            .collect::<Vec<_>>();
+
+        let finalized = try_join_all(nodes.iter().map(|(_, client)| {
+            self.retry_policy
+                .retry(move || client.call(|client| client.get_finalized_block_number()))
+        }))
+        .await?;
+        let block_number = finalized.into_iter().min().unwrap_or_default();
+
+        let blocks = try_join_all(nodes.iter().map(|(_, client)| {
+            self.retry_policy
+                .retry(move || client.call(move |client| client.get_block_by_number(block_number)))
+        }))
+        .await?;
+        let hashes = nodes
+            .iter()
+            .zip(blocks)
+            .map(|((url, _), block)| ((*url).clone(), block.map(|block| H256(block.block_id.0))))
+            .collect::<Vec<_>>();
+
+        if hashes.iter().all(|(_, hash)| *hash == hashes[0].1) {
+            return Ok(None);
+        }
+        warn!(block_number, ?hashes, "Nodes disagree on a finalized block");
+        Ok(Some(ForkReport {
+            block_number,
+            hashes,
+        }))
+    }
+
+    /// Checks that the node can resolve `reorg_period`. Tags resolve to the latest
+    /// solidified block, which private nodes may not serve, so checking at startup
+    /// turns failures of every later query into a single clear error. Failures to
+    /// reach the node are returned as they are, and don't mean the endpoint is missing.
+    #[instrument(err, skip(self))]
+    pub async fn ensure_reorg_period_supported(
+        &self,
+        reorg_period: &ReorgPeriod,
+    ) -> ChainResult<()> {
+        let ReorgPeriod::Tag(tag) = reorg_period else {
+            return Ok(());
+        };
+
+        let finalized = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_finalized_block_number_if_served())
+            })
+            .await?;
+        if finalized.is_none() {
+            return Err(HyperlaneTronError::FinalityUnsupported {
+                tag: tag.clone(),
+                reason: "/walletsolidity/getblock not found".to_owned(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Estimated time left until the transaction `txid` is solidified, zero if it
+    /// already is. Nodes don't expose the position of a transaction in their pending
+    /// pool, so a pending or not yet propagated transaction is assumed to be included
+    /// in the next block. Block time is averaged over recent blocks, or assumed to be
+    /// Tron's 3s target if it can't be measured.
+    #[instrument(err, skip(self))]
+    pub async fn estimate_confirmation_time(&self, txid: H256) -> ChainResult<Duration> {
+        let tx_id = TransactionId(txid.0);
+        let status = self
+            .retry_policy
+            .retry(|| TronRpc::get_tx_status(self.rpc_client.as_ref(), tx_id))
+            .await?;
+        if matches!(status, TxStatus::Solidified { .. }) {
+            return Ok(Duration::ZERO);
+        }
+
+        let latest = self
+            .retry_policy
+            .retry(|| self.rpc_client.call(|client| client.get_latest_block()))
+            .await?;
+        let solidification_blocks = u64::from(TRON_SOLIDIFICATION_BLOCKS);
+        let blocks_left = match status {
+            TxStatus::InBlock => {
+                let included = self
+                    .retry_policy
+                    .retry(|| {
+                        self.rpc_client
+                            .call(|client| client.get_tx_block_number(tx_id))
+                    })
+                    .await?;
+                match included {
+                    Some(included) => (included + solidification_blocks)
+                        .saturating_sub(latest.block_number())
+                        .max(1),
+                    None => solidification_blocks,
+                }
+            }
+            _ => solidification_blocks + 1,
+        };
+
+        let block_time = self.average_block_time(&latest).await?;
+        Ok(block_time * blocks_left as u32)
+    }
+
+    /// Average interval between the blocks preceding `latest`, or the default block
+    /// time if the node doesn't serve them
+    async fn average_block_time(&self, latest: &Block) -> ChainResult<Duration> {
+        let number = latest.block_number().saturating_sub(BLOCK_TIME_SAMPLE);
+        let older = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_block_by_number(number))
+            })
+            .await?;
+
+        let block_time = older.and_then(|older| {
+            let blocks = latest.block_number().checked_sub(older.block_number())?;
+            let elapsed = latest
+                .block_header
+                .raw_data
+                .timestamp
+                .checked_sub(older.block_header.raw_data.timestamp)?;
+            (blocks > 0 && elapsed > 0).then(|| Duration::from_millis(elapsed / blocks))
+        });
+        Ok(block_time.unwrap_or(DEFAULT_BLOCK_TIME))
+    }
+
+    /// Whether the latest block of the node was produced less than `within` ago. A
+    /// stall means that the witnesses stopped producing blocks, or that the node fell
+    /// out of sync, and nothing sent to it will be confirmed until it recovers.
+    #[instrument(err, skip(self))]
+    pub async fn is_producing_blocks(&self, within: Duration) -> ChainResult<bool> {
+        let latest = self
+            .retry_policy
+            .retry(|| self.rpc_client.call(|client| client.get_latest_block()))
+            .await?;
+
+        let produced_at = Duration::from_millis(latest.block_header.raw_data.timestamp);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        // A block timestamped ahead of the local clock is as fresh as it gets
+        let age = now.saturating_sub(produced_at);
+        if age > within {
+            warn!(
+                block_number = latest.block_number(),
+                ?age,
+                "Latest block of the node is stale"
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Snapshot of an indexer that indexed up to `last_indexed_block`, see
+    /// [`Self::resume_block`]
+    #[instrument(err, skip(self))]
+    pub async fn cursor_snapshot(&self, last_indexed_block: u32) -> ChainResult<CursorSnapshot> {
+        let block = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_block_by_number(last_indexed_block.into()))
+            })
+            .await?
+            .ok_or_else(|| {
+                HyperlaneProviderError::CouldNotFindBlockByHeight(last_indexed_block.into())
+            })?;
+
+        Ok(CursorSnapshot {
+            last_indexed_block,
+            last_block_hash: H256(block.block_id.0),
+        })
+    }
+
+    /// Block from which to resume indexing from a snapshot: the next one if the last
+    /// indexed block is still part of the chain, otherwise one far enough back to
+    /// re-index the blocks that may have been reorged out along with it
+    #[instrument(err, ret, skip(self))]
+    pub async fn resume_block(&self, snapshot: &CursorSnapshot) -> ChainResult<u32> {
+        let block = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_block_by_number(snapshot.last_indexed_block.into()))
+            })
+            .await?;
+
+        let hash = block.map(|block| H256(block.block_id.0));
+        if hash == Some(snapshot.last_block_hash) {
+            return Ok(snapshot.last_indexed_block + 1);
+        }
+        let safe_block = snapshot
+            .last_indexed_block
+            .saturating_sub(TRON_SOLIDIFICATION_BLOCKS);
+        warn!(
+            ?snapshot,
+            ?hash,
+            safe_block,
+            "Last indexed block was reorged out, re-indexing from a safe block"
+        );
+        Ok(safe_block)
+    }
+
+    /// Block with the given hash, `None` if the node doesn't know it, e.g. because
+    /// it was reorged out
+    #[instrument(err, skip(self))]
+    pub async fn get_block_by_id(&self, hash: H256) -> ChainResult<Option<BlockInfo>> {
+        let block = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_block_by_id(BlockId(hash.0)))
+            })
+            .await?;
+
+        Ok(block.as_ref().map(block_info))
+    }
+
+    /// Awaits the confirmation of many transactions concurrently, returning whether
+    /// each of them was executed successfully. Transactions that fail or are not
+    /// confirmed within [`TX_CONFIRMATION_TIMEOUT`] are reported as not executed.
+    #[instrument(skip(self))]
+    pub async fn await_confirmations(&self, txids: &[H256]) -> Vec<(H256, bool)> {
+        self.await_confirmations_with_timeout(txids, TX_CONFIRMATION_TIMEOUT)
+            .await
+    }
+
+    /// Awaits the outcome of a transaction already broadcast, e.g. one whose
+    /// confirmation was interrupted. Transactions that fail or are not confirmed
+    /// within [`TX_CONFIRMATION_TIMEOUT`] are reported as not executed.
+    #[instrument(err, skip(self))]
+    pub async fn await_outcome(&self, txid: H512) -> ChainResult<TxOutcome> {
+        self.await_outcome_with_timeout(txid, TX_CONFIRMATION_TIMEOUT)
+            .await
+    }
+
+    pub(crate) async fn await_outcome_with_timeout(
+        &self,
+        txid: H512,
+        timeout: Duration,
+    ) -> ChainResult<TxOutcome> {
+        let txid_h256 = H256::from_slice(&txid.as_bytes()[32..]);
+        let (_, executed) = self
+            .await_confirmations_with_timeout(&[txid_h256], timeout)
+            .await
+            .remove(0);
+        let gas_used = match executed {
+            true => self.gas_used(txid).await,
+            false => U256::zero(),
+        };
+
+        Ok(TxOutcome {
+            transaction_id: txid,
+            executed,
+            gas_used,
+            gas_price: self.gas_price().await?,
         })
     }
+
+    pub(crate) async fn await_confirmations_with_timeout(
+        &self,
+        txids: &[H256],
+        timeout: Duration,
+    ) -> Vec<(H256, bool)> {
+        join_all(txids.iter().map(|txid| async move {
+            let confirmation = self
+                .rpc_client
+                .await_confirmation(TransactionId(txid.0), None);
+            let executed = matches!(tokio::time::timeout(timeout, confirmation).await, Ok(Ok(_)));
+            (*txid, executed)
+        }))
+        .await
+    }
 }
 
 impl HyperlaneChain for TronProvider {
@@ -41,19 +1074,49 @@ impl HyperlaneChain for TronProvider {
     }
 
     fn provider(&self) -> Box<dyn HyperlaneProvider> {
-        Box::new(TronProvider {
-            domain: self.domain.clone(),
-            eth_client: self.eth_client.clone(),
-            rpc_client: self.rpc_client.clone(),
-        })
+        Box::new(self.clone())
     }
 }
 
 #[async_trait]
 impl HyperlaneProvider for TronProvider {
+    /// Transport errors are retried, but a transaction unknown to the node is
+    /// reported right away
     #[instrument(err, skip(self))]
     async fn get_txn_by_hash(&self, hash: &H512) -> ChainResult<TxnInfo> {
-        todo!()
+        let txid = EthersH256::from_slice(&hash.as_bytes()[32..]);
+
+        let txn = self
+            .retry_policy
+            .retry(|| async { Ok(self.eth_client.get_transaction(txid).await?) })
+            .await?
+            .ok_or(HyperlaneProviderError::CouldNotFindTransactionByHash(*hash))?;
+
+        let receipt = self
+            .retry_policy
+            .retry(|| async { Ok(self.eth_client.get_transaction_receipt(txid).await?) })
+            .await?
+            .map(|r| -> Result<_, HyperlaneProviderError> {
+                Ok(TxnReceiptInfo {
+                    gas_used: r.gas_used.ok_or(HyperlaneProviderError::NoGasUsed)?.into(),
+                    cumulative_gas_used: r.cumulative_gas_used.into(),
+                    effective_gas_price: r.effective_gas_price.map(Into::into),
+                })
+            })
+            .transpose()?;
+
+        Ok(TxnInfo {
+            hash: *hash,
+            gas_limit: txn.gas.into(),
+            max_priority_fee_per_gas: None,
+            max_fee_per_gas: None,
+            gas_price: txn.gas_price.map(Into::into),
+            nonce: txn.nonce.as_u64(),
+            sender: TronAddress::from(txn.from).into(),
+            recipient: txn.to.map(|to| TronAddress::from(to).into()),
+            receipt,
+            raw_input_data: Some(txn.input.to_vec()),
+        })
     }
 
     #[instrument(err, skip(self))]
@@ -68,23 +1131,1057 @@ impl HyperlaneProvider for TronProvider {
 
     #[instrument(err, skip(self))]
     async fn get_balance(&self, address: String) -> ChainResult<U256> {
-        let address = &address.parse().map_err(Into::<HyperlaneTronError>::into)?;
+        let address = TronAddress::parse_any(&address)?;
+        let address = address.as_ref();
 
         let balance = self
-            .rpc_client
-            .get_account_balance(address)
-            .await
-            .map_err(Into::<HyperlaneTronError>::into)?
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_account_balance(address))
+            })
+            .await?
             .into();
 
         Ok(balance)
     }
 
+    #[instrument(err, skip(self))]
     async fn get_chain_metrics(&self) -> ChainResult<Option<ChainInfo>> {
-        todo!()
+        let latest = self
+            .retry_policy
+            .retry(|| self.rpc_client.call(|client| client.get_latest_block()))
+            .await?;
+
+        // Tron has no EIP-1559 style base fee
+        Ok(Some(ChainInfo {
+            latest_block: block_info(&latest),
+            min_gas_price: None,
+        }))
     }
 
+    #[instrument(err, skip(self))]
     async fn get_block_by_height(&self, height: u64) -> ChainResult<BlockInfo> {
-        todo!()
+        let block = self
+            .retry_policy
+            .retry(|| {
+                self.rpc_client
+                    .call(|client| client.get_block_by_number(height))
+            })
+            .await?
+            .ok_or(HyperlaneProviderError::CouldNotFindBlockByHeight(height))?;
+
+        if block.block_number() != height {
+            return Err(HyperlaneProviderError::IncorrectBlockByHeight(
+                height,
+                block.block_number(),
+            )
+            .into());
+        }
+
+        Ok(block_info(&block))
+    }
+}
+
+fn block_info(block: &Block) -> BlockInfo {
+    BlockInfo {
+        hash: H256(block.block_id.0),
+        timestamp: block.block_header.raw_data.timestamp / 1000,
+        number: block.block_number(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use futures::StreamExt;
+
+    use crate::test_utils::{self, MockTronNode};
+    use crate::PermissionKey;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_json_rpc_calls_share_the_circuit_breaker() {
+        let conf = ConnectionConf {
+            circuit_breaker: crate::CircuitBreakerConf {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            },
+            // Nothing listens on port 1
+            ..test_utils::conf(Url::parse("http://127.0.0.1:1").unwrap())
+        };
+        let provider = test_utils::provider(conf);
+
+        for _ in 0..2 {
+            let err =
+                HyperlaneTronError::from(provider.eth_client.get_block_number().await.unwrap_err());
+            assert!(err.is_transient());
+        }
+
+        // Both the Tron API and the JSON-RPC calls are short-circuited
+        let res = provider
+            .rpc_client
+            .call(|client| client.get_latest_block())
+            .await;
+        assert!(matches!(res, Err(HyperlaneTronError::CircuitOpen { .. })));
+        let err =
+            HyperlaneTronError::from(provider.eth_client.get_block_number().await.unwrap_err());
+        assert!(err.to_string().contains("short-circuited"), "{err}");
+        assert!(!err.is_transient());
+    }
+
+    fn tx_info(txid: &str, contract_ret: &str) -> serde_json::Value {
+        let mut tx = test_utils::transaction(txid);
+        tx["ret"] = serde_json::json!([{ "contractRet": contract_ret }]);
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_get_txn_by_hash_retries_transient_failure() {
+        let hash = EthersH256::repeat_byte(0xab);
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let calls = calls.clone();
+            MockTronNode::start(move |_, body| {
+                let mut calls = calls.lock().unwrap();
+                calls.push(body["method"].as_str().unwrap().to_owned());
+                // The first call fails in transport, with a response that isn't JSON-RPC
+                if calls.len() == 1 {
+                    return serde_json::json!("502 Bad Gateway");
+                }
+                let result = match body["method"].as_str().unwrap() {
+                    "eth_getTransactionByHash" => {
+                        let mut tx =
+                            test_utils::json_rpc_transaction(&format!("{hash:#x}"), "0x12345678");
+                        tx["to"] = format!("{:#x}", H160::from_low_u64_be(0xcc)).into();
+                        tx
+                    }
+                    "eth_getTransactionReceipt" => {
+                        let mut receipt =
+                            test_utils::json_rpc_receipt(&format!("{hash:#x}"), vec![]);
+                        receipt["to"] = format!("{:#x}", H160::from_low_u64_be(0xcc)).into();
+                        receipt["gasUsed"] = "0xfb1d".into();
+                        receipt
+                    }
+                    method => panic!("unexpected call to {method}"),
+                };
+                test_utils::json_rpc_result(&body, result)
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            retry_policy: RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                ..Default::default()
+            },
+            ..node.conf()
+        });
+
+        let txn = provider
+            .get_txn_by_hash(&H256::from(hash.0).into())
+            .await
+            .unwrap();
+
+        assert_eq!(txn.gas_limit, U256::from(100_000));
+        assert_eq!(txn.gas_price, Some(U256::from(210)));
+        assert_eq!(txn.sender, H256::from_low_u64_be(0xaa));
+        assert_eq!(txn.recipient, Some(H256::from_low_u64_be(0xcc)));
+        assert_eq!(txn.raw_input_data, Some(vec![0x12, 0x34, 0x56, 0x78]));
+        assert_eq!(txn.receipt.unwrap().gas_used, U256::from(64_285));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "eth_getTransactionByHash",
+                "eth_getTransactionByHash",
+                "eth_getTransactionReceipt"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_txn_by_hash_not_found() {
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let node = {
+            let calls = calls.clone();
+            MockTronNode::start(move |_, body| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                test_utils::json_rpc_result(&body, serde_json::Value::Null)
+            })
+            .await
+        };
+        let hash = H256::repeat_byte(0xab).into();
+
+        let err = node.provider().get_txn_by_hash(&hash).await.unwrap_err();
+
+        assert!(
+            err.to_string().contains("Could not find transaction"),
+            "{err}"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics() {
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let signer_hex = signer.0.address().as_hex();
+
+        let node = MockTronNode::start(move |path, body| match path {
+            "/wallet/getnodeinfo" => serde_json::json!({
+                "activeConnectCount": 8,
+                "configNodeInfo": {"codeVersion": "4.7.5", "p2pVersion": "11111"},
+                "block": "Num:64000000,ID:0000000003d09000"
+            }),
+            "/wallet/getnowblock" => test_utils::block(64_000_000),
+            "/walletsolidity/getblock" => test_utils::block(63_999_981),
+            "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+            "/wallet/getaccount" => {
+                assert_eq!(body["address"], signer_hex);
+                serde_json::json!({"address": signer_hex, "balance": 150_000_000})
+            }
+            "/wallet/getaccountresource" => serde_json::json!({
+                "freeNetLimit": 600,
+                "freeNetUsed": 267,
+                "EnergyLimit": 100_000,
+                "EnergyUsed": 42_000
+            }),
+            _ => panic!("unexpected request to {path}"),
+        })
+        .await;
+
+        let diagnostics = node.provider().diagnostics(Some(&signer)).await.unwrap();
+
+        assert_eq!(diagnostics.node_version, "4.7.5");
+        assert_eq!(diagnostics.latest_block, 64_000_000);
+        assert_eq!(diagnostics.finalized_block, 63_999_981);
+        assert_eq!(diagnostics.energy_fee, 210);
+        let signer_diagnostics = diagnostics.signer.unwrap();
+        assert_eq!(signer_diagnostics.address, signer.address());
+        assert_eq!(signer_diagnostics.balance, Some(150_000_000));
+        assert_eq!(signer_diagnostics.resources.free_net_used, 267);
+        assert_eq!(signer_diagnostics.resources.energy_limit, 100_000);
+        assert_eq!(signer_diagnostics.resources.energy_used, 42_000);
+    }
+
+    #[tokio::test]
+    async fn test_fee_display() {
+        let node = MockTronNode::start(|path, _| match path {
+            "/wallet/getchainparameters" => test_utils::chain_parameters(420),
+            _ => panic!("unexpected request to {path}"),
+        })
+        .await;
+        let provider = node.provider();
+
+        assert_eq!(provider.fee_display(0).await.unwrap(), "0 TRX");
+        assert_eq!(provider.fee_display(29_381).await.unwrap(), "12.34002 TRX");
+        assert_eq!(provider.fee_display(100_000).await.unwrap(), "42 TRX");
+    }
+
+    #[tokio::test]
+    async fn test_finalized_block_stream() {
+        // Heights reported by successive polls, `None` for a failed poll
+        let heights = [Some(10), Some(10), Some(11), None, Some(13), Some(12)];
+        let polls = Arc::new(AtomicU32::new(0));
+
+        let node = {
+            let polls = polls.clone();
+            MockTronNode::start(move |path, _| {
+                assert_eq!(path, "/walletsolidity/getblock");
+                let poll = polls.fetch_add(1, Ordering::SeqCst) as usize;
+                match heights[poll.min(heights.len() - 1)] {
+                    Some(height) => test_utils::block(height),
+                    None => serde_json::json!("502 Bad Gateway"),
+                }
+            })
+            .await
+        };
+
+        let stream = node
+            .provider()
+            .finalized_block_stream(Duration::from_millis(1));
+        let yielded = stream.take(3).collect::<Vec<_>>().await;
+
+        assert_eq!(yielded, vec![10, 11, 13]);
+        assert_eq!(polls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_get_internal_transactions() {
+        let tx_hash = H512::from(H256::repeat_byte(0xab));
+        let node = MockTronNode::start(move |path, body| {
+            assert_eq!(path, "/wallet/gettransactioninfobyid");
+            assert_eq!(body["value"], format!("{:#x}", H256::repeat_byte(0xab)));
+            serde_json::json!({
+                "id": "ab".repeat(32),
+                "fee": 8_345_210,
+                "blockNumber": 64_851_234,
+                "blockTimeStamp": 1_722_858_393_000u64,
+                "contractResult": [""],
+                "contract_address": "41000000000000000000000000000000000000000a",
+                "receipt": {"energy_usage_total": 39_739, "net_usage": 345, "result": "SUCCESS"},
+                "internal_transactions": [
+                    {
+                        "hash": "cd".repeat(32),
+                        "caller_address": "41000000000000000000000000000000000000000a",
+                        "transferTo_address": "41000000000000000000000000000000000000000b",
+                        "callValueInfo": [{}],
+                        "note": "63616c6c"
+                    },
+                    {
+                        "hash": "ef".repeat(32),
+                        "caller_address": "41000000000000000000000000000000000000000b",
+                        "transferTo_address": "41000000000000000000000000000000000000000c",
+                        "callValueInfo": [{"callValue": 1_000_000}, {"callValue": 5, "tokenId": "1002000"}],
+                        "note": "63616c6c",
+                        "rejected": true
+                    }
+                ]
+            })
+        })
+        .await;
+
+        let internal_txs = node
+            .provider()
+            .get_internal_transactions(tx_hash)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            internal_txs,
+            vec![
+                InternalTx {
+                    caller: H256::from_low_u64_be(0xa),
+                    callee: H256::from_low_u64_be(0xb),
+                    value: 0,
+                    kind: "call".to_owned(),
+                    rejected: false,
+                },
+                InternalTx {
+                    caller: H256::from_low_u64_be(0xb),
+                    callee: H256::from_low_u64_be(0xc),
+                    value: 1_000_000,
+                    kind: "call".to_owned(),
+                    rejected: true,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_proxy_implementation() {
+        let implementation = H256::from_low_u64_be(0x1234);
+
+        let node = MockTronNode::start(move |_, body| {
+            assert_eq!(body["method"], "eth_getStorageAt");
+            let result = match body["params"][0].as_str().unwrap() {
+                "0x00000000000000000000000000000000000000aa" => {
+                    assert_eq!(
+                        body["params"][1],
+                        format!("{EIP1967_IMPLEMENTATION_SLOT:#x}")
+                    );
+                    format!("{implementation:#x}")
+                }
+                _ => format!("{:#x}", H256::zero()),
+            };
+            test_utils::json_rpc_result(&body, result)
+        })
+        .await;
+        let provider = node.provider();
+
+        assert_eq!(
+            provider
+                .read_proxy_implementation(H256::from_low_u64_be(0xaa))
+                .await
+                .unwrap(),
+            Some(implementation)
+        );
+        // Not a proxy
+        assert_eq!(
+            provider
+                .read_proxy_implementation(H256::from_low_u64_be(0xbb))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            U256::from_big_endian(EIP1967_IMPLEMENTATION_SLOT.as_bytes()) + 1,
+            U256::from(ethers::utils::keccak256("eip1967.proxy.implementation"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_contract_creation() {
+        let tx_id = "3e7a3c3a1c7f8c3e4a1b6d0f5e2c9b8a7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b2a";
+        let contract = H256::from_low_u64_be(0xaa);
+        let contract_base58 = TronAddress::try_from(contract)
+            .unwrap()
+            .as_ref()
+            .as_base58();
+
+        let node = MockTronNode::start(move |path, _| {
+            assert!(path.starts_with(&format!("/v1/contracts/{contract_base58}/transactions?")));
+            let mut tx = test_utils::transaction(tx_id);
+            tx["raw_data"]["contract"] = serde_json::json!([{
+                "parameter": {"value": {}, "type_url": "type.googleapis.com/protocol.CreateSmartContract"},
+                "type": "CreateSmartContract"
+            }]);
+            serde_json::json!({"data": [tx], "success": true})
+        })
+        .await;
+
+        let creation = node
+            .provider()
+            .get_contract_creation(contract)
+            .await
+            .unwrap();
+
+        assert_eq!(creation, Some(tx_id.parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_get_contract_events() {
+        let contract = H160::from_low_u64_be(0xaa);
+        let contract_base58 = TronAddress::from(contract).as_ref().as_base58();
+        let event = |index: u32| {
+            serde_json::json!({
+                "transaction_id": "3e7a3c3a1c7f8c3e4a1b6d0f5e2c9b8a7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b2a",
+                "block_number": 7,
+                "block_timestamp": 1722858393000u64,
+                "event_index": index,
+                "event_name": "Dispatch",
+                "result": {"0": "1"}
+            })
+        };
+
+        let node = MockTronNode::start(move |path, _| {
+            // Queried by the base58 address, as the event server expects
+            assert!(path.starts_with(&format!("/v1/contracts/{contract_base58}/events?")));
+            assert!(path.contains("block_number=7"));
+            if path.contains("fingerprint=next") {
+                serde_json::json!({"data": [event(1)], "meta": {}})
+            } else {
+                serde_json::json!({"data": [event(0)], "meta": {"fingerprint": "next"}})
+            }
+        })
+        .await;
+
+        let events = node
+            .provider()
+            .get_contract_events(contract, 7)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events.iter().map(|e| e.event_index).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert_eq!(events[0].event_name, "Dispatch");
+    }
+
+    #[tokio::test]
+    async fn test_is_producing_blocks() {
+        let node_with_latest_block_at = |timestamp: SystemTime| {
+            let timestamp = timestamp.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+            MockTronNode::start(move |path, _| {
+                assert_eq!(path, "/wallet/getnowblock");
+                let mut block = test_utils::block(100);
+                block["block_header"]["raw_data"]["timestamp"] = timestamp.into();
+                block
+            })
+        };
+        let within = Duration::from_secs(30);
+
+        let fresh = node_with_latest_block_at(SystemTime::now() - Duration::from_secs(3)).await;
+        assert!(fresh.provider().is_producing_blocks(within).await.unwrap());
+
+        let stale = node_with_latest_block_at(SystemTime::now() - Duration::from_secs(600)).await;
+        assert!(!stale.provider().is_producing_blocks(within).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_confirmation_time() {
+        let in_block = H256::from_low_u64_be(1);
+        let pending = H256::from_low_u64_be(2);
+        let solidified = H256::from_low_u64_be(3);
+        let block = |number: u64, timestamp: u64| {
+            let mut block = test_utils::block(number);
+            block["block_header"]["raw_data"]["timestamp"] = timestamp.into();
+            block
+        };
+
+        let node = MockTronNode::start(move |path, body| {
+            let txid = body["value"].as_str().unwrap_or_default();
+            let is = |tx: H256| txid.parse::<H256>().ok() == Some(tx);
+            match path {
+                "/walletsolidity/gettransactionbyid" if is(solidified) => {
+                    let mut tx = test_utils::transaction(txid);
+                    tx["ret"] = serde_json::json!([{"contractRet": "SUCCESS"}]);
+                    tx
+                }
+                "/wallet/gettransactionbyid" if is(in_block) => test_utils::transaction(txid),
+                "/wallet/gettransactionfrompending" if is(pending) => test_utils::transaction(txid),
+                "/wallet/gettransactioninfobyid" => serde_json::json!({"blockNumber": 195}),
+                "/wallet/getnowblock" => block(200, 1_000_070_000),
+                // 20 blocks in 70s
+                "/wallet/getblockbynum" => {
+                    assert_eq!(body["num"], 180);
+                    block(180, 1_000_000_000)
+                }
+                _ => serde_json::json!({}),
+            }
+        })
+        .await;
+        let provider = node.provider();
+
+        assert_eq!(
+            provider
+                .estimate_confirmation_time(solidified)
+                .await
+                .unwrap(),
+            Duration::ZERO
+        );
+        // 14 blocks left until block 195 is solidified, at 3.5s per block
+        assert_eq!(
+            provider.estimate_confirmation_time(in_block).await.unwrap(),
+            Duration::from_secs(49)
+        );
+        // Included in the next block, then solidified
+        assert_eq!(
+            provider.estimate_confirmation_time(pending).await.unwrap(),
+            Duration::from_secs(70)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ensure_reorg_period_supported() {
+        // A node without a solidity endpoint
+        let node = MockTronNode::start(|path, _| match path {
+            "/walletsolidity/getblock" => serde_json::json!({"Error": "404 page not found"}),
+            _ => panic!("unexpected request to {path}"),
+        })
+        .await;
+        let provider = test_utils::provider(ConnectionConf {
+            retry_policy: RetryPolicy::no_retries(),
+            ..node.conf()
+        });
+
+        // Block counts don't need solidified blocks
+        provider
+            .ensure_reorg_period_supported(&ReorgPeriod::None)
+            .await
+            .unwrap();
+        provider
+            .ensure_reorg_period_supported(&ReorgPeriod::from_blocks(20))
+            .await
+            .unwrap();
+
+        let err = provider
+            .ensure_reorg_period_supported(&ReorgPeriod::Tag("finalized".to_owned()))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Reorg period tag `finalized` requires"),
+            "{err}"
+        );
+
+        // A node that can't be reached may still serve solidified blocks
+        let provider = test_utils::provider(ConnectionConf {
+            retry_policy: RetryPolicy::no_retries(),
+            // Nothing listens on port 1
+            ..test_utils::conf(Url::parse("http://127.0.0.1:1").unwrap())
+        });
+        let err = provider
+            .ensure_reorg_period_supported(&ReorgPeriod::Tag("finalized".to_owned()))
+            .await
+            .unwrap_err();
+        assert!(!err.to_string().contains("requires"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_get_contract_abi() {
+        let contract = H256::from(H160::repeat_byte(0x11));
+        let node = MockTronNode::start(move |path, body| {
+            assert_eq!(path, "/wallet/getcontract");
+            if body["value"] != format!("41{}", "11".repeat(20)) {
+                return serde_json::json!({});
+            }
+            // Sample of a mainnet response, with the bytecode shortened
+            serde_json::json!({
+                "bytecode": "6080604052",
+                "name": "Mailbox",
+                "origin_address": "41e552f6487585c2b58bc2c9bb4492bc1f17132cd0",
+                "consume_user_resource_percent": 100,
+                "origin_energy_limit": 10000000,
+                "contract_address": format!("41{}", "11".repeat(20)),
+                "code_hash": "3b1c5e0d2f2a3e4b",
+                "abi": {
+                    "entrys": [
+                        {
+                            "inputs": [
+                                {"name": "_metadata", "type": "bytes"},
+                                {"name": "_message", "type": "bytes"}
+                            ],
+                            "name": "process",
+                            "stateMutability": "Payable",
+                            "type": "Function"
+                        },
+                        {
+                            "outputs": [{"type": "uint32"}],
+                            "constant": true,
+                            "name": "localDomain",
+                            "stateMutability": "View",
+                            "type": "Function"
+                        }
+                    ]
+                }
+            })
+        })
+        .await;
+        let provider = node.provider();
+
+        let abi = provider.get_contract_abi(contract).await.unwrap();
+        let functions: Vec<_> = abi["entrys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(functions, ["process", "localDomain"]);
+
+        let missing = H256::from(H160::repeat_byte(0x22));
+        assert!(provider.get_contract_abi(missing).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_account_permissions() {
+        let key = |byte: &str| format!("41{}", byte.repeat(20));
+        let (multisig, plain) = (key("11"), key("22"));
+        let node = MockTronNode::start({
+            let (multisig, plain) = (multisig.clone(), plain.clone());
+            move |path, body| {
+                assert_eq!(path, "/wallet/getaccount");
+                match body["address"].as_str().unwrap() {
+                    address if address == multisig => serde_json::json!({
+                        "address": multisig,
+                        "balance": 1000000,
+                        "owner_permission": {
+                            "permission_name": "owner",
+                            "threshold": 2,
+                            "keys": [
+                                {"address": key("aa"), "weight": 1},
+                                {"address": key("bb"), "weight": 1}
+                            ]
+                        },
+                        "active_permission": [{
+                            "type": "Active",
+                            "id": 2,
+                            "permission_name": "active",
+                            "threshold": 1,
+                            "operations": "7fff1fc0033e0000000000000000000000000000000000000000000000000000",
+                            "keys": [{"address": key("aa"), "weight": 1}]
+                        }]
+                    }),
+                    // An account whose permissions were never updated
+                    address if address == plain => {
+                        serde_json::json!({"address": plain, "balance": 1000000})
+                    }
+                    _ => serde_json::json!({}),
+                }
+            }
+        })
+        .await;
+        let provider = ConnectionConf {
+            retry_policy: RetryPolicy::no_retries(),
+            ..node.conf()
+        };
+        let provider = test_utils::provider(provider);
+        let h256 = |byte: u8| H256::from(H160::repeat_byte(byte));
+
+        let permissions = provider.get_account_permissions(&multisig).await.unwrap();
+        assert!(permissions.is_multisig());
+        assert_eq!(permissions.owner.threshold, 2);
+        assert_eq!(
+            permissions.owner.keys,
+            vec![
+                PermissionKey {
+                    address: h256(0xaa),
+                    weight: 1
+                },
+                PermissionKey {
+                    address: h256(0xbb),
+                    weight: 1
+                },
+            ]
+        );
+        assert!(!permissions.owner.signs_alone(h256(0xaa)));
+        assert_eq!(permissions.active.len(), 1);
+        assert_eq!(permissions.active[0].id, 2);
+        assert!(permissions.active[0].signs_alone(h256(0xaa)));
+
+        let permissions = provider.get_account_permissions(&plain).await.unwrap();
+        assert!(!permissions.is_multisig());
+        assert!(permissions.owner.signs_alone(h256(0x22)));
+        assert!(permissions.active.is_empty());
+
+        assert!(provider.get_account_permissions(&key("33")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_account_transactions() {
+        let account = TronAddress::from(H160::from_low_u64_be(0xaa))
+            .as_ref()
+            .as_base58();
+        let tx = |i: u64| {
+            serde_json::json!({
+                "txID": format!("{:x}", H256::from_low_u64_be(i)),
+                "blockNumber": 100 - i,
+                "block_timestamp": 1706102949000u64,
+                "raw_data": {"contract": [{"type": "TriggerSmartContract"}]},
+                "ret": [{"contractRet": "SUCCESS", "fee": 0}]
+            })
+        };
+
+        let node = {
+            let account = account.clone();
+            MockTronNode::start(move |path, _| {
+                if path.starts_with(&format!("/v1/accounts/{account}/transactions?")) {
+                    assert!(path.contains("order_by=block_timestamp,desc"));
+                    if path.contains("fingerprint=next") {
+                        assert!(path.contains("limit=1"));
+                        serde_json::json!({"data": [tx(3), tx(4)], "success": true, "meta": {}})
+                    } else {
+                        assert!(path.contains("limit=3"));
+                        serde_json::json!({
+                            "data": [tx(1), tx(2)],
+                            "success": true,
+                            "meta": {"fingerprint": "next"}
+                        })
+                    }
+                } else {
+                    serde_json::json!({"success": false, "error": "account not found"})
+                }
+            })
+            .await
+        };
+        let provider = node.provider();
+
+        let tx_ids = provider
+            .get_account_transactions(&account, 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            tx_ids,
+            (1..=3)
+                .map(|i| H512::from(H256::from_low_u64_be(i)))
+                .collect::<Vec<_>>()
+        );
+
+        // An account that was never activated
+        let unknown = TronAddress::from(H160::from_low_u64_be(0xbb))
+            .as_ref()
+            .as_base58();
+        assert!(provider
+            .get_account_transactions(&unknown, 3)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(provider
+            .get_account_transactions("not an address", 3)
+            .await
+            .is_err());
+    }
+
+    /// Node whose latest finalized block is `finalized`, and whose blocks have their
+    /// number and `fork` as id
+    async fn node_on_fork(finalized: u64, fork: u8) -> MockTronNode {
+        MockTronNode::start(move |path, body| {
+            let number = match path {
+                "/walletsolidity/getblock" => finalized,
+                "/wallet/getblockbynum" => body["num"].as_u64().unwrap(),
+                _ => panic!("unexpected call to {path}"),
+            };
+            let mut block = test_utils::block(number);
+            block["blockID"] = format!("{number:016x}{}", format!("{fork:02x}").repeat(24)).into();
+            block
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_detect_fork() {
+        let node = node_on_fork(100, 0xaa).await;
+        let lagging = node_on_fork(98, 0xaa).await;
+        let forked = node_on_fork(99, 0xbb).await;
+        let provider = |reference: &MockTronNode| {
+            test_utils::provider(ConnectionConf {
+                reference_urls: vec![lagging.url.clone(), reference.url.clone()],
+                ..node.conf()
+            })
+        };
+
+        assert_eq!(provider(&lagging).detect_fork().await.unwrap(), None);
+
+        let report = provider(&forked).detect_fork().await.unwrap().unwrap();
+        let hash = |fork: u8| {
+            let mut hash = [fork; 32];
+            hash[..8].copy_from_slice(&98u64.to_be_bytes());
+            Some(H256(hash))
+        };
+        assert_eq!(
+            report,
+            ForkReport {
+                block_number: 98,
+                hashes: vec![
+                    (node.url.clone(), hash(0xaa)),
+                    (lagging.url.clone(), hash(0xaa)),
+                    (forked.url.clone(), hash(0xbb)),
+                ],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reference_nodes_send_their_own_api_key() {
+        let node = node_on_fork(100, 0xaa).await;
+        let private = node_on_fork(99, 0xaa).await;
+        let trongrid = node_on_fork(99, 0xaa).await;
+        let conf = crate::ConnectionConfBuilder::new(node.url.clone())
+            .with_reference_urls(vec![private.url.clone(), trongrid.url.clone()])
+            .with_api_key("main-key")
+            .with_reference_api_key(trongrid.url.clone(), "trongrid-key")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            test_utils::provider(conf).detect_fork().await.unwrap(),
+            None
+        );
+
+        let sent = |node: &MockTronNode, key: &str| {
+            let keys = node.api_keys();
+            !keys.is_empty() && keys.iter().all(|k| k.as_deref() == Some(key))
+        };
+        assert!(sent(&node, "main-key"));
+        assert!(sent(&private, "main-key"));
+        assert!(sent(&trongrid, "trongrid-key"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_block_from_cursor_snapshot() {
+        let node = node_on_fork(100, 0xaa).await;
+        let provider = node.provider();
+
+        let snapshot = provider.cursor_snapshot(80).await.unwrap();
+        assert_eq!(snapshot.last_indexed_block, 80);
+        assert_eq!(provider.resume_block(&snapshot).await.unwrap(), 81);
+
+        // The node switched to another fork since the snapshot
+        let reorged = node_on_fork(100, 0xbb).await;
+        assert_eq!(
+            reorged.provider().resume_block(&snapshot).await.unwrap(),
+            80 - TRON_SOLIDIFICATION_BLOCKS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_block_by_height() {
+        let node = MockTronNode::start(|path, body| match path {
+            "/wallet/getnowblock" => test_utils::block(100),
+            "/wallet/getblockbynum" => match body["num"].as_u64().unwrap() {
+                // A node serving the wrong block
+                50 => test_utils::block(49),
+                number if number <= 100 => test_utils::block(number),
+                _ => serde_json::json!({}),
+            },
+            _ => panic!("unexpected call to {path}"),
+        })
+        .await;
+        let provider = node.provider();
+
+        let block = provider.get_block_by_height(80).await.unwrap();
+        assert_eq!(block.number, 80);
+        assert_eq!(block.timestamp, 1722858393);
+        assert_eq!(block.hash.0[..8], 80u64.to_be_bytes());
+
+        assert!(provider.get_block_by_height(50).await.is_err());
+        assert!(provider.get_block_by_height(101).await.is_err());
+
+        let metrics = provider.get_chain_metrics().await.unwrap().unwrap();
+        assert_eq!(metrics.latest_block.number, 100);
+        assert_eq!(metrics.min_gas_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_trx() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let recipient = H256::from_low_u64_be(0xaa);
+        let transfers = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let transfers = transfers.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/createtransaction" => {
+                    transfers.lock().unwrap().push(body.clone());
+                    test_utils::transaction(&txid)
+                }
+                "/wallet/broadcasttransaction" => {
+                    assert_eq!(body["signature"].as_array().unwrap().len(), 1);
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                _ => panic!("unexpected call to {path}"),
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+
+        let outcome = provider
+            .transfer_trx(recipient, 1_500_000, &signer)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.transaction_id, H256::from_low_u64_be(1).into());
+        assert_eq!(
+            *transfers.lock().unwrap(),
+            vec![serde_json::json!({
+                "owner_address": signer.0.address().as_hex(),
+                "to_address": TronAddress::try_from(recipient).unwrap().as_ref().as_hex(),
+                "amount": 1_500_000
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_trx_already_accepted() {
+        let txid = format!("{:#x}", H256::from_low_u64_be(1));
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+
+        // An earlier attempt reached the node, but its response was lost
+        let node = MockTronNode::start(move |path, body| match path {
+            "/wallet/createtransaction" => test_utils::transaction(&txid),
+            "/wallet/broadcasttransaction" => serde_json::json!({
+                "code": "DUP_TRANSACTION_ERROR",
+                "message": ethers::utils::hex::encode("Dup transaction"),
+                "txid": body["txID"]
+            }),
+            _ => panic!("unexpected call to {path}"),
+        })
+        .await;
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+
+        let outcome = provider
+            .transfer_trx(H256::from_low_u64_be(0xaa), 1_500_000, &signer)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.transaction_id, H256::from_low_u64_be(1).into());
+    }
+
+    #[tokio::test]
+    async fn test_reissue_expired_transaction() {
+        let txid = |n: u64| format!("{:#x}", H256::from_low_u64_be(n));
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let transfers = Arc::new(Mutex::new(vec![]));
+
+        let node = {
+            let transfers = transfers.clone();
+            MockTronNode::start(move |path, body| match path {
+                "/wallet/createtransaction" => {
+                    let mut transfers = transfers.lock().unwrap();
+                    transfers.push(body.clone());
+                    // Expired long ago
+                    let mut tx = test_utils::transaction(&txid(transfers.len() as u64));
+                    tx["raw_data"]["contract"] = serde_json::json!([{
+                        "type": "TransferContract",
+                        "parameter": {"value": body}
+                    }]);
+                    tx
+                }
+                "/wallet/broadcasttransaction" => {
+                    test_utils::broadcast_result(body["txID"].clone())
+                }
+                "/wallet/getchainparameters" => test_utils::chain_parameters(210),
+                // Only the reissued transaction is known, still pending
+                "/wallet/gettransactionfrompending" if body["value"] == txid(2) => {
+                    test_utils::transaction(&txid(2))
+                }
+                _ => serde_json::json!({}),
+            })
+            .await
+        };
+        let provider = test_utils::provider(ConnectionConf {
+            wait_for_confirmation: false,
+            ..node.conf()
+        });
+
+        provider
+            .transfer_trx(H256::from_low_u64_be(0xaa), 1_000_000, &signer)
+            .await
+            .unwrap();
+        let outcome = provider
+            .reissue(H256::from_low_u64_be(1), &signer)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.transaction_id, H256::from_low_u64_be(2).into());
+        let transfers = transfers.lock().unwrap().clone();
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0], transfers[1]);
+
+        // A pending transaction can't be canceled
+        let err = provider
+            .reissue(H256::from_low_u64_be(2), &signer)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("still pending"), "{err}");
+        // Nor can one that was never sent
+        let err = provider
+            .reissue(H256::from_low_u64_be(3), &signer)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_await_confirmations() {
+        let confirmed = H256::from_low_u64_be(1);
+        let reverted = H256::from_low_u64_be(2);
+        let pending = H256::from_low_u64_be(3);
+
+        let node = MockTronNode::start(move |path, body| {
+            assert_eq!(path, "/walletsolidity/gettransactionbyid");
+            let txid = body["value"].as_str().unwrap().to_owned();
+            if txid == format!("{confirmed:#x}") {
+                tx_info(&txid, "SUCCESS")
+            } else if txid == format!("{reverted:#x}") {
+                tx_info(&txid, "REVERT")
+            } else {
+                serde_json::json!({})
+            }
+        })
+        .await;
+
+        let statuses = node
+            .provider()
+            .await_confirmations_with_timeout(
+                &[confirmed, reverted, pending],
+                Duration::from_millis(200),
+            )
+            .await;
+
+        assert_eq!(
+            statuses,
+            vec![(confirmed, true), (reverted, false), (pending, false)]
+        );
     }
 }
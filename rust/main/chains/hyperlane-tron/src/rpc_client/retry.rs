@@ -0,0 +1,229 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::config::serde_duration;
+use crate::HyperlaneTronError;
+
+/// How calls to the Tron node are retried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each following retry
+    #[serde(rename = "baseDelayMs", with = "serde_duration::millis")]
+    pub base_delay: Duration,
+    /// Upper bound of the delay between two attempts
+    #[serde(rename = "maxDelayMs", with = "serde_duration::millis")]
+    pub max_delay: Duration,
+    /// Whether to randomize delays so that clients don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy making a single attempt
+    pub fn no_retries() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay to wait after the given failed attempt (starting at 1)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+
+        if self.jitter && !delay.is_zero() {
+            // Somewhere between half and the full delay
+            delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            delay
+        }
+    }
+
+    /// Calls `f` until it succeeds or the attempts are exhausted, returning the last error.
+    /// Only transient errors are retried, see [`HyperlaneTronError::is_transient`]: a
+    /// revert or a short-circuited call fails the same way when made again.
+    pub async fn retry<T, F, Fut>(&self, f: F) -> Result<T, HyperlaneTronError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, HyperlaneTronError>>,
+    {
+        self.retry_when(HyperlaneTronError::is_transient, f).await
+    }
+
+    /// Like [`RetryPolicy::retry`], but only retries errors matching `retryable`
+    pub async fn retry_when<T, E, F, Fut>(
+        &self,
+        retryable: impl Fn(&E) -> bool,
+        mut f: F,
+    ) -> Result<T, E>
+    where
+        E: std::fmt::Debug,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Ok(res) => return Ok(res),
+                Err(err) if attempt < self.max_attempts && retryable(&err) => {
+                    let delay = self.delay(attempt);
+                    warn!(attempt, ?delay, error = ?err, "Retrying call to Tron node");
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        }
+    }
+
+    /// Error of a request that never reached the node
+    fn transient_error() -> HyperlaneTronError {
+        let err = reqwest::Client::new().get("http://").build().unwrap_err();
+        HyperlaneTronError::HttpClientError(err)
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausts_attempts() {
+        let attempts = AtomicU32::new(0);
+
+        let res: Result<(), u32> = policy(3)
+            .retry_when(
+                |_| true,
+                || async { Err(attempts.fetch_add(1, Ordering::SeqCst)) },
+            )
+            .await;
+
+        assert_eq!(res, Err(2));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_on_success() {
+        let attempts = AtomicU32::new(0);
+
+        let res = policy(5)
+            .retry(|| async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(transient_error()),
+                    n => Ok(n),
+                }
+            })
+            .await;
+
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_errors_answered_by_the_node() {
+        let attempts = AtomicU32::new(0);
+
+        let res: Result<(), _> = policy(5)
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(HyperlaneTronError::CircuitOpen {
+                    retry_in: Duration::from_secs(1),
+                })
+            })
+            .await;
+
+        assert!(matches!(res, Err(HyperlaneTronError::CircuitOpen { .. })));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_when_skips_fatal_errors() {
+        let attempts = AtomicU32::new(0);
+
+        let res: Result<(), &str> = policy(5)
+            .retry_when(
+                |err| *err == "transient",
+                || async {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err("transient"),
+                        _ => Err("fatal"),
+                    }
+                },
+            )
+            .await;
+
+        assert_eq!(res, Err("fatal"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_retries() {
+        let attempts = AtomicU32::new(0);
+
+        let res: Result<(), _> = RetryPolicy::no_retries()
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(transient_error())
+            })
+            .await;
+
+        assert!(res.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            jitter: false,
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(4), Duration::from_millis(800));
+        assert_eq!(policy.delay(5), Duration::from_secs(1));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(1));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for attempt in 1..10 {
+            let delay = policy.delay(attempt);
+            assert!(delay <= Duration::from_secs(1));
+            assert!(delay >= Duration::from_millis(50));
+        }
+    }
+}
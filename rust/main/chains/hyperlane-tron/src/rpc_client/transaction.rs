@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use heliosphere_core::transaction::{Transaction, TransactionId};
+use sha2::{Digest, Sha256};
+
+use crate::HyperlaneTronError;
+
+/// Longest validity window the network accepts for a transaction
+pub const MAX_TX_EXPIRATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Code of the broadcast error returned for an expired transaction
+const EXPIRATION_ERROR_CODE: &str = "TRANSACTION_EXPIRATION_ERROR";
+
+/// Code of the broadcast error returned for a transaction the node already accepted
+const DUPLICATE_ERROR_CODE: &str = "DUP_TRANSACTION_ERROR";
+
+/// Number of the `expiration` field in the `Transaction.raw` protobuf message
+const EXPIRATION_FIELD: u64 = 8;
+/// Number of the `contract` field in the `Transaction.raw` protobuf message
+const CONTRACT_FIELD: u64 = 11;
+/// Number of the `Permission_id` field in the `Transaction.Contract` protobuf message
+const PERMISSION_ID_FIELD: u64 = 5;
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_64BIT: u64 = 1;
+const WIRE_LEN: u64 = 2;
+const WIRE_32BIT: u64 = 5;
+
+/// Whether the node rejected a broadcast transaction because it expired
+pub(crate) fn is_expiration_error(err: &HyperlaneTronError) -> bool {
+    matches!(
+        err,
+        HyperlaneTronError::ClientError(heliosphere::Error::TxConstructionFailed(code, _))
+            if code == EXPIRATION_ERROR_CODE
+    )
+}
+
+/// Whether the node rejected a broadcast transaction because it already accepted it,
+/// e.g. from an attempt whose response was lost in transport
+pub(crate) fn is_duplicate_error(err: &HyperlaneTronError) -> bool {
+    matches!(
+        err,
+        HyperlaneTronError::ClientError(heliosphere::Error::TxConstructionFailed(code, _))
+            if code == DUPLICATE_ERROR_CODE
+    )
+}
+
+/// Makes the (unsigned) transaction expire `window` after its creation.
+///
+/// The node builds transactions with its own default window, usually 60s. The
+/// expiration is part of the signed raw data, so the protobuf encoded raw data is
+/// patched and the transaction id recomputed.
+pub(crate) fn set_expiration(
+    tx: &mut Transaction,
+    window: Duration,
+) -> Result<(), HyperlaneTronError> {
+    let expiration = tx.raw_data.timestamp + window.as_millis() as u64;
+    let raw = &tx.raw_data_bytes;
+
+    let mut patched = Vec::with_capacity(raw.len() + 10);
+    let mut found = false;
+    let mut pos = 0;
+    while pos < raw.len() {
+        let start = pos;
+        let key = read_varint(raw, &mut pos)?;
+        match key & 0x7 {
+            WIRE_VARINT => {
+                read_varint(raw, &mut pos)?;
+            }
+            WIRE_64BIT => pos += 8,
+            WIRE_LEN => {
+                let len = read_varint(raw, &mut pos)?;
+                pos = pos.saturating_add(len as usize);
+            }
+            WIRE_32BIT => pos += 4,
+            wire_type => {
+                return Err(malformed(format!("unsupported wire type {wire_type}")));
+            }
+        }
+        if pos > raw.len() {
+            return Err(malformed("truncated field".to_owned()));
+        }
+
+        if key == EXPIRATION_FIELD << 3 | WIRE_VARINT {
+            write_varint(key, &mut patched);
+            write_varint(expiration, &mut patched);
+            found = true;
+        } else {
+            patched.extend_from_slice(&raw[start..pos]);
+        }
+    }
+    if !found {
+        write_varint(EXPIRATION_FIELD << 3 | WIRE_VARINT, &mut patched);
+        write_varint(expiration, &mut patched);
+    }
+
+    tx.tx_id = TransactionId(Sha256::digest(&patched).into());
+    tx.raw_data_bytes = patched;
+    tx.raw_data.expiration = expiration;
+
+    Ok(())
+}
+
+/// Permission the transaction is signed under, read from its protobuf encoded raw
+/// data. `None` for the owner permission, which is left out of the encoding.
+pub(crate) fn permission_id(tx: &Transaction) -> Result<Option<u32>, HyperlaneTronError> {
+    let mut permission_id = None;
+    for (key, value) in fields(&tx.raw_data_bytes)? {
+        if key != CONTRACT_FIELD << 3 | WIRE_LEN {
+            continue;
+        }
+        for (key, value) in fields(value)? {
+            if key == PERMISSION_ID_FIELD << 3 | WIRE_VARINT {
+                let id = read_varint(value, &mut 0)?;
+                permission_id = Some(
+                    u32::try_from(id)
+                        .map_err(|_| malformed(format!("permission id {id} out of range")))?,
+                );
+            }
+        }
+    }
+    Ok(permission_id.filter(|id| *id != 0))
+}
+
+/// JSON body broadcasting the signed transaction. The node rebuilds the signed raw
+/// data from the JSON `raw_data`, where the deserialized transaction lost the
+/// permission id of its contracts, so it is put back.
+pub(crate) fn transaction_json(tx: &Transaction) -> Result<serde_json::Value, HyperlaneTronError> {
+    let mut json =
+        serde_json::to_value(tx).map_err(|err| malformed(format!("can't serialize: {err}")))?;
+    if let Some(permission_id) = permission_id(tx)? {
+        if let Some(contracts) = json["raw_data"]["contract"].as_array_mut() {
+            for contract in contracts {
+                contract["Permission_id"] = permission_id.into();
+            }
+        }
+    }
+    Ok(json)
+}
+
+/// Key and encoded value, without the key, of each field of a protobuf message
+fn fields(bytes: &[u8]) -> Result<Vec<(u64, &[u8])>, HyperlaneTronError> {
+    let mut fields = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let key = read_varint(bytes, &mut pos)?;
+        let (start, end) = match key & 0x7 {
+            WIRE_VARINT => {
+                let start = pos;
+                read_varint(bytes, &mut pos)?;
+                (start, pos)
+            }
+            WIRE_64BIT => (pos, pos + 8),
+            WIRE_LEN => {
+                let len = read_varint(bytes, &mut pos)?;
+                (pos, pos.saturating_add(len as usize))
+            }
+            WIRE_32BIT => (pos, pos + 4),
+            wire_type => {
+                return Err(malformed(format!("unsupported wire type {wire_type}")));
+            }
+        };
+        if end > bytes.len() {
+            return Err(malformed("truncated field".to_owned()));
+        }
+        fields.push((key, &bytes[start..end]));
+        pos = end;
+    }
+    Ok(fields)
+}
+
+fn malformed(reason: String) -> HyperlaneTronError {
+    HyperlaneTronError::MalformedTransaction(reason)
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, HyperlaneTronError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| malformed("truncated varint".to_owned()))?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(malformed("varint overflow".to_owned()))
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_tx(raw_data_bytes: Vec<u8>) -> Transaction {
+        let mut tx: Transaction = serde_json::from_value(crate::test_utils::transaction(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        ))
+        .unwrap();
+        tx.raw_data_bytes = raw_data_bytes;
+        tx
+    }
+
+    #[test]
+    fn test_set_expiration() {
+        let mut raw = vec![];
+        // ref_block_bytes
+        raw.extend([0x0a, 0x02, 0xc3, 0xd2]);
+        // expiration
+        write_varint(EXPIRATION_FIELD << 3, &mut raw);
+        write_varint(1722858453000, &mut raw);
+        // timestamp
+        write_varint(14 << 3, &mut raw);
+        write_varint(1722858393000, &mut raw);
+        let mut tx = raw_tx(raw);
+
+        set_expiration(&mut tx, Duration::from_secs(3600)).unwrap();
+
+        let expiration = 1722858393000 + 3_600_000;
+        let mut expected = vec![0x0a, 0x02, 0xc3, 0xd2];
+        write_varint(EXPIRATION_FIELD << 3, &mut expected);
+        write_varint(expiration, &mut expected);
+        write_varint(14 << 3, &mut expected);
+        write_varint(1722858393000, &mut expected);
+        assert_eq!(tx.raw_data_bytes, expected);
+        assert_eq!(tx.raw_data.expiration, expiration);
+        assert_eq!(tx.tx_id.0, <[u8; 32]>::from(Sha256::digest(&expected)));
+    }
+
+    #[test]
+    fn test_set_expiration_malformed() {
+        // Length prefixed field longer than the data
+        let mut tx = raw_tx(vec![0x0a, 0x05, 0xc3, 0xd2]);
+
+        assert!(matches!(
+            set_expiration(&mut tx, Duration::from_secs(60)),
+            Err(HyperlaneTronError::MalformedTransaction(_))
+        ));
+    }
+
+    #[test]
+    fn test_permission_id() {
+        // ref_block_bytes, then a TriggerSmartContract signed under permission 2
+        let raw = vec![0x0a, 0x02, 0xc3, 0xd2, 0x5a, 0x04, 0x08, 0x1f, 0x28, 0x02];
+        let mut tx = raw_tx(raw);
+        tx.raw_data.contract = vec![serde_json::from_value(serde_json::json!({
+            "parameter": {},
+            "type": "TriggerSmartContract"
+        }))
+        .unwrap()];
+
+        assert_eq!(permission_id(&tx).unwrap(), Some(2));
+        let json = transaction_json(&tx).unwrap();
+        assert_eq!(json["raw_data"]["contract"][0]["Permission_id"], 2);
+
+        // The owner permission is left out
+        let mut tx = raw_tx(vec![0x0a, 0x02, 0xc3, 0xd2, 0x5a, 0x02, 0x08, 0x1f]);
+        tx.raw_data.contract = json["raw_data"]["contract"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|contract| serde_json::from_value(contract.clone()).unwrap())
+            .collect();
+        assert_eq!(permission_id(&tx).unwrap(), None);
+        let json = transaction_json(&tx).unwrap();
+        assert!(json["raw_data"]["contract"][0]
+            .get("Permission_id")
+            .is_none());
+    }
+
+    #[test]
+    fn test_is_expiration_error() {
+        assert!(is_expiration_error(
+            &heliosphere::Error::TxConstructionFailed(
+                "TRANSACTION_EXPIRATION_ERROR".to_owned(),
+                "Transaction expired".to_owned()
+            )
+            .into()
+        ));
+        assert!(!is_expiration_error(
+            &heliosphere::Error::TxConstructionFailed(
+                "SIGERROR".to_owned(),
+                "Validate signature error".to_owned()
+            )
+            .into()
+        ));
+    }
+
+    #[test]
+    fn test_is_duplicate_error() {
+        assert!(is_duplicate_error(
+            &heliosphere::Error::TxConstructionFailed(
+                "DUP_TRANSACTION_ERROR".to_owned(),
+                "Dup transaction".to_owned()
+            )
+            .into()
+        ));
+        assert!(!is_duplicate_error(
+            &heliosphere::Error::TxConstructionFailed(
+                "TRANSACTION_EXPIRATION_ERROR".to_owned(),
+                "Transaction expired".to_owned()
+            )
+            .into()
+        ));
+    }
+}
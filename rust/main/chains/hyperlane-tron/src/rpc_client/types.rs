@@ -0,0 +1,1028 @@
+use std::collections::BTreeMap;
+
+use ethers::utils::hex;
+use heliosphere::AccountResources;
+use heliosphere_core::{
+    block::Block,
+    transaction::{Transaction, TransactionId},
+};
+use serde::{Deserialize, Serialize};
+
+use hyperlane_core::{TxOutcome, H256};
+
+use crate::{decode_revert_reason, TronAddress};
+
+/// Id of the `getEnergyFee` chain parameter in governance proposals
+pub(crate) const ENERGY_FEE_PARAMETER_ID: i64 = 11;
+
+/// Proposal list as returned by `/wallet/listproposals`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ListProposalsResponse {
+    #[serde(default)]
+    pub proposals: Vec<Proposal>,
+}
+
+/// Committee proposal changing one or more chain parameters
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct Proposal {
+    /// Time (ms) at which voting closes and approved parameters take effect
+    pub expiration_time: u64,
+    #[serde(default)]
+    pub parameters: Vec<ProposalParameter>,
+    #[serde(default)]
+    pub state: Option<String>,
+}
+
+/// Chain parameter change carried by a proposal
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ProposalParameter {
+    pub key: i64,
+    pub value: i64,
+}
+
+impl Proposal {
+    pub fn is_approved(&self) -> bool {
+        self.state.as_deref() == Some("APPROVED")
+    }
+}
+
+impl ListProposalsResponse {
+    /// (timestamp, fee) pairs of approved energy fee changes, oldest first
+    pub fn energy_fee_history(&self) -> Vec<(u64, u64)> {
+        let mut history: Vec<(u64, u64)> = self
+            .proposals
+            .iter()
+            .filter(|proposal| proposal.is_approved())
+            .flat_map(|proposal| {
+                proposal
+                    .parameters
+                    .iter()
+                    .filter(|param| param.key == ENERGY_FEE_PARAMETER_ID)
+                    .map(|param| (proposal.expiration_time, param.value as u64))
+            })
+            .collect();
+
+        history.sort_by_key(|(timestamp, _)| *timestamp);
+        history
+    }
+}
+
+/// Subset of the `/wallet/getnodeinfo` response
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NodeInfoResponse {
+    pub config_node_info: ConfigNodeInfo,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigNodeInfo {
+    pub code_version: String,
+}
+
+/// State of the node and of a signer, to be attached to bug reports
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    /// Version of the java-tron node
+    pub node_version: String,
+    /// Latest block number
+    pub latest_block: u64,
+    /// Latest solidified block number
+    pub finalized_block: u64,
+    /// Energy price, in sun
+    pub energy_fee: u64,
+    /// State of the signer, if any
+    pub signer: Option<SignerDiagnostics>,
+}
+
+/// State of a signer account
+#[derive(Debug, Clone, Serialize)]
+pub struct SignerDiagnostics {
+    /// Base58 address
+    pub address: String,
+    /// Balance in sun, `None` if the account isn't activated
+    pub balance: Option<u64>,
+    /// Bandwidth and energy of the account
+    pub resources: AccountResources,
+}
+
+/// Result of the execution of a transaction, as reported in the `contractRet` of its
+/// receipt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TronTxResult {
+    /// Executed successfully
+    Success,
+    /// Reverted by the contract
+    Revert,
+    /// Ran out of energy, i.e. its fee limit was too low
+    OutOfEnergy,
+    /// Exceeded the maximum execution time of a transaction
+    OutOfTime,
+    /// Any other result, e.g. an invalid opcode, as reported by the node
+    Unknown(String),
+}
+
+impl From<&str> for TronTxResult {
+    fn from(contract_ret: &str) -> Self {
+        match contract_ret {
+            "SUCCESS" => TronTxResult::Success,
+            "REVERT" => TronTxResult::Revert,
+            "OUT_OF_ENERGY" => TronTxResult::OutOfEnergy,
+            "OUT_OF_TIME" => TronTxResult::OutOfTime,
+            other => TronTxResult::Unknown(other.to_owned()),
+        }
+    }
+}
+
+impl std::fmt::Display for TronTxResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TronTxResult::Success => f.write_str("SUCCESS"),
+            TronTxResult::Revert => f.write_str("REVERT"),
+            TronTxResult::OutOfEnergy => f.write_str("OUT_OF_ENERGY"),
+            TronTxResult::OutOfTime => f.write_str("OUT_OF_TIME"),
+            TronTxResult::Unknown(contract_ret) => f.write_str(contract_ret),
+        }
+    }
+}
+
+/// Outcome of a sent transaction, along with why it failed if it did
+#[derive(Debug, Clone)]
+pub struct TronTxOutcome {
+    /// Outcome in the chain-agnostic shape of hyperlane-core
+    pub outcome: TxOutcome,
+    /// Result of the execution, `None` if the transaction wasn't awaited until
+    /// confirmed, or was dropped
+    pub result: Option<TronTxResult>,
+}
+
+/// Cost of a transaction in Tron resources, richer than the EVM-shaped
+/// `TxCostEstimate` it maps to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TronCostEstimate {
+    /// Energy consumed by the execution
+    pub energy: u64,
+    /// Bandwidth consumed, i.e. bytes of the signed transaction
+    pub bandwidth: u64,
+    /// Price of a unit of energy (in sun) the fee was computed with
+    pub energy_price_sun: u64,
+    /// Fee burned (in sun) if the signer has no staked or free resources left
+    pub trx_fee_sun: u64,
+}
+
+/// Disagreement between nodes of a chain on a finalized block, found by
+/// [`crate::TronProvider::detect_fork`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkReport {
+    /// Height compared, the lowest finalized one among the nodes
+    pub block_number: u64,
+    /// Hash of the block at that height according to each node, `None` if the
+    /// node doesn't have it
+    pub hashes: Vec<(url::Url, Option<H256>)>,
+}
+
+/// Position of an indexer, persisted to resume indexing after a restart. The hash of
+/// the last indexed block tells whether the indexed blocks were reorged out since.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorSnapshot {
+    /// Last block whose logs were indexed
+    pub last_indexed_block: u32,
+    /// Hash of that block when it was indexed
+    pub last_block_hash: H256,
+}
+
+/// Number of blocks produced per day, one every 3 seconds
+pub(crate) const BLOCKS_PER_DAY: u64 = 28_800;
+
+/// Resources consumed by the transactions of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockResourceUsage {
+    /// Number of the block
+    pub block_number: u64,
+    /// Energy consumed by the transactions of the block
+    pub energy_used: u64,
+    /// Bandwidth (bytes) consumed by the transactions of the block, staked or paid for
+    pub bandwidth_used: u64,
+    /// Share of the network's daily energy limit available to a block
+    pub energy_budget: u64,
+}
+
+impl BlockResourceUsage {
+    pub(crate) fn from_tx_infos(
+        block_number: u64,
+        tx_infos: &[TransactionInfoReceipt],
+        total_energy_limit: u64,
+        bandwidth_price: u64,
+    ) -> Self {
+        let receipts = tx_infos.iter().map(|info| &info.receipt);
+        Self {
+            block_number,
+            energy_used: receipts.clone().map(|r| r.energy_usage_total).sum(),
+            bandwidth_used: receipts
+                .map(|r| r.net_usage + r.net_fee.checked_div(bandwidth_price).unwrap_or(0))
+                .sum(),
+            energy_budget: total_energy_limit / BLOCKS_PER_DAY,
+        }
+    }
+
+    /// Energy used relative to the block's budget, above 1 under congestion
+    pub fn energy_utilization(&self) -> f64 {
+        if self.energy_budget == 0 {
+            return 0.;
+        }
+        self.energy_used as f64 / self.energy_budget as f64
+    }
+}
+
+/// Chain parameters set by governance, as returned by `/wallet/getchainparameters`.
+/// Prices are in sun.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChainParameters {
+    /// Price of a unit of energy
+    pub energy_fee: u64,
+    /// Price of a byte of bandwidth
+    pub transaction_fee: u64,
+    /// Highest fee limit a transaction can set
+    pub max_fee_limit: u64,
+    /// Fee for activating an account by sending it TRX or tokens
+    pub create_account_fee: u64,
+    /// Fee for activating an account through the `CreateAccount` system contract
+    pub create_new_account_fee_in_system_contract: u64,
+    /// Fee for attaching a memo to a transaction
+    pub memo_fee: u64,
+    /// Longest CPU time (ms) a contract call can take
+    pub max_cpu_time_of_one_tx: u64,
+    /// Energy shared by all accounts staking for it, per day
+    pub total_energy_current_limit: u64,
+    /// Free bandwidth given to each account, per day
+    pub free_net_limit: u64,
+    /// Bandwidth shared by all accounts staking for it, per day
+    pub total_net_limit: u64,
+    /// Time (ms) between maintenance periods, when governance changes take effect
+    pub maintenance_time_interval: u64,
+    /// Whether the energy used by popular contracts is increased, `false` on
+    /// nodes predating dynamic energy
+    pub allow_dynamic_energy: bool,
+}
+
+impl TryFrom<&BTreeMap<String, i64>> for ChainParameters {
+    type Error = heliosphere::Error;
+
+    fn try_from(params: &BTreeMap<String, i64>) -> Result<Self, Self::Error> {
+        let param = |key: &str| {
+            params
+                .get(key)
+                .map(|v| *v as u64)
+                .ok_or_else(|| heliosphere::Error::UnknownResponse(format!("{key} not found")))
+        };
+
+        Ok(Self {
+            energy_fee: param("getEnergyFee")?,
+            transaction_fee: param("getTransactionFee")?,
+            max_fee_limit: param("getMaxFeeLimit")?,
+            create_account_fee: param("getCreateAccountFee")?,
+            create_new_account_fee_in_system_contract: param(
+                "getCreateNewAccountFeeInSystemContract",
+            )?,
+            memo_fee: param("getMemoFee")?,
+            max_cpu_time_of_one_tx: param("getMaxCpuTimeOfOneTx")?,
+            total_energy_current_limit: param("getTotalEnergyCurrentLimit")?,
+            free_net_limit: param("getFreeNetLimit")?,
+            total_net_limit: param("getTotalNetLimit")?,
+            maintenance_time_interval: param("getMaintenanceTimeInterval")?,
+            allow_dynamic_energy: param("getAllowDynamicEnergy").unwrap_or(0) != 0,
+        })
+    }
+}
+
+/// Internal transactions of a transaction, as returned by `/wallet/gettransactioninfobyid`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct InternalTransactionsResponse {
+    #[serde(default)]
+    pub internal_transactions: Vec<InternalTransaction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct InternalTransaction {
+    pub caller_address: TronAddress,
+    #[serde(rename = "transferTo_address")]
+    pub transfer_to_address: TronAddress,
+    #[serde(default, rename = "callValueInfo")]
+    pub call_value_info: Vec<CallValueInfo>,
+    /// Hex encoded kind of the call
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub rejected: bool,
+}
+
+/// Value sent along an internal transaction, in TRX unless it has a token id
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct CallValueInfo {
+    #[serde(default, rename = "callValue")]
+    pub call_value: u64,
+    #[serde(default, rename = "tokenId")]
+    pub token_id: Option<String>,
+}
+
+/// Call made by a contract while executing a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InternalTx {
+    /// Contract making the call
+    pub caller: H256,
+    /// Account or contract called
+    pub callee: H256,
+    /// TRX (sun) sent along the call
+    pub value: u64,
+    /// Kind of call, e.g. `call`, `create` or `suicide`
+    pub kind: String,
+    /// Whether the call reverted
+    pub rejected: bool,
+}
+
+impl From<InternalTransaction> for InternalTx {
+    fn from(tx: InternalTransaction) -> Self {
+        let note = hex::decode(&tx.note).unwrap_or_default();
+        Self {
+            caller: tx.caller_address.into(),
+            callee: tx.transfer_to_address.into(),
+            value: tx
+                .call_value_info
+                .iter()
+                .filter(|info| info.token_id.is_none())
+                .map(|info| info.call_value)
+                .sum(),
+            kind: String::from_utf8_lossy(&note).into_owned(),
+            rejected: tx.rejected,
+        }
+    }
+}
+
+/// Permissions of an account, which decide which keys can sign its transactions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountPermissions {
+    /// Permission transactions are signed under unless they name another one
+    pub owner: Permission,
+    /// Permissions restricted to some kinds of transactions
+    pub active: Vec<Permission>,
+}
+
+/// Keys that can sign under a permission, and the weight their signatures must add up to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Permission {
+    /// Id transactions refer to the permission by, 0 for the owner
+    pub id: i32,
+    /// Name given by the account, e.g. `owner`
+    pub name: String,
+    /// Total weight of the signatures required
+    pub threshold: u64,
+    /// Keys allowed to sign
+    pub keys: Vec<PermissionKey>,
+}
+
+/// Key of a permission and the weight of its signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PermissionKey {
+    /// Address of the key, as the right-aligned 20 bytes
+    pub address: H256,
+    /// Weight of a signature by the key
+    pub weight: u64,
+}
+
+impl AccountPermissions {
+    /// Whether transactions of the account need the signatures of several keys, in
+    /// which case they can't be signed by a single signer
+    pub fn is_multisig(&self) -> bool {
+        !self.owner.signs_alone_any()
+    }
+}
+
+impl Permission {
+    /// Whether the key at `address` can sign under the permission on its own
+    pub fn signs_alone(&self, address: H256) -> bool {
+        self.keys
+            .iter()
+            .any(|key| key.address == address && key.weight >= self.threshold)
+    }
+
+    fn signs_alone_any(&self) -> bool {
+        self.keys.iter().any(|key| key.weight >= self.threshold)
+    }
+}
+
+/// Account as returned by `/wallet/getaccount`, limited to its permissions. Empty for
+/// an account that doesn't exist on chain.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct AccountPermissionsResponse {
+    #[serde(default)]
+    pub address: Option<TronAddress>,
+    /// Absent if never updated, in which case the account's own key is the owner
+    #[serde(default)]
+    pub owner_permission: Option<PermissionResponse>,
+    #[serde(default)]
+    pub active_permission: Vec<PermissionResponse>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct PermissionResponse {
+    #[serde(default)]
+    pub id: i32,
+    #[serde(default)]
+    pub permission_name: String,
+    pub threshold: u64,
+    #[serde(default)]
+    pub keys: Vec<PermissionKeyResponse>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct PermissionKeyResponse {
+    pub address: TronAddress,
+    pub weight: u64,
+}
+
+impl AccountPermissionsResponse {
+    /// Permissions of the account, `None` if it doesn't exist
+    pub fn into_permissions(self) -> Option<AccountPermissions> {
+        let address = self.address?;
+        let owner = self
+            .owner_permission
+            .map(Permission::from)
+            .unwrap_or(Permission {
+                id: 0,
+                name: "owner".to_owned(),
+                threshold: 1,
+                keys: vec![PermissionKey {
+                    address: address.into(),
+                    weight: 1,
+                }],
+            });
+
+        Some(AccountPermissions {
+            owner,
+            active: self
+                .active_permission
+                .into_iter()
+                .map(Permission::from)
+                .collect(),
+        })
+    }
+}
+
+impl From<PermissionResponse> for Permission {
+    fn from(permission: PermissionResponse) -> Self {
+        Self {
+            id: permission.id,
+            name: permission.permission_name,
+            threshold: permission.threshold,
+            keys: permission
+                .keys
+                .into_iter()
+                .map(|key| PermissionKey {
+                    address: key.address.into(),
+                    weight: key.weight,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Resource receipt of a transaction, as returned by `/wallet/gettransactioninfobyblocknum`
+/// and `/wallet/gettransactioninfobyid`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct TransactionInfoReceipt {
+    #[serde(default)]
+    pub receipt: ResourceReceipt,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ResourceReceipt {
+    #[serde(default)]
+    pub energy_usage_total: u64,
+    /// Bandwidth consumed from the staked or free allowance
+    #[serde(default)]
+    pub net_usage: u64,
+    /// TRX (sun) burnt to pay for bandwidth
+    #[serde(default)]
+    pub net_fee: u64,
+}
+
+/// Transactions of a contract as returned by the TronGrid `/v1/contracts/{address}/transactions` API
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ContractTransactionsResponse {
+    #[serde(default)]
+    pub data: Vec<ContractTransaction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ContractTransaction {
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+    pub raw_data: ContractTransactionRawData,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ContractTransactionRawData {
+    #[serde(default)]
+    pub contract: Vec<ContractTransactionCall>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ContractTransactionCall {
+    #[serde(rename = "type")]
+    pub call_type: String,
+}
+
+/// Block of a transaction as returned by `/wallet/gettransactioninfobyid`, absent if the
+/// transaction isn't in a block
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct TransactionInfoBlock {
+    #[serde(default, rename = "blockNumber")]
+    pub block_number: Option<u64>,
+}
+
+/// Contract as returned by `/wallet/getcontract`, empty if there is no contract at
+/// the address
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ContractResponse {
+    #[serde(default)]
+    pub bytecode: Option<String>,
+    /// ABI stored on chain at deployment, absent if deployed without one
+    #[serde(default)]
+    pub abi: Option<serde_json::Value>,
+}
+
+/// Outcome of `/wallet/broadcasttransaction`
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct BroadcastResponse {
+    /// Set if the node rejected the transaction
+    #[serde(default)]
+    pub code: Option<String>,
+    /// Hex encoded reason of the rejection
+    #[serde(default)]
+    pub message: String,
+    pub txid: TransactionId,
+}
+
+/// Contract call executed by the node through `/wallet/triggerconstantcontract`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ConstantCallResponse {
+    /// Set if the node couldn't execute the call at all
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub result: ConstantCallResult,
+    #[serde(default)]
+    pub energy_used: u64,
+    /// Hex encoded return data, or revert data if the call reverted
+    #[serde(default)]
+    pub constant_result: Vec<String>,
+}
+
+/// Outcome of the execution of a constant call
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ConstantCallResult {
+    /// Set if the call failed, e.g. `CONTRACT_EXE_ERROR` for a revert
+    #[serde(default)]
+    pub code: Option<String>,
+    /// Hex encoded description of the failure
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl ConstantCallResponse {
+    /// Reason the call failed, decoded from its revert data if any, `None` if it
+    /// succeeded
+    pub fn revert_reason(&self) -> Option<String> {
+        let code = self.result.code.as_ref()?;
+        let data = self
+            .constant_result
+            .first()
+            .and_then(|data| hex::decode(data).ok())
+            .unwrap_or_default();
+        if !data.is_empty() {
+            return Some(decode_revert_reason(&data));
+        }
+
+        let message = self
+            .result
+            .message
+            .as_ref()
+            .and_then(|message| hex::decode(message).ok())
+            .map(|message| String::from_utf8_lossy(&message).into_owned());
+        Some(message.unwrap_or_else(|| code.clone()))
+    }
+}
+
+/// Transaction built by the node for a contract call
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct TriggerContractResponse {
+    pub transaction: Transaction,
+}
+
+/// Transactions of an account as returned by the TronGrid `/v1/accounts/{address}/transactions` API
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct AccountTransactionsResponse {
+    #[serde(default)]
+    pub data: Vec<AccountTransaction>,
+    #[serde(default = "default_success")]
+    pub success: bool,
+    /// Reason of the failure of the query, if not successful
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub meta: Option<EventsPage>,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct AccountTransaction {
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+}
+
+impl AccountTransactionsResponse {
+    /// Whether the query failed because the account doesn't exist on chain, as it
+    /// hasn't been activated yet
+    pub fn is_account_not_found(&self) -> bool {
+        !self.success
+            && self
+                .error
+                .as_deref()
+                .is_some_and(|error| error.to_lowercase().contains("not found"))
+    }
+}
+
+/// Events of a contract as returned by the TronGrid `/v1/contracts/{address}/events` API
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct ContractEventsResponse {
+    #[serde(default)]
+    pub data: Vec<ContractEvent>,
+    #[serde(default)]
+    pub meta: Option<EventsPage>,
+}
+
+/// Pagination of the TronGrid events and account transactions APIs
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct EventsPage {
+    /// Cursor of the next page, absent on the last one
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+}
+
+/// Event emitted by a contract, as indexed by the TronGrid event server
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractEvent {
+    /// Id of the transaction emitting the event, as hex without prefix
+    pub transaction_id: String,
+    pub block_number: u64,
+    /// Time (ms) of the block
+    pub block_timestamp: u64,
+    /// Index of the event among those of the transaction
+    #[serde(default)]
+    pub event_index: u32,
+    pub event_name: String,
+    /// Decoded event arguments, by name and position
+    #[serde(default)]
+    pub result: serde_json::Value,
+}
+
+impl ContractEvent {
+    /// Address argument `name` of the event, as base58 or hex depending on the event
+    /// server. `None` if absent or not an address.
+    pub fn address_arg(&self, name: &str) -> Option<H256> {
+        let arg = self.result.get(name)?.as_str()?;
+        TronAddress::parse_any(arg).ok().map(Into::into)
+    }
+}
+
+impl ContractTransactionsResponse {
+    /// Id of the transaction deploying the contract, if among the transactions
+    pub fn creation_tx_id(&self) -> Option<&str> {
+        self.data
+            .iter()
+            .find(|tx| {
+                tx.raw_data
+                    .contract
+                    .iter()
+                    .any(|call| call.call_type == "CreateSmartContract")
+            })
+            .map(|tx| tx.tx_id.as_str())
+    }
+}
+
+/// Resources delegated from one account to another, as returned by
+/// `/wallet/getdelegatedresourcev2`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct DelegatedResourceResponse {
+    #[serde(default, rename = "delegatedResource")]
+    pub delegated_resource: Vec<DelegatedResource>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct DelegatedResource {
+    /// TRX (sun) staked for energy, whose energy is delegated
+    #[serde(default)]
+    pub frozen_balance_for_energy: u64,
+}
+
+impl DelegatedResourceResponse {
+    /// TRX (sun) staked for the delegated energy
+    pub fn staked_for_energy(&self) -> u64 {
+        self.delegated_resource
+            .iter()
+            .map(|resource| resource.frozen_balance_for_energy)
+            .sum()
+    }
+}
+
+/// Parses a `/wallet/getblockbyid` or `/wallet/getblockbynum` response. The node
+/// answers with an empty object when it doesn't know the block.
+pub(crate) fn parse_optional_block_response(
+    value: serde_json::Value,
+) -> Result<Option<Block>, serde_json::Error> {
+    match value.as_object() {
+        Some(object) if object.is_empty() => Ok(None),
+        _ => serde_json::from_value(value).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_energy_fee_history() {
+        let resp: ListProposalsResponse = serde_json::from_str(
+            r#"{
+                "proposals": [
+                    {
+                        "proposal_id": 90,
+                        "proposer_address": "41d25855804e4e65de904faf3ac74b0bdfc53fac76",
+                        "parameters": [{"key": 11, "value": 420}, {"key": 19, "value": 90000000000}],
+                        "expiration_time": 1694588400000,
+                        "create_time": 1694329557000,
+                        "approvals": ["41d25855804e4e65de904faf3ac74b0bdfc53fac76"],
+                        "state": "APPROVED"
+                    },
+                    {
+                        "proposal_id": 87,
+                        "proposer_address": "41d25855804e4e65de904faf3ac74b0bdfc53fac76",
+                        "parameters": [{"key": 11, "value": 210}],
+                        "expiration_time": 1680667200000,
+                        "create_time": 1680422400000,
+                        "state": "APPROVED"
+                    },
+                    {
+                        "proposal_id": 89,
+                        "proposer_address": "41d25855804e4e65de904faf3ac74b0bdfc53fac76",
+                        "parameters": [{"key": 11, "value": 1000}],
+                        "expiration_time": 1690000000000,
+                        "create_time": 1689700000000,
+                        "state": "DISAPPROVED"
+                    },
+                    {
+                        "proposal_id": 88,
+                        "proposer_address": "41d25855804e4e65de904faf3ac74b0bdfc53fac76",
+                        "parameters": [{"key": 47, "value": 15000000000}],
+                        "expiration_time": 1685000000000,
+                        "create_time": 1684700000000,
+                        "state": "APPROVED"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            resp.energy_fee_history(),
+            vec![(1680667200000, 210), (1694588400000, 420)]
+        );
+    }
+
+    #[test]
+    fn test_tx_result_from_contract_ret() {
+        for (contract_ret, result) in [
+            ("SUCCESS", TronTxResult::Success),
+            ("REVERT", TronTxResult::Revert),
+            ("OUT_OF_ENERGY", TronTxResult::OutOfEnergy),
+            ("OUT_OF_TIME", TronTxResult::OutOfTime),
+            (
+                "BAD_JUMP_DESTINATION",
+                TronTxResult::Unknown("BAD_JUMP_DESTINATION".to_owned()),
+            ),
+        ] {
+            assert_eq!(TronTxResult::from(contract_ret), result);
+            assert_eq!(result.to_string(), contract_ret);
+        }
+    }
+
+    #[test]
+    fn test_energy_fee_history_empty_response() {
+        let resp: ListProposalsResponse = serde_json::from_str("{}").unwrap();
+        assert!(resp.energy_fee_history().is_empty());
+    }
+
+    #[test]
+    fn test_parse_optional_block_response() {
+        let value = serde_json::json!({
+            "blockID": "0000000003d0e8a8e1d2a7ea0bae2d8e37da1a8abf28ba6e4d1fe3b8cfb6b0f5",
+            "block_header": {
+                "raw_data": {
+                    "number": 64000168,
+                    "txTrieRoot": "0000000000000000000000000000000000000000000000000000000000000000",
+                    "witness_address": "41d25855804e4e65de904faf3ac74b0bdfc53fac76",
+                    "parentHash": "0000000003d0e8a7b5e4d2bd5e5a2a3ac8ebc6a43bf3e5f5a8d2b3f1f1e2d3c4",
+                    "version": 30,
+                    "timestamp": 1722858393000u64
+                },
+                "witness_signature": "aa"
+            }
+        });
+
+        let block = parse_optional_block_response(value).unwrap().unwrap();
+
+        assert_eq!(block.block_number(), 64000168);
+        assert_eq!(block.block_header.raw_data.timestamp, 1722858393000);
+        assert_eq!(
+            block.block_id.to_string(),
+            "0000000003d0e8a8e1d2a7ea0bae2d8e37da1a8abf28ba6e4d1fe3b8cfb6b0f5"
+        );
+    }
+
+    #[test]
+    fn test_parse_optional_block_response_not_found() {
+        assert!(parse_optional_block_response(serde_json::json!({}))
+            .unwrap()
+            .is_none());
+        assert!(parse_optional_block_response(serde_json::json!({"blockID": "zz"})).is_err());
+    }
+
+    #[test]
+    fn test_block_resource_usage() {
+        let tx_infos: Vec<TransactionInfoReceipt> = serde_json::from_str(
+            r#"[
+                {
+                    "id": "9a8b5fb1d1f5cf7c9c3f5b1e7d7c7a6a1f3e3a2c0c2d9a4d6e5f1b2c3d4e5f60",
+                    "fee": 13844850,
+                    "blockNumber": 64000168,
+                    "blockTimeStamp": 1722858393000,
+                    "contract_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                    "receipt": {
+                        "energy_fee": 13499850,
+                        "energy_usage_total": 64285,
+                        "net_fee": 345000,
+                        "result": "SUCCESS"
+                    }
+                },
+                {
+                    "id": "1b8b5fb1d1f5cf7c9c3f5b1e7d7c7a6a1f3e3a2c0c2d9a4d6e5f1b2c3d4e5f61",
+                    "blockNumber": 64000168,
+                    "blockTimeStamp": 1722858393000,
+                    "receipt": {"net_usage": 268}
+                },
+                {
+                    "id": "2b8b5fb1d1f5cf7c9c3f5b1e7d7c7a6a1f3e3a2c0c2d9a4d6e5f1b2c3d4e5f62",
+                    "blockNumber": 64000168,
+                    "blockTimeStamp": 1722858393000,
+                    "receipt": {
+                        "energy_usage": 30000,
+                        "energy_usage_total": 30000,
+                        "net_usage": 345,
+                        "result": "SUCCESS"
+                    }
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let usage = BlockResourceUsage::from_tx_infos(64000168, &tx_infos, 180_000_000_000, 1000);
+
+        assert_eq!(
+            usage,
+            BlockResourceUsage {
+                block_number: 64000168,
+                energy_used: 94285,
+                bandwidth_used: 345 + 268 + 345,
+                energy_budget: 6_250_000,
+            }
+        );
+        assert!(usage.energy_utilization() > 0.015 && usage.energy_utilization() < 0.016);
+    }
+
+    #[test]
+    fn test_block_resource_usage_empty_block() {
+        let usage = BlockResourceUsage::from_tx_infos(1, &[], 0, 0);
+
+        assert_eq!(usage.energy_used, 0);
+        assert_eq!(usage.bandwidth_used, 0);
+        assert_eq!(usage.energy_utilization(), 0.);
+    }
+
+    #[test]
+    fn test_contract_creation_tx_id() {
+        let resp: ContractTransactionsResponse = serde_json::from_str(
+            r#"{
+                "data": [
+                    {
+                        "ret": [{"contractRet": "SUCCESS", "fee": 0}],
+                        "signature": ["c6d7c1b6e2b5b9d4a7b0a1a1f6a5e3b9d4c2e1f0a9b8c7d6e5f4a3b2c1d0e9f801"],
+                        "txID": "3e7a3c3a1c7f8c3e4a1b6d0f5e2c9b8a7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b2a",
+                        "net_usage": 0,
+                        "raw_data_hex": "0a02",
+                        "net_fee": 2871000,
+                        "energy_usage": 0,
+                        "blockNumber": 58723412,
+                        "block_timestamp": 1706102949000,
+                        "energy_fee": 517325820,
+                        "energy_usage_total": 1231728,
+                        "raw_data": {
+                            "contract": [
+                                {
+                                    "parameter": {
+                                        "value": {
+                                            "owner_address": "41d25855804e4e65de904faf3ac74b0bdfc53fac76",
+                                            "new_contract": {"name": "Mailbox"}
+                                        },
+                                        "type_url": "type.googleapis.com/protocol.CreateSmartContract"
+                                    },
+                                    "type": "CreateSmartContract"
+                                }
+                            ],
+                            "ref_block_bytes": "1b2c",
+                            "ref_block_hash": "5e4d3c2b1a090807",
+                            "expiration": 1706103006000,
+                            "fee_limit": 1000000000,
+                            "timestamp": 1706102946000
+                        },
+                        "internal_transactions": []
+                    }
+                ],
+                "success": true,
+                "meta": {"at": 1722858393000, "page_size": 1}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            resp.creation_tx_id(),
+            Some("3e7a3c3a1c7f8c3e4a1b6d0f5e2c9b8a7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b2a")
+        );
+    }
+
+    #[test]
+    fn test_contract_creation_tx_id_not_found() {
+        let resp: ContractTransactionsResponse = serde_json::from_str(
+            r#"{
+                "data": [
+                    {
+                        "txID": "3e7a3c3a1c7f8c3e4a1b6d0f5e2c9b8a7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b2a",
+                        "raw_data": {"contract": [{"type": "TriggerSmartContract"}]}
+                    }
+                ],
+                "success": true
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(resp.creation_tx_id(), None);
+
+        let resp: ContractTransactionsResponse =
+            serde_json::from_str(r#"{"data": [], "success": true}"#).unwrap();
+        assert_eq!(resp.creation_tx_id(), None);
+    }
+
+    #[test]
+    fn test_contract_event_address_arg() {
+        let event: ContractEvent = serde_json::from_value(serde_json::json!({
+            "transaction_id": "00".repeat(32),
+            "block_number": 1,
+            "block_timestamp": 1722858393000u64,
+            "event_name": "ValidatorAnnouncement",
+            "result": {
+                "0": "0xa614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                "validator": "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t",
+                "storageLocation": "s3://bucket"
+            }
+        }))
+        .unwrap();
+
+        let validator = event.address_arg("validator").unwrap();
+        assert_eq!(event.address_arg("0"), Some(validator));
+        assert_eq!(
+            validator,
+            H256::from_slice(
+                &hex::decode(format!(
+                    "{}a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                    "00".repeat(12)
+                ))
+                .unwrap()
+            )
+        );
+        assert_eq!(event.address_arg("storageLocation"), None);
+        assert_eq!(event.address_arg("missing"), None);
+    }
+}
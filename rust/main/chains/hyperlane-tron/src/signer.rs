@@ -3,9 +3,22 @@ use std::{
     sync::Arc,
 };
 
-use heliosphere_signer::{k256::ecdsa::SigningKey, keypair::Keypair, signer::Signer as _};
+use ethers::signers::{coins_bip39::English, MnemonicBuilder};
+use ethers::types::{Signature as EthersSignature, H256 as EthersH256};
+use heliosphere_signer::{
+    k256::ecdsa::{signature::digest::Digest, SigningKey},
+    keypair::Keypair,
+    signer::{hash_message, Signer as _},
+};
+
+use hyperlane_core::{
+    ChainCommunicationError, ChainResult, CheckpointWithMessageId, Signable, Signature, H256,
+};
 
-use crate::HyperlaneTronError;
+use crate::{HyperlaneTronError, TronAddress};
+
+/// BIP-44 derivation path of Tron accounts (coin type 195), without the index
+const TRON_DERIVATION_PATH_PREFIX: &str = "m/44'/195'/0'/0/";
 
 #[derive(Clone)]
 /// Signer for tron chain
@@ -19,9 +32,37 @@ impl Signer {
         Ok(Signer(Arc::new(key_pair)))
     }
 
+    /// Derives the `account_index`-th account of the mnemonic, at the
+    /// `m/44'/195'/0'/0/{account_index}` path used by Tron wallets
+    pub fn from_mnemonic_indexed(
+        phrase: &str,
+        account_index: u32,
+    ) -> Result<Self, HyperlaneTronError> {
+        let wallet = MnemonicBuilder::<English>::default()
+            .phrase(phrase)
+            .derivation_path(&format!("{TRON_DERIVATION_PATH_PREFIX}{account_index}"))?
+            .build()?;
+
+        Self::from_bytes(&wallet.signer().to_bytes())
+    }
+
     pub fn address(&self) -> String {
         self.0.address().as_base58()
     }
+
+    /// Signs `msg` the way Tron wallets sign messages, i.e. the keccak256 hash of
+    /// `msg` prefixed with `\x19TRON Signed Message:\n` and its length, rather than
+    /// the `\x19Ethereum Signed Message:\n` prefix of EIP-191. Returns `r || s || v`
+    /// with `v` being 27 or 28, as TronWeb's `verifyMessageV2` expects.
+    pub fn sign_message(&self, msg: &[u8]) -> Result<[u8; 65], HyperlaneTronError> {
+        let hash = hash_message(msg).finalize();
+        let (signature, recovery_id) = self.0.sign_prehash(&hash)?;
+
+        let mut signed = [0u8; 65];
+        signed[..64].copy_from_slice(&signature.to_bytes());
+        signed[64] = 27 + recovery_id.to_byte();
+        Ok(signed)
+    }
 }
 
 impl Debug for Signer {
@@ -29,3 +70,126 @@ impl Debug for Signer {
         f.write_str("Signer { ... }")
     }
 }
+
+/// Whether `signature` of the EIP-191 hash of `checkpoint` recovers to the validator
+/// `expected`, as announced, i.e. the Tron address of the signing key without its
+/// 0x41 prefix. Fails if the signature is malformed.
+pub fn verify_checkpoint_signature(
+    checkpoint: &CheckpointWithMessageId,
+    signature: &Signature,
+    expected: H256,
+) -> ChainResult<bool> {
+    let serialized_signature: [u8; 65] = (*signature).into();
+    let signature = EthersSignature::try_from(&serialized_signature[..])
+        .map_err(ChainCommunicationError::from_other)?;
+    let hash = EthersH256::from(checkpoint.eth_signed_message_hash().0);
+
+    let signer = signature
+        .recover(hash)
+        .map_err(ChainCommunicationError::from_other)?;
+    Ok(H256::from(TronAddress::from(signer)) == expected)
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::signers::LocalWallet;
+    use hyperlane_core::{Checkpoint, U256};
+
+    use super::*;
+
+    const PHRASE: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_from_mnemonic_indexed() {
+        let first = Signer::from_mnemonic_indexed(PHRASE, 0).unwrap();
+        let second = Signer::from_mnemonic_indexed(PHRASE, 1).unwrap();
+
+        assert_eq!(first.address(), "TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH");
+        assert_eq!(second.address(), "TSeJkUh4Qv67VNFwY8LaAxERygNdy6NQZK");
+    }
+
+    fn checkpoint(index: u32) -> CheckpointWithMessageId {
+        CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook_address: H256::from_low_u64_be(0xaa),
+                mailbox_domain: 728126428,
+                root: H256::repeat_byte(0x11),
+                index,
+            },
+            message_id: H256::repeat_byte(0x22),
+        }
+    }
+
+    /// Signature of `checkpoint` by the key `[1; 32]`, as validators sign them
+    fn sign(checkpoint: &CheckpointWithMessageId) -> Signature {
+        let wallet = LocalWallet::from_bytes(&[1; 32]).unwrap();
+        let hash = EthersH256::from(checkpoint.eth_signed_message_hash().0);
+        let signature = wallet.sign_hash(hash).unwrap();
+
+        let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+        signature.r.to_big_endian(&mut r);
+        signature.s.to_big_endian(&mut s);
+        Signature {
+            r: U256::from_big_endian(&r),
+            s: U256::from_big_endian(&s),
+            v: signature.v,
+        }
+    }
+
+    #[test]
+    fn test_verify_checkpoint_signature() {
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let validator =
+            H256::from_slice(&[&[0u8; 12][..], &signer.0.address().as_bytes()[1..]].concat());
+        let signature = sign(&checkpoint(7));
+
+        assert!(verify_checkpoint_signature(&checkpoint(7), &signature, validator).unwrap());
+        // Signed by another validator
+        assert!(
+            !verify_checkpoint_signature(&checkpoint(7), &signature, H256::from_low_u64_be(1))
+                .unwrap()
+        );
+        // Signature of another checkpoint
+        assert!(!verify_checkpoint_signature(&checkpoint(8), &signature, validator).unwrap());
+    }
+
+    #[test]
+    fn test_sign_message() {
+        let signer = Signer::from_bytes(&[1; 32]).unwrap();
+        let msg = b"hello tron";
+
+        let signed = signer.sign_message(msg).unwrap();
+        assert_eq!(
+            ethers::utils::hex::encode(signed),
+            "be31fcce7bbc0d7e21b46fab8bb79ecfdf65f5efe0c5aa56bbf9f2555533c05c\
+             137afd85d6ef4fe2959c4957efda4151a272a64dba229503f93f3fd9cf19320f1b"
+        );
+
+        // Recovers to the signer from the hash of the Tron-prefixed message
+        let prefixed = [&b"\x19TRON Signed Message:\n10"[..], msg].concat();
+        let signature = EthersSignature::try_from(&signed[..]).unwrap();
+        let recovered = signature
+            .recover(EthersH256::from(ethers::utils::keccak256(prefixed)))
+            .unwrap();
+        assert_eq!(
+            TronAddress::from(recovered).as_ref().as_base58(),
+            signer.address()
+        );
+
+        // Not a valid EIP-191 signature of the message
+        let recovered = signature.recover(&msg[..]).unwrap();
+        assert_ne!(
+            TronAddress::from(recovered).as_ref().as_base58(),
+            signer.address()
+        );
+    }
+
+    #[test]
+    fn test_from_mnemonic_indexed_invalid_phrase() {
+        assert!(matches!(
+            Signer::from_mnemonic_indexed("not a mnemonic", 0),
+            Err(HyperlaneTronError::WalletError(_))
+        ));
+    }
+}
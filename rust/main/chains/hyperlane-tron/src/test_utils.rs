@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use ethers::contract::EthCall;
+use ethers::types::{H160 as EthersH160, H256 as EthersH256};
+use ethers::utils::hex;
+use heliosphere_core::transaction::TransactionId;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use url::Url;
+
+use hyperlane_core::{ContractLocator, HyperlaneDomain, KnownHyperlaneDomain, H256};
+
+use crate::interfaces::i_mailbox::{DefaultIsmCall, NonceCall, RecipientIsmCall};
+use crate::{
+    ConnectionConf, HyperlaneTronError, Signer, TronMailbox, TronProvider, TronRpc, TxStatus,
+};
+
+type Handler = dyn Fn(&str, serde_json::Value) -> serde_json::Value + Send + Sync;
+
+/// Minimal HTTP/1.1 node answering JSON requests with a handler taking the
+/// request path and body. Connections are kept alive and counted, and the API
+/// keys the requests carry are recorded.
+pub(crate) struct MockTronNode {
+    pub url: Url,
+    connections: Arc<AtomicUsize>,
+    api_keys: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+impl MockTronNode {
+    pub async fn start(
+        handler: impl Fn(&str, serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let api_keys = Arc::new(Mutex::new(vec![]));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let accepted = connections.clone();
+        let received_keys = api_keys.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let handler = handler.clone();
+                let received_keys = received_keys.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    while let Some((path, body, api_key)) = read_request(&mut stream).await {
+                        received_keys.lock().unwrap().push(api_key);
+                        let resp = handler(&path, body).to_string();
+                        let resp = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                            resp.len(),
+                            resp
+                        );
+                        if stream.get_mut().write_all(resp.as_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        Self {
+            url,
+            connections,
+            api_keys,
+        }
+    }
+
+    /// Number of TCP connections accepted so far
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// API key header of each request received so far
+    pub fn api_keys(&self) -> Vec<Option<String>> {
+        self.api_keys.lock().unwrap().clone()
+    }
+
+    pub fn conf(&self) -> ConnectionConf {
+        conf(self.url.clone())
+    }
+
+    pub fn provider(&self) -> TronProvider {
+        provider(self.conf())
+    }
+}
+
+/// [`TronRpc`] answering queries from canned responses, for logic that doesn't need
+/// the HTTP API of a node
+#[derive(Default)]
+pub(crate) struct MockTronRpc {
+    /// Statuses reported for transactions, one per query, the last one repeatedly
+    pub tx_statuses: Mutex<VecDeque<TxStatus>>,
+    /// Transactions whose status was queried, in order
+    pub tx_status_queries: Mutex<Vec<TransactionId>>,
+}
+
+impl MockTronRpc {
+    pub fn with_tx_statuses(statuses: impl IntoIterator<Item = TxStatus>) -> Self {
+        Self {
+            tx_statuses: Mutex::new(statuses.into_iter().collect()),
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl TronRpc for MockTronRpc {
+    async fn get_tx_status(&self, txid: TransactionId) -> Result<TxStatus, HyperlaneTronError> {
+        self.tx_status_queries.lock().unwrap().push(txid);
+        let mut statuses = self.tx_statuses.lock().unwrap();
+        let status = match statuses.len() {
+            0 => TxStatus::Unknown,
+            1 => statuses[0].clone(),
+            _ => statuses.pop_front().unwrap(),
+        };
+        Ok(status)
+    }
+}
+
+/// Connection configuration with default settings
+pub(crate) fn conf(url: Url) -> ConnectionConf {
+    ConnectionConf::new(url)
+}
+
+pub(crate) fn provider(conf: ConnectionConf) -> TronProvider {
+    TronProvider::new(HyperlaneDomain::Known(KnownHyperlaneDomain::Test1), conf).unwrap()
+}
+
+/// Block as returned by the node, without transactions
+pub(crate) fn block(number: u64) -> serde_json::Value {
+    serde_json::json!({
+        "blockID": format!("{number:016x}{}", "00".repeat(24)),
+        "block_header": {
+            "raw_data": {
+                "number": number,
+                "txTrieRoot": "00".repeat(32),
+                "witness_address": "41928c9af0651632157ef27a2cf17ca72c575a4d21",
+                "parentHash": "00".repeat(32),
+                "version": 30,
+                "timestamp": 1722858393000u64
+            },
+            "witness_signature": ""
+        }
+    })
+}
+
+/// Transaction as returned by the node, with an empty raw data
+pub(crate) fn transaction(txid: &str) -> serde_json::Value {
+    serde_json::json!({
+        "signature": [],
+        "txID": txid,
+        "raw_data": {
+            "contract": [],
+            "ref_block_bytes": "c3d2",
+            "ref_block_hash": "1f9b3b8f3c7e4e2a",
+            "expiration": 1722858453000u64,
+            "fee_limit": 150000000,
+            "timestamp": 1722858393000u64
+        },
+        "raw_data_hex": "0a02c3d2"
+    })
+}
+
+/// Response of `/wallet/getchainparameters` with the given energy fee, in sun
+pub(crate) fn chain_parameters(energy_fee: u64) -> serde_json::Value {
+    serde_json::json!({
+        "chainParameter": [{"key": "getEnergyFee", "value": energy_fee}]
+    })
+}
+
+/// Successful response of `/wallet/triggerconstantcontract` returning nothing
+pub(crate) fn constant_call(energy_used: u64) -> serde_json::Value {
+    serde_json::json!({
+        "result": {"result": true},
+        "energy_used": energy_used,
+        "constant_result": [""]
+    })
+}
+
+/// Response of `/wallet/triggersmartcontract` building the transaction `txid`
+pub(crate) fn triggered_transaction(txid: &str) -> serde_json::Value {
+    serde_json::json!({"transaction": transaction(txid)})
+}
+
+/// Response of `/wallet/broadcasttransaction` accepting the transaction `txid`
+pub(crate) fn broadcast_result(txid: impl Into<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({"result": true, "txid": txid.into()})
+}
+
+/// Response of a node sending contract calls at an energy fee of 210 sun: the chain
+/// parameters, the transaction `txid` built for the call, and its acceptance when
+/// broadcast. Other requests get an empty object, e.g. for a transaction that isn't
+/// confirmed yet.
+pub(crate) fn send_call_response(
+    path: &str,
+    body: &serde_json::Value,
+    txid: &str,
+) -> serde_json::Value {
+    match path {
+        "/wallet/getchainparameters" => chain_parameters(210),
+        "/wallet/triggersmartcontract" => triggered_transaction(txid),
+        "/wallet/broadcasttransaction" => broadcast_result(body["txID"].clone()),
+        _ => serde_json::json!({}),
+    }
+}
+
+/// JSON-RPC response to `request` with `result`
+pub(crate) fn json_rpc_result(
+    request: &serde_json::Value,
+    result: impl Into<serde_json::Value>,
+) -> serde_json::Value {
+    serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": result.into()})
+}
+
+/// Log of the contract at the zero address, emitted in transaction `0xcc..cc` at
+/// block 100
+pub(crate) fn json_rpc_log(topics: &[String], data: &str) -> serde_json::Value {
+    serde_json::json!({
+        "address": format!("{:#x}", EthersH160::zero()),
+        "topics": topics,
+        "data": data,
+        "blockNumber": "0x64",
+        "blockHash": format!("{:#x}", EthersH256::repeat_byte(0xbb)),
+        "transactionHash": format!("{:#x}", EthersH256::repeat_byte(0xcc)),
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false
+    })
+}
+
+/// Transaction `hash` from `0xaa` to the contract at the zero address with `input`,
+/// included at block 100
+pub(crate) fn json_rpc_transaction(hash: &str, input: &str) -> serde_json::Value {
+    serde_json::json!({
+        "hash": hash,
+        "nonce": "0x0",
+        "blockHash": format!("{:#x}", EthersH256::repeat_byte(0xbb)),
+        "blockNumber": "0x64",
+        "transactionIndex": "0x0",
+        "from": format!("{:#x}", EthersH160::from_low_u64_be(0xaa)),
+        "to": format!("{:#x}", EthersH160::zero()),
+        "value": "0x0",
+        "gasPrice": "0xd2",
+        "gas": "0x186a0",
+        "input": input,
+        "v": "0x1b",
+        "r": "0x1",
+        "s": "0x1"
+    })
+}
+
+/// Receipt of the successful transaction `hash` from `0xaa` to the contract at the
+/// zero address, included at block 100
+pub(crate) fn json_rpc_receipt(hash: &str, logs: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "transactionHash": hash,
+        "transactionIndex": "0x0",
+        "blockHash": format!("{:#x}", EthersH256::repeat_byte(0xbb)),
+        "blockNumber": "0x64",
+        "from": format!("{:#x}", EthersH160::from_low_u64_be(0xaa)),
+        "to": format!("{:#x}", EthersH160::zero()),
+        "cumulativeGasUsed": "0x0",
+        "gasUsed": "0x0",
+        "contractAddress": null,
+        "status": "0x1",
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "logs": logs
+    })
+}
+
+/// Mailbox at the zero address on the test domain
+pub(crate) fn mailbox(conf: ConnectionConf, signer: Option<Signer>) -> TronMailbox {
+    let domain = HyperlaneDomain::Known(KnownHyperlaneDomain::Test1);
+    TronMailbox::new(conf, ContractLocator::new(&domain, H256::zero()), signer).unwrap()
+}
+
+/// Mailbox behind a node answering `recipientIsm` calls with `recipient_ism` and
+/// `defaultIsm` calls with `0xdd`
+pub(crate) async fn mailbox_with_recipient_ism(recipient_ism: H256) -> (TronMailbox, MockTronNode) {
+    let node = MockTronNode::start(move |_, body| {
+        let data = body["params"][0]["data"].as_str().unwrap();
+        let selector = |selector: [u8; 4]| format!("0x{}", hex::encode(selector));
+        let ism = if data.starts_with(&selector(RecipientIsmCall::selector())) {
+            recipient_ism
+        } else if data.starts_with(&selector(DefaultIsmCall::selector())) {
+            H256::from_low_u64_be(0xdd)
+        } else {
+            panic!("unexpected call {data}");
+        };
+        json_rpc_result(&body, format!("{ism:#x}"))
+    })
+    .await;
+    let mailbox = mailbox(node.conf(), None);
+
+    (mailbox, node)
+}
+
+/// Mailbox behind a node answering `nonce` calls with `nonce` and any other view
+/// with a valid word
+pub(crate) async fn mailbox_with_nonce(nonce: String) -> (TronMailbox, MockTronNode) {
+    let node = MockTronNode::start(move |_, body| {
+        let data = body["params"][0]["data"].as_str().unwrap();
+        let result = if data == format!("0x{}", hex::encode(NonceCall::selector())) {
+            nonce.clone()
+        } else {
+            format!("{:#x}", H256::from_low_u64_be(1))
+        };
+        json_rpc_result(&body, result)
+    })
+    .await;
+    let mailbox = mailbox(node.conf(), None);
+
+    (mailbox, node)
+}
+
+async fn read_request<R: AsyncBufReadExt + Unpin>(
+    stream: &mut R,
+) -> Option<(String, serde_json::Value, Option<String>)> {
+    let mut line = String::new();
+    stream.read_line(&mut line).await.ok().filter(|n| *n > 0)?;
+    let path = line.split_whitespace().nth(1)?.to_owned();
+
+    let mut content_length = 0;
+    let mut api_key = None;
+    loop {
+        line.clear();
+        stream.read_line(&mut line).await.ok().filter(|n| *n > 0)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            } else if name.eq_ignore_ascii_case(crate::TRON_API_KEY_HEADER) {
+                api_key = Some(value.trim().to_owned());
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await.ok()?;
+    let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+
+    Some((path, body, api_key))
+}
@@ -412,6 +412,7 @@ impl ChainConf {
                     locator,
                     self.reorg_period.clone(),
                 )?);
+                indexer.ensure_reorg_period_supported().await?;
                 Ok(indexer as Box<dyn SequenceAwareIndexer<HyperlaneMessage>>)
             }
         }
@@ -470,6 +471,7 @@ impl ChainConf {
                     locator,
                     self.reorg_period.clone(),
                 )?);
+                indexer.ensure_reorg_period_supported().await?;
                 Ok(indexer as Box<dyn SequenceAwareIndexer<H256>>)
             }
         }
@@ -632,6 +634,7 @@ impl ChainConf {
                     locator,
                     self.reorg_period.clone(),
                 )?);
+                indexer.ensure_reorg_period_supported().await?;
                 Ok(indexer as Box<dyn SequenceAwareIndexer<MerkleTreeInsertion>>)
             }
         }
@@ -765,8 +768,9 @@ impl ChainConf {
                 )?);
                 Ok(ism as Box<dyn MultisigIsm>)
             }
-            ChainConnectionConf::Tron(_) => {
-                todo!()
+            ChainConnectionConf::Tron(conf) => {
+                let ism = Box::new(h_tron::TronMultisigIsm::new(conf.clone(), locator)?);
+                Ok(ism as Box<dyn MultisigIsm>)
             }
         }
         .context(ctx)
@@ -1,7 +1,9 @@
-use eyre::eyre;
+use eyre::{eyre, Context};
 use hyperlane_sealevel::{
     HeliusPriorityFeeLevel, HeliusPriorityFeeOracleConfig, PriorityFeeOracleConfig,
 };
+use serde::Deserialize;
+use serde_json::Value;
 use url::Url;
 
 use h_eth::TransactionOverrides;
@@ -12,6 +14,7 @@ use hyperlane_core::{config::ConfigParsingError, HyperlaneDomainProtocol, Native
 use crate::settings::envs::*;
 use crate::settings::ChainConnectionConf;
 
+use super::flat_case::FlatCaseDeserializer;
 use super::{parse_base_and_override_urls, parse_cosmos_gas_price, ValueParser};
 
 #[allow(clippy::question_mark)] // TODO: `rustc` 1.80.1 clippy issue
@@ -186,6 +189,54 @@ fn build_sealevel_connection_conf(
     }
 }
 
+fn build_tron_connection_conf(
+    url: &Url,
+    reference_urls: &[Url],
+    chain: &ValueParser,
+    err: &mut ConfigParsingError,
+) -> Option<ChainConnectionConf> {
+    // The Tron options sit next to the other chain keys, and take their defaults
+    // from the deserialization of `h_tron::ConnectionConf`
+    let mut section = chain.val.clone();
+    if let Value::Object(entries) = &mut section {
+        entries.insert("url".to_owned(), url.as_str().into());
+        entries.insert(
+            "referenceurls".to_owned(),
+            reference_urls.iter().map(Url::as_str).collect(),
+        );
+    }
+    let mut conf = h_tron::ConnectionConf::deserialize(FlatCaseDeserializer(section))
+        .context("Invalid Tron connection config")
+        .take_err(err, || chain.cwp.clone())?;
+
+    // Each reference node in `rpcUrls` may carry its own `apiKey`
+    conf.reference_api_keys = chain
+        .chain(err)
+        .get_opt_key("rpcUrls")
+        .into_array_iter()
+        .map(|rpcs| {
+            rpcs.filter_map(|rpc| {
+                let url: Url = rpc
+                    .chain(err)
+                    .get_opt_key("http")
+                    .parse_from_str("Invalid url")
+                    .end()?;
+                let api_key = rpc.chain(err).get_opt_key("apiKey").parse_string().end()?;
+                Some((url, api_key.to_owned().into()))
+            })
+            .filter(|(url, _)| reference_urls.contains(url))
+            .collect()
+        })
+        .unwrap_or_default();
+
+    if let Err(e) = conf.validate() {
+        err.push(&chain.cwp + "rpcUrls", eyre!(e));
+        return None;
+    }
+
+    Some(ChainConnectionConf::Tron(conf))
+}
+
 fn parse_native_token(
     chain: &ValueParser,
     err: &mut ConfigParsingError,
@@ -379,9 +430,9 @@ pub fn build_connection_conf(
         HyperlaneDomainProtocol::Cosmos => {
             build_cosmos_connection_conf(rpcs, chain, err, operation_batch)
         }
-        HyperlaneDomainProtocol::Tron => rpcs
-            .iter()
-            .next()
-            .map(|url| ChainConnectionConf::Tron(h_tron::ConnectionConf { url: url.clone() })),
+        // Further urls are only checked against the first one for forks
+        HyperlaneDomainProtocol::Tron => rpcs.split_first().and_then(|(url, reference_urls)| {
+            build_tron_connection_conf(url, reference_urls, chain, err)
+        }),
     }
 }
@@ -0,0 +1,196 @@
+use convert_case::{Case, Casing};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Error, Map, Value};
+
+/// Deserializer of a raw config value into a type deriving `Deserialize`.
+///
+/// The loader flat-cases all keys, so struct fields are matched with the keys by
+/// their flat case. Values set through environment variables are strings, so
+/// numbers and booleans are parsed from strings too.
+pub struct FlatCaseDeserializer(pub Value);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),+) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0 {
+                Value::String(s) => visitor.$visit(s.parse().map_err(<Error as de::Error>::custom)?),
+                value => value.$method(visitor),
+            }
+        })+
+    };
+}
+
+impl<'de> Deserializer<'de> for FlatCaseDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(values) => {
+                let mut seq =
+                    SeqDeserializer::<_, Error>::new(values.into_iter().map(FlatCaseDeserializer));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(entries) => {
+                let mut map = MapDeserializer::<_, Error>::new(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, FlatCaseDeserializer(value))),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(FlatCaseDeserializer(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let entries = match self.0 {
+            Value::Object(entries) => entries,
+            value => return FlatCaseDeserializer(value).deserialize_any(visitor),
+        };
+        let entries: Map<String, Value> = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let flat_key = key.to_case(Case::Flat);
+                let field = fields
+                    .iter()
+                    .find(|field| field.to_case(Case::Flat) == flat_key);
+                (field.map_or(key, |field| field.to_string()), value)
+            })
+            .collect();
+        FlatCaseDeserializer(Value::Object(entries)).deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_parsed!(
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64
+    );
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for FlatCaseDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Conf {
+        max_log_range: Option<u32>,
+        wait_for_confirmation: bool,
+        #[serde(default)]
+        retry_policy: RetryPolicy,
+        reference_urls: Vec<String>,
+    }
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    struct RetryPolicy {
+        max_attempts: u32,
+        base_delay_ms: u64,
+    }
+
+    #[test]
+    fn test_deserialize_flat_cased_keys() {
+        let value = serde_json::json!({
+            "maxlogrange": 5000,
+            "waitforconfirmation": false,
+            "retrypolicy": {"maxattempts": 6},
+            "referenceurls": ["http://localhost:8090"],
+            "unknownkey": "ignored",
+        });
+
+        let conf = Conf::deserialize(FlatCaseDeserializer(value)).unwrap();
+
+        assert_eq!(
+            conf,
+            Conf {
+                max_log_range: Some(5000),
+                wait_for_confirmation: false,
+                retry_policy: RetryPolicy {
+                    max_attempts: 6,
+                    base_delay_ms: 0,
+                },
+                reference_urls: vec!["http://localhost:8090".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_values_from_strings() {
+        let value = serde_json::json!({
+            "maxlogrange": "5000",
+            "waitforconfirmation": "true",
+            "retrypolicy": {"basedelayms": "250"},
+            "referenceurls": [],
+        });
+
+        let conf = Conf::deserialize(FlatCaseDeserializer(value)).unwrap();
+
+        assert_eq!(conf.max_log_range, Some(5000));
+        assert!(conf.wait_for_confirmation);
+        assert_eq!(conf.retry_policy.base_delay_ms, 250);
+
+        let value = serde_json::json!({
+            "waitforconfirmation": "maybe",
+            "referenceurls": [],
+        });
+        assert!(Conf::deserialize(FlatCaseDeserializer(value)).is_err());
+    }
+}
@@ -32,6 +32,7 @@ pub use super::envs::*;
 pub use self::json_value_parser::ValueParser;
 
 mod connection_parser;
+mod flat_case;
 mod json_value_parser;
 
 const DEFAULT_CHUNK_SIZE: u32 = 1999;